@@ -1,3 +1,7 @@
+use crate::lineage::EntryLink;
+use crate::rephrase::{RephraseCandidate, RephraseJobMark};
+use crate::storage::get_data_file_path;
+use crate::transforms::code::looks_like_code;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -18,8 +22,17 @@ pub struct ClipboardEntry {
     pub char_count: usize,
     pub line_count: usize,
     pub has_formatting: bool,
-    pub content_type: String, // "text", "url", "email", etc.
+    pub content_type: String, // "text", "url", "email", "code", etc.
     pub preview: String,      // First 100 chars for quick display
+    // Latest queued rephrase of this entry, while pending and after it finishes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rephrase_job: Option<RephraseJobMark>,
+    // The entry this one was made from, and how
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<EntryLink>,
+    // Rephrase candidates of the same source that weren't chosen (yet)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rephrase_candidates: Vec<RephraseCandidate>,
 }
 
 impl ClipboardEntry {
@@ -32,26 +45,10 @@ impl ClipboardEntry {
             || content.contains('\t')
             || content.chars().any(|c| c.is_whitespace() && c != ' ');
 
-        // Detect content type
-        let content_type = if content.starts_with("http://") || content.starts_with("https://") {
-            "url".to_string()
-        } else if content.contains('@') && content.contains('.') && !content.contains('\n') {
-            "email".to_string()
-        } else if content
-            .chars()
-            .all(|c| c.is_numeric() || c.is_whitespace() || "-+().".contains(c))
-        {
-            "phone".to_string()
-        } else {
-            "text".to_string()
-        };
+        let content_type = detect_content_type(&content);
 
         // Create preview (first 100 chars)
-        let preview = if content.len() > 100 {
-            format!("{}...", &content[..97])
-        } else {
-            content.clone()
-        };
+        let preview = truncate_preview(&content, 100);
 
         ClipboardEntry {
             id,
@@ -64,6 +61,9 @@ impl ClipboardEntry {
             has_formatting,
             content_type,
             preview,
+            rephrase_job: None,
+            parent: None,
+            rephrase_candidates: Vec::new(),
         }
     }
 
@@ -74,6 +74,35 @@ impl ClipboardEntry {
     }
 }
 
+// Classify content as "url", "email", "phone", "code" or "text"
+pub fn detect_content_type(content: &str) -> String {
+    if content.starts_with("http://") || content.starts_with("https://") {
+        "url".to_string()
+    } else if content.contains('@') && content.contains('.') && !content.contains('\n') {
+        "email".to_string()
+    } else if content
+        .chars()
+        .all(|c| c.is_numeric() || c.is_whitespace() || "-+().".contains(c))
+    {
+        "phone".to_string()
+    } else if looks_like_code(content) {
+        "code".to_string()
+    } else {
+        "text".to_string()
+    }
+}
+
+// Shorten text to at most `max_chars` characters, ending with "..." when cut.
+// Counts chars rather than bytes so multi-byte text never splits mid-character.
+pub fn truncate_preview(text: &str, max_chars: usize) -> String {
+    if text.chars().count() > max_chars {
+        let kept: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        format!("{}...", kept)
+    } else {
+        text.to_string()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ClipboardHistory {
     pub entries: Vec<ClipboardEntry>,
@@ -88,9 +117,23 @@ impl ClipboardHistory {
         }
     }
 
-    pub fn add_entry(&mut self, entry: ClipboardEntry) {
-        // Remove duplicate if content already exists
-        self.entries.retain(|e| e.content != entry.content);
+    pub fn add_entry(&mut self, mut entry: ClipboardEntry) {
+        // Remove duplicate if content already exists, passing its lineage to the new entry
+        if let Some(index) = self.entries.iter().position(|e| e.content == entry.content) {
+            let duplicate = self.entries.remove(index);
+            let derived_from_duplicate = entry
+                .parent
+                .as_ref()
+                .is_none_or(|link| link.parent_id == duplicate.id);
+            if derived_from_duplicate {
+                entry.parent = duplicate.parent;
+            }
+            for link in self.entries.iter_mut().filter_map(|e| e.parent.as_mut()) {
+                if link.parent_id == duplicate.id {
+                    link.parent_id = entry.id.clone();
+                }
+            }
+        }
 
         // Add new entry at the beginning (most recent first)
         self.entries.insert(0, entry);
@@ -136,18 +179,7 @@ pub type ClipboardHistoryState = Arc<RwLock<ClipboardHistory>>;
 
 // Storage functions
 pub fn get_history_file_path() -> io::Result<PathBuf> {
-    let data_dir = dirs::data_dir()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find data directory"))?;
-
-    // Use the proper Tauri app data directory
-    let clipify_dir = data_dir.join("com.suhailmalik.clipify");
-
-    // Create directory if it doesn't exist
-    if !clipify_dir.exists() {
-        fs::create_dir_all(&clipify_dir)?;
-    }
-
-    Ok(clipify_dir.join("clipboard_history.json"))
+    get_data_file_path("clipboard_history.json")
 }
 
 pub fn save_history_to_file(history: &ClipboardHistory) -> io::Result<()> {
@@ -1,6 +1,18 @@
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::clipboard::detect_content_type;
 use crate::clipboard::{save_history_to_file, ClipboardEntry, ClipboardHistoryState};
 use crate::clipboard_monitor::{ClipboardMonitor, ClipboardMonitorState};
-use crate::config::RephraseResponse;
+use crate::diff::{self, ContentDiff};
+use crate::lineage::{entry_lineage, EntryLineage, EntryLink, LineageOperation};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::transforms::links::UrlRulesState;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::transforms::presets::CleanupPresetStore;
+use crate::transforms::presets::CleanupPresetsState;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use crate::transforms::replace::ReplaceRulesState;
+use crate::transforms::{Pipeline, TransformStep};
+use crate::undo::{ClipboardChange, ClipboardUndoStack, ClipboardUndoState, UndoStatus};
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 #[cfg(target_os = "macos")]
@@ -135,80 +147,347 @@ pub async fn paste_from_history(
     };
 
     // Copy to clipboard
-    if let Err(e) = app.clipboard().write_text(&content) {
+    if let Err(e) = write_clipboard_text(&app, &content, "paste").await {
         return Err(format!("Failed to copy to clipboard: {}", e));
     }
 
     Ok(content)
 }
 
+// Write text produced by the app to the clipboard, remembering what it replaced so
+// the write can be undone. Content that isn't text can't be restored, so undo history
+// is cleared rather than letting a later undo skip back past it.
+pub async fn write_clipboard_text(app: &AppHandle, text: &str, label: &str) -> Result<(), String> {
+    let before = app.clipboard().read_text().ok();
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| e.to_string())?;
+
+    let undo_state = app.state::<ClipboardUndoState>();
+    let mut undo_stack = undo_state.write().await;
+    match before {
+        Some(before) => undo_stack.record(ClipboardChange::new(label, before, text.to_string())),
+        None => undo_stack.clear(),
+    }
+    Ok(())
+}
+
+// Undo and redo only apply while the clipboard still holds what they expect; anything
+// copied since then would be overwritten, so the stale history is dropped instead
+fn check_clipboard_unchanged(
+    app: &AppHandle,
+    undo_stack: &mut ClipboardUndoStack,
+    expected: &str,
+) -> Result<(), String> {
+    if app.clipboard().read_text().ok().as_deref() == Some(expected) {
+        return Ok(());
+    }
+    undo_stack.clear();
+    Err("The clipboard changed since the app last wrote to it, so there is nothing to undo or redo"
+        .to_string())
+}
+
+// Put back the clipboard content from before the app's last write
 #[tauri::command]
-pub async fn rephrase_text(
-    _text: String,
-    _jwt_token: String,
-    _api_base_url: Option<String>,
-) -> Result<RephraseResponse, String> {
-    // For now, return an error indicating this should be handled by the frontend
-    // The HTTP requests will be made from the frontend using the existing rephraseService
-    Err("Rephrase functionality should be called from frontend".to_string())
+pub async fn undo_last_clipboard_change(app: AppHandle) -> Result<ClipboardChange, String> {
+    let undo_state = app.state::<ClipboardUndoState>();
+    let mut undo_stack = undo_state.write().await;
+    let (before, after) = match undo_stack.next_undo() {
+        Some(change) => (change.before.clone(), change.after.clone()),
+        None => return Err("Nothing to undo".to_string()),
+    };
+    check_clipboard_unchanged(&app, &mut undo_stack, &after)?;
+
+    if let Err(e) = app.clipboard().write_text(&before) {
+        return Err(format!("Failed to restore clipboard: {}", e));
+    }
+    let change = undo_stack.finish_undo().ok_or("Nothing to undo")?;
+    drop(undo_stack);
+
+    // Emit an event to notify the frontend
+    if let Err(e) = app.emit("clipboard-updated", &before) {
+        println!("Failed to emit clipboard update event: {}", e);
+    }
+
+    Ok(change)
 }
 
-// Function to clean and beautify text according to Clipify specifications
-fn cleanup_text(text: &str) -> String {
-    // Handle null, undefined, or empty text
-    if text.is_empty() || text.trim().is_empty() {
-        return String::new();
+// Reapply the last undone clipboard write
+#[tauri::command]
+pub async fn redo_clipboard_change(app: AppHandle) -> Result<ClipboardChange, String> {
+    let undo_state = app.state::<ClipboardUndoState>();
+    let mut undo_stack = undo_state.write().await;
+    let (before, after) = match undo_stack.next_redo() {
+        Some(change) => (change.before.clone(), change.after.clone()),
+        None => return Err("Nothing to redo".to_string()),
+    };
+    check_clipboard_unchanged(&app, &mut undo_stack, &before)?;
+
+    if let Err(e) = app.clipboard().write_text(&after) {
+        return Err(format!("Failed to restore clipboard: {}", e));
     }
+    let change = undo_stack.finish_redo().ok_or("Nothing to redo")?;
+    drop(undo_stack);
 
-    text
-        // Convert all line endings to Unix format
-        .replace("\r\n", "\n") // Convert Windows line endings
-        .replace("\r", "\n") // Convert Mac line endings
-        // Replace multiple spaces/tabs with single space
-        .chars()
-        .collect::<Vec<char>>()
-        .windows(2)
-        .fold(String::new(), |mut acc, window| {
-            if window.len() == 2 {
-                let current = window[0];
-                let next = window[1];
-
-                // Add current character if it's not a redundant space/tab
-                if !(current == ' ' && (next == ' ' || next == '\t'))
-                    && !(current == '\t' && (next == ' ' || next == '\t'))
-                {
-                    acc.push(if current == '\t' { ' ' } else { current });
-                }
-            }
-            acc
+    // Emit an event to notify the frontend
+    if let Err(e) = app.emit("clipboard-updated", &after) {
+        println!("Failed to emit clipboard update event: {}", e);
+    }
+
+    Ok(change)
+}
+
+#[tauri::command]
+pub async fn get_clipboard_undo_status(
+    undo_state: tauri::State<'_, ClipboardUndoState>,
+) -> Result<UndoStatus, String> {
+    let undo_stack = undo_state.read().await;
+    Ok(undo_stack.status())
+}
+
+// What cleanup changed in an entry, as hunks from its original content to its content
+#[tauri::command]
+pub async fn get_entry_diff(
+    id: String,
+    history_state: tauri::State<'_, ClipboardHistoryState>,
+) -> Result<ContentDiff, String> {
+    let (original, content) = {
+        let history = history_state.read().await;
+        let entry = history.get_entry_by_id(&id).ok_or("Entry not found")?;
+        (entry.original_content.clone(), entry.content.clone())
+    };
+    tauri::async_runtime::spawn_blocking(move || diff::diff_texts(&original, &content))
+        .await
+        .map_err(|e| format!("Failed to diff entry: {}", e))
+}
+
+// Undo some of an entry's changes by restoring the given hunks from its original
+// content. The result is copied and recorded as a new entry with the same original.
+#[tauri::command]
+pub async fn revert_diff_hunks(
+    id: String,
+    hunks: Vec<usize>,
+    app: AppHandle,
+    history_state: tauri::State<'_, ClipboardHistoryState>,
+) -> Result<ClipboardEntry, String> {
+    let (original, content) = {
+        let history = history_state.read().await;
+        let entry = history.get_entry_by_id(&id).ok_or("Entry not found")?;
+        (entry.original_content.clone(), entry.content.clone())
+    };
+    let (original, reverted) = {
+        let hunks = hunks.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let reverted = diff::revert_hunks(&original, &content, &hunks);
+            reverted.map(|reverted| (original, reverted))
         })
-        // Handle the last character
-        .chars()
-        .chain(text.chars().last())
-        .collect::<String>()
-        // Replace multiple line breaks with double
-        .split('\n')
-        .map(|line| line.trim())
-        .collect::<Vec<&str>>()
-        .join("\n")
-        // Limit to max 2 consecutive line breaks
-        .split("\n\n\n")
-        .collect::<Vec<&str>>()
-        .join("\n\n")
-        .trim()
-        .to_string()
+        .await
+        .map_err(|e| format!("Failed to revert hunks: {}", e))??
+    };
+
+    if let Err(e) = write_clipboard_text(&app, &reverted, "revert").await {
+        return Err(format!("Failed to copy to clipboard: {}", e));
+    }
+
+    let is_cleaned = reverted != original;
+    let mut entry = ClipboardEntry::new(reverted.clone(), is_cleaned, Some(original));
+    let hunk_list = hunks.iter().map(|h| h.to_string()).collect::<Vec<_>>().join(",");
+    entry.parent = Some(
+        EntryLink::new(&id, LineageOperation::Revert).with_param("hunks", hunk_list),
+    );
+    {
+        let mut history = history_state.write().await;
+        history.add_entry(entry.clone());
+
+        // Save to file
+        if let Err(e) = save_history_to_file(&history) {
+            eprintln!("Failed to save clipboard history: {}", e);
+        }
+    }
+
+    // Emit an event to notify the frontend
+    if let Err(e) = app.emit("clipboard-updated", &reverted) {
+        println!("Failed to emit clipboard update event: {}", e);
+    }
+
+    Ok(entry)
+}
+
+// The tree of entries an entry was derived from and derived into, from its oldest
+// ancestor still in history
+#[tauri::command]
+pub async fn get_entry_lineage(
+    id: String,
+    history_state: tauri::State<'_, ClipboardHistoryState>,
+) -> Result<EntryLineage, String> {
+    let history = history_state.read().await;
+    entry_lineage(&history.entries, &id).ok_or_else(|| "Entry not found".to_string())
+}
+
+// Save a hand-edited version of an entry. It is copied and recorded as a new entry
+// derived from the one edited.
+#[tauri::command]
+pub async fn edit_clipboard_entry(
+    id: String,
+    content: String,
+    app: AppHandle,
+    history_state: tauri::State<'_, ClipboardHistoryState>,
+) -> Result<ClipboardEntry, String> {
+    if content.trim().is_empty() {
+        return Err("Content cannot be empty".to_string());
+    }
+    let original = {
+        let history = history_state.read().await;
+        let entry = history.get_entry_by_id(&id).ok_or("Entry not found")?;
+        if entry.content == content {
+            return Ok(entry.clone());
+        }
+        entry.original_content.clone()
+    };
+
+    if let Err(e) = write_clipboard_text(&app, &content, "manual_edit").await {
+        return Err(format!("Failed to copy to clipboard: {}", e));
+    }
+
+    let mut entry = ClipboardEntry::new(content.clone(), content != original, Some(original));
+    entry.parent = Some(EntryLink::new(&id, LineageOperation::ManualEdit));
+    {
+        let mut history = history_state.write().await;
+        history.add_entry(entry.clone());
+
+        if let Err(e) = save_history_to_file(&history) {
+            eprintln!("Failed to save clipboard history: {}", e);
+        }
+    }
+
+    if let Err(e) = app.emit("clipboard-updated", &content) {
+        println!("Failed to emit clipboard update event: {}", e);
+    }
+
+    Ok(entry)
+}
+
+// Run a single transform on a history entry, or on the current clipboard when no
+// entry is given. The result is copied and recorded with the source as its original.
+pub async fn transform_clipboard_text(
+    app: &AppHandle,
+    step: TransformStep,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    let label = step.as_transform().name();
+    transform_clipboard_with(app, entry_id, label, move |source, _| {
+        step.as_transform().apply(source)
+    })
+    .await
+}
+
+// Like `transform_clipboard_text`, for transforms that also need the source entry.
+// Clipboard text is matched to its history entry when it has one. The transform runs on
+// a blocking thread, as scripts and large inputs can take a while.
+pub async fn transform_clipboard_with<F>(
+    app: &AppHandle,
+    entry_id: Option<String>,
+    label: &str,
+    transform: F,
+) -> Result<ClipboardEntry, String>
+where
+    F: FnOnce(&str, Option<&ClipboardEntry>) -> Result<String, String> + Send + 'static,
+{
+    let history_state = app.state::<ClipboardHistoryState>();
+
+    let (source, source_entry) = match entry_id {
+        Some(id) => {
+            let history = history_state.read().await;
+            match history.get_entry_by_id(&id) {
+                Some(entry) => (entry.content.clone(), Some(entry.clone())),
+                None => return Err("Entry not found".to_string()),
+            }
+        }
+        None => {
+            let text = app
+                .clipboard()
+                .read_text()
+                .map_err(|e| format!("Failed to read clipboard: {}", e))?;
+            let history = history_state.read().await;
+            let entry = history.entries.iter().find(|e| e.content == text).cloned();
+            (text, entry)
+        }
+    };
+    if source.is_empty() {
+        return Err("There is no text to transform".to_string());
+    }
+
+    let (source, source_entry, result) = tauri::async_runtime::spawn_blocking(move || {
+        let result = transform(&source, source_entry.as_ref());
+        (source, source_entry, result)
+    })
+    .await
+    .map_err(|e| format!("Transform failed: {}", e))?;
+    let result = result?;
+    let link = source_entry.map(|entry| {
+        EntryLink::new(&entry.id, LineageOperation::Transform).with_param("step", label)
+    });
+    copy_transformed_text(app, source, result, label, link).await
+}
+
+// Run a cleanup pipeline on a blocking thread, as presets may include scripts
+pub async fn run_pipeline(pipeline: Pipeline, text: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || pipeline.run(&text))
+        .await
+        .map_err(|e| format!("Cleanup failed: {}", e))?
+}
+
+// Copy text the app derived from `source` and record it in history with `source` as its original
+pub async fn copy_transformed_text(
+    app: &AppHandle,
+    source: String,
+    result: String,
+    label: &str,
+    link: Option<EntryLink>,
+) -> Result<ClipboardEntry, String> {
+    if let Err(e) = write_clipboard_text(app, &result, label).await {
+        return Err(format!("Failed to write transformed text to clipboard: {}", e));
+    }
+
+    let history_state = app.state::<ClipboardHistoryState>();
+    let mut entry = ClipboardEntry::new(result.clone(), true, Some(source));
+    entry.parent = link;
+    {
+        let mut history = history_state.write().await;
+        history.add_entry(entry.clone());
+
+        // Save to file
+        if let Err(e) = save_history_to_file(&history) {
+            eprintln!("Failed to save clipboard history: {}", e);
+        }
+    }
+
+    // Emit an event to notify the frontend
+    if let Err(e) = app.emit("clipboard-updated", &result) {
+        println!("Failed to emit clipboard update event: {}", e);
+    }
+
+    Ok(entry)
 }
 
 #[tauri::command]
-pub async fn trigger_clipboard_copy(app: AppHandle) -> Result<String, String> {
+pub async fn trigger_clipboard_copy(
+    app: AppHandle,
+    preset: Option<String>,
+) -> Result<String, String> {
     let history_state = app.state::<ClipboardHistoryState>();
-    copy_selected_text_to_clipboard(app.clone(), history_state).await
+    copy_selected_text_to_clipboard(app.clone(), history_state, preset).await
 }
 
 pub async fn copy_selected_text_to_clipboard(
     app: AppHandle,
     history_state: tauri::State<'_, ClipboardHistoryState>,
+    preset: Option<String>,
 ) -> Result<String, String> {
+    // Snapshot the presets and validate the requested one before touching the clipboard
+    let presets = app.state::<CleanupPresetsState>().read().await.clone();
+    presets.pipeline_for(preset.as_deref())?;
+
     #[cfg(target_os = "macos")]
     {
         use std::time::Duration;
@@ -379,53 +658,14 @@ pub async fn copy_selected_text_to_clipboard(
             new_clipboard.len()
         );
 
-        // Use the newly copied text
-        let new_text = new_clipboard;
-
-        // Clean the text according to Clipify specifications
-        let cleaned_text = cleanup_text(&new_text);
-
-        // Check if cleaned text is empty and return early if so
-        if cleaned_text.is_empty() {
-            // Emit an event to notify the frontend about empty text
-            if let Err(e) = app.emit("clipboard-updated", "") {
-                println!(
-                    "Failed to emit clipboard update event for empty text: {}",
-                    e
-                );
-            }
-            return Ok("".to_string());
-        }
-
-        // Write cleaned text back to clipboard
-        if let Err(e) = app.clipboard().write_text(&cleaned_text) {
-            return Err(format!("Failed to write cleaned text to clipboard: {}", e));
-        }
-
-        // Add to clipboard history
-        let original_entry = ClipboardEntry::new(new_text.clone(), false, None);
-        let cleaned_entry = ClipboardEntry::new(cleaned_text.clone(), true, Some(new_text.clone()));
-
-        {
-            let mut history = history_state.write().await;
-            // Add both original and cleaned entries to history
-            history.add_entry(cleaned_entry);
-            if new_text != cleaned_text {
-                history.add_entry(original_entry);
-            }
-
-            // Save to file
-            if let Err(e) = save_history_to_file(&*history) {
-                eprintln!("Failed to save clipboard history: {}", e);
-            }
-        }
-
-        // Emit an event to notify the frontend
-        if let Err(e) = app.emit("clipboard-updated", &cleaned_text) {
-            println!("Failed to emit clipboard update event: {}", e);
-        }
-
-        Ok(cleaned_text)
+        process_copied_text(
+            &app,
+            &history_state,
+            new_clipboard,
+            &presets,
+            preset.as_deref(),
+        )
+        .await
     }
 
     #[cfg(target_os = "windows")]
@@ -538,59 +778,124 @@ pub async fn copy_selected_text_to_clipboard(
             new_clipboard.len()
         );
 
-        // Use the newly copied text
-        let new_text = new_clipboard;
+        process_copied_text(
+            &app,
+            &history_state,
+            new_clipboard,
+            &presets,
+            preset.as_deref(),
+        )
+        .await
+    }
 
-        // Clean the text according to Clipify specifications
-        let cleaned_text = cleanup_text(&new_text);
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // For other platforms, we'll need to implement platform-specific solutions
+        // For now, just return an error
+        let _ = (history_state, presets);
+        Err("Global shortcut copy is currently only supported on macOS and Windows".to_string())
+    }
+}
 
-        // Check if cleaned text is empty and return early if so
-        if cleaned_text.is_empty() {
-            // Emit an event to notify the frontend about empty text
-            if let Err(e) = app.emit("clipboard-updated", "") {
-                println!(
-                    "Failed to emit clipboard update event for empty text: {}",
-                    e
-                );
-            }
-            return Ok("".to_string());
-        }
+// Clean freshly copied text, write it back to the clipboard and record it in history
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+async fn process_copied_text(
+    app: &AppHandle,
+    history_state: &ClipboardHistoryState,
+    new_text: String,
+    presets: &CleanupPresetStore,
+    preset: Option<&str>,
+) -> Result<String, String> {
+    // Clean the text with the selected cleanup pipeline (code gets the code preset by default)
+    let pipeline = presets.pipeline_for_text(preset, &new_text)?;
+    let pipeline = {
+        let url_rules = app.state::<UrlRulesState>();
+        let url_rules = url_rules.read().await;
+        pipeline.with_url_rules(&url_rules.rules)
+    };
+
+    // Pipelines that convert HTML get the rich-text flavor when the source app provided one
+    let source = if pipeline.accepts_html() {
+        read_clipboard_html().unwrap_or_else(|| new_text.clone())
+    } else {
+        new_text.clone()
+    };
+    let cleaned_text = run_pipeline(pipeline.clone(), source).await?;
+
+    // House find/replace rules run last, scoped by what kind of content was copied
+    let cleaned_text = {
+        let replace_rules = app.state::<ReplaceRulesState>();
+        let replace_rules = replace_rules.read().await;
+        replace_rules.apply(&cleaned_text, &detect_content_type(&new_text))
+    };
 
-        // Write cleaned text back to clipboard
-        if let Err(e) = app.clipboard().write_text(&cleaned_text) {
-            return Err(format!("Failed to write cleaned text to clipboard: {}", e));
+    // Check if cleaned text is empty and return early if so
+    if cleaned_text.is_empty() {
+        // Emit an event to notify the frontend about empty text
+        if let Err(e) = app.emit("clipboard-updated", "") {
+            println!(
+                "Failed to emit clipboard update event for empty text: {}",
+                e
+            );
         }
+        return Ok("".to_string());
+    }
 
-        // Add to clipboard history
-        let original_entry = ClipboardEntry::new(new_text.clone(), false, None);
-        let cleaned_entry = ClipboardEntry::new(cleaned_text.clone(), true, Some(new_text.clone()));
+    // Write cleaned text back to clipboard
+    if let Err(e) = write_clipboard_text(app, &cleaned_text, "cleanup").await {
+        return Err(format!("Failed to write cleaned text to clipboard: {}", e));
+    }
 
-        {
-            let mut history = history_state.write().await;
-            // Add both original and cleaned entries to history
-            history.add_entry(cleaned_entry);
-            if new_text != cleaned_text {
-                history.add_entry(original_entry);
-            }
+    // Add to clipboard history
+    let original_entry = ClipboardEntry::new(new_text.clone(), false, None);
+    let mut cleaned_entry = ClipboardEntry::new(cleaned_text.clone(), true, Some(new_text.clone()));
+    if new_text != cleaned_text {
+        let steps = pipeline
+            .steps
+            .iter()
+            .filter(|s| s.enabled)
+            .map(|s| s.step.as_transform().name())
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut link = EntryLink::new(&original_entry.id, LineageOperation::Cleanup)
+            .with_param("steps", steps);
+        if let Some(preset) = preset {
+            link = link.with_param("preset", preset);
+        }
+        cleaned_entry.parent = Some(link);
+    }
 
-            // Save to file
-            if let Err(e) = save_history_to_file(&*history) {
-                eprintln!("Failed to save clipboard history: {}", e);
-            }
+    {
+        let mut history = history_state.write().await;
+        // Add both original and cleaned entries to history
+        history.add_entry(cleaned_entry);
+        if new_text != cleaned_text {
+            history.add_entry(original_entry);
         }
 
-        // Emit an event to notify the frontend
-        if let Err(e) = app.emit("clipboard-updated", &cleaned_text) {
-            println!("Failed to emit clipboard update event: {}", e);
+        // Save to file
+        if let Err(e) = save_history_to_file(&*history) {
+            eprintln!("Failed to save clipboard history: {}", e);
         }
+    }
 
-        Ok(cleaned_text)
+    // Emit an event to notify the frontend
+    if let Err(e) = app.emit("clipboard-updated", &cleaned_text) {
+        println!("Failed to emit clipboard update event: {}", e);
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    {
-        // For other platforms, we'll need to implement platform-specific solutions
-        // For now, just return an error
-        Err("Global shortcut copy is currently only supported on macOS and Windows".to_string())
+    Ok(cleaned_text)
+}
+
+// Read the HTML flavor of the clipboard, if the copying app put one there
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn read_clipboard_html() -> Option<String> {
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get().html()) {
+        Ok(html) if !html.trim().is_empty() => Some(html),
+        Ok(_) => None,
+        Err(e) => {
+            println!("No HTML clipboard content available: {}", e);
+            None
+        }
     }
 }
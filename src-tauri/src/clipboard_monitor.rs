@@ -1,4 +1,7 @@
-use crate::clipboard::{ClipboardEntry, ClipboardHistoryState, save_history_to_file};
+use crate::clipboard::{truncate_preview, ClipboardEntry, ClipboardHistoryState, save_history_to_file};
+use crate::clipboard_commands::write_clipboard_text;
+use crate::transforms::links::UrlRulesState;
+use crate::undo::ClipboardUndoState;
 use clipboard::{ClipboardContext, ClipboardProvider};
 use std::sync::Arc;
 use std::time::Duration;
@@ -92,12 +95,38 @@ impl ClipboardMonitor {
 
         println!("[ClipboardMonitor] Clipboard content changed, length: {}", current_content.len());
         
+        // Text put back by an undo or redo is left as it is
+        let restored = {
+            let undo_state = self.app_handle.state::<ClipboardUndoState>();
+            let undo_stack = undo_state.read().await;
+            undo_stack.is_restored(&current_content)
+        };
+
+        // Strip tracking parameters and redirect wrappers from copied links when enabled
+        let cleaned_url = if restored {
+            None
+        } else {
+            let url_rules_state = self.app_handle.state::<UrlRulesState>();
+            let url_rules = url_rules_state.read().await;
+            url_rules.clean_copied_text(&current_content)
+        };
+        let (current_content, original_content) = match cleaned_url {
+            Some(cleaned) => {
+                println!("[ClipboardMonitor] Cleaned copied URL");
+                if let Err(e) = write_clipboard_text(&self.app_handle, &cleaned, "url_cleanup").await {
+                    eprintln!("[ClipboardMonitor] Failed to write cleaned URL to clipboard: {}", e);
+                }
+                (cleaned, Some(current_content))
+            }
+            None => (current_content, None),
+        };
+
         // Update last content
         *last_content = current_content.clone();
         drop(last_content);
 
         // Add to history
-        self.add_to_history(current_content.clone()).await?;
+        self.add_to_history(current_content.clone(), original_content).await?;
 
         // Emit event to frontend
         if let Err(e) = self.app_handle.emit("clipboard-content-changed", &current_content) {
@@ -118,11 +147,15 @@ impl ClipboardMonitor {
             .map_err(|e| format!("Failed to get clipboard contents: {}", e))
     }
 
-    async fn add_to_history(&self, content: String) -> Result<(), String> {
-        let entry = ClipboardEntry::new(content, false, None);
+    async fn add_to_history(&self, content: String, original_content: Option<String>) -> Result<(), String> {
+        let entry = ClipboardEntry::new(content, original_content.is_some(), original_content);
         
         {
             let mut history = self.history_state.write().await;
+            // The app records its own clipboard writes; don't replace that entry and its original
+            if history.entries.first().is_some_and(|e| e.content == entry.content) {
+                return Ok(());
+            }
             history.add_entry(entry);
             
             // Save to file
@@ -145,11 +178,7 @@ impl ClipboardMonitor {
         // Add recent clipboard items to menu if any exist
         if !recent_entries.is_empty() {
             for (index, entry) in recent_entries.iter().enumerate() {
-                let preview = if entry.preview.len() > 50 {
-                    format!("{}...", &entry.preview[..47])
-                } else {
-                    entry.preview.clone()
-                };
+                let preview = truncate_preview(&entry.preview, 50);
                 
                 let menu_text = format!("📋 {}", preview.replace('\n', " ").replace('\t', " "));
                 let menu_item = tauri::menu::MenuItem::with_id(
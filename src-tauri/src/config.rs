@@ -10,7 +10,7 @@ pub struct EnvironmentConfig {
     pub dev_url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RephraseRequest {
     pub text: String,
     pub style: String,
@@ -19,12 +19,25 @@ pub struct RephraseRequest {
     pub preserve_length: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RephraseResponse {
     pub processing_time_ms: Option<u64>,
     pub rephrased_text: String,
 }
 
+impl RephraseRequest {
+    // A request with the defaults the app uses for the rephrase shortcut
+    pub fn new(text: &str) -> Self {
+        RephraseRequest {
+            text: text.to_string(),
+            style: "formal".to_string(),
+            context: "Business communication".to_string(),
+            target_audience: "Colleagues".to_string(),
+            preserve_length: false,
+        }
+    }
+}
+
 impl EnvironmentConfig {
     pub fn from_env() -> Self {
         let environment = env::var("NODE_ENV").unwrap_or_else(|_| "development".to_string());
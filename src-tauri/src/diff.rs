@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use similar::algorithms::{myers, Capture, DiffHook};
+use similar::DiffOp;
+use std::hash::Hash;
+use std::ops::Range;
+
+// Myers takes quadratic time at worst, so when more lines or characters than this
+// differ between the common prefix and suffix they are shown as one replacement.
+// A size cutoff rather than a time limit keeps hunks the same on every run, which
+// `revert_hunks` relies on to find the hunks `diff_texts` showed.
+const MAX_DIFF_ITEMS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+// A run of characters with the same change kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffSpan {
+    pub kind: ChangeKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: ChangeKind,
+    // The line without its line break
+    pub text: String,
+}
+
+// One block of consecutive changed lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub index: usize,
+    // Zero-based line ranges in the original and in the current content
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    // Removed lines followed by added lines
+    pub lines: Vec<DiffLine>,
+    // Character-level changes from the removed text to the added text
+    pub spans: Vec<DiffSpan>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentDiff {
+    pub hunks: Vec<DiffHunk>,
+    pub inserted_chars: usize,
+    pub deleted_chars: usize,
+}
+
+// Myers diff ops in order. similar's TextDiff also compacts the ops, which can
+// leave them out of order, so the algorithm is driven directly.
+fn diff_ops<T: Hash + Eq + Ord>(old: &[T], new: &[T]) -> Vec<DiffOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+
+    let mut capture = Capture::new();
+    let _ = if (old_end - prefix) + (new_end - prefix) <= MAX_DIFF_ITEMS {
+        myers::diff(&mut capture, old, 0..old.len(), new, 0..new.len())
+    } else {
+        coarse_diff(&mut capture, prefix, old_end, new_end, suffix)
+    };
+    capture.into_ops()
+}
+
+// The changed middle as a single delete and insert between the unchanged ends
+fn coarse_diff(
+    capture: &mut Capture,
+    prefix: usize,
+    old_end: usize,
+    new_end: usize,
+    suffix: usize,
+) -> Result<(), std::convert::Infallible> {
+    if prefix > 0 {
+        capture.equal(0, 0, prefix)?;
+    }
+    if old_end > prefix {
+        capture.delete(prefix, old_end - prefix, prefix)?;
+    }
+    if new_end > prefix {
+        capture.insert(old_end, prefix, new_end - prefix)?;
+    }
+    if suffix > 0 {
+        capture.equal(old_end, new_end, suffix)?;
+    }
+    capture.finish()
+}
+
+// Lines with their line breaks, so concatenating them gives back the text
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+// Line ranges of each hunk, merging adjacent deletes and inserts
+fn change_blocks(ops: &[DiffOp]) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut blocks: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+    let mut previous_changed = false;
+    for op in ops {
+        let changed = !matches!(op, DiffOp::Equal { .. });
+        if changed {
+            let (old, new) = (op.old_range(), op.new_range());
+            match blocks.last_mut() {
+                Some((old_block, new_block)) if previous_changed => {
+                    old_block.end = old.end;
+                    new_block.end = new.end;
+                }
+                _ => blocks.push((old, new)),
+            }
+        }
+        previous_changed = changed;
+    }
+    blocks
+}
+
+// Hunks describing how `old` became `new`
+pub fn diff_texts(old: &str, new: &str) -> ContentDiff {
+    let (old_lines, new_lines) = (split_lines(old), split_lines(new));
+
+    let mut inserted_chars = 0;
+    let mut deleted_chars = 0;
+    let hunks = change_blocks(&diff_ops(&old_lines, &new_lines))
+        .into_iter()
+        .enumerate()
+        .map(|(index, (old_range, new_range))| {
+            let removed = old_lines[old_range.clone()].concat();
+            let added = new_lines[new_range.clone()].concat();
+
+            let mut lines: Vec<DiffLine> = Vec::new();
+            for (kind, range, source) in [
+                (ChangeKind::Delete, old_range.clone(), &old_lines),
+                (ChangeKind::Insert, new_range.clone(), &new_lines),
+            ] {
+                lines.extend(source[range].iter().map(|line| DiffLine {
+                    kind,
+                    text: line.trim_end_matches(['\n', '\r']).to_string(),
+                }));
+            }
+
+            let spans = char_spans(&removed, &added);
+            for span in &spans {
+                match span.kind {
+                    ChangeKind::Insert => inserted_chars += span.text.chars().count(),
+                    ChangeKind::Delete => deleted_chars += span.text.chars().count(),
+                    ChangeKind::Equal => {}
+                }
+            }
+
+            DiffHunk {
+                index,
+                old_start: old_range.start,
+                old_lines: old_range.len(),
+                new_start: new_range.start,
+                new_lines: new_range.len(),
+                lines,
+                spans,
+            }
+        })
+        .collect();
+
+    ContentDiff {
+        hunks,
+        inserted_chars,
+        deleted_chars,
+    }
+}
+
+fn char_spans(old: &str, new: &str) -> Vec<DiffSpan> {
+    let (old_chars, new_chars): (Vec<char>, Vec<char>) =
+        (old.chars().collect(), new.chars().collect());
+
+    let mut spans: Vec<DiffSpan> = Vec::new();
+    let mut push = |kind: ChangeKind, chars: &[char]| {
+        if chars.is_empty() {
+            return;
+        }
+        match spans.last_mut() {
+            Some(span) if span.kind == kind => span.text.extend(chars),
+            _ => spans.push(DiffSpan {
+                kind,
+                text: chars.iter().collect(),
+            }),
+        }
+    };
+    for op in diff_ops(&old_chars, &new_chars) {
+        match op {
+            DiffOp::Equal { .. } => push(ChangeKind::Equal, &old_chars[op.old_range()]),
+            DiffOp::Delete { .. } => push(ChangeKind::Delete, &old_chars[op.old_range()]),
+            DiffOp::Insert { .. } => push(ChangeKind::Insert, &new_chars[op.new_range()]),
+            DiffOp::Replace { .. } => {
+                push(ChangeKind::Delete, &old_chars[op.old_range()]);
+                push(ChangeKind::Insert, &new_chars[op.new_range()]);
+            }
+        }
+    }
+    spans
+}
+
+// `new` with the selected hunks (by index) restored to their text in `old`
+pub fn revert_hunks(old: &str, new: &str, hunks: &[usize]) -> Result<String, String> {
+    let (old_lines, new_lines) = (split_lines(old), split_lines(new));
+    let blocks = change_blocks(&diff_ops(&old_lines, &new_lines));
+    if let Some(bad) = hunks.iter().find(|&&index| index >= blocks.len()) {
+        return Err(format!(
+            "Hunk {} does not exist; the diff has {} hunks",
+            bad,
+            blocks.len()
+        ));
+    }
+
+    let mut output = String::with_capacity(new.len());
+    let mut position = 0;
+    for (index, (old_range, new_range)) in blocks.into_iter().enumerate() {
+        output.push_str(&new_lines[position..new_range.start].concat());
+        if hunks.contains(&index) {
+            output.push_str(&old_lines[old_range].concat());
+        } else {
+            output.push_str(&new_lines[new_range.clone()].concat());
+        }
+        position = new_range.end;
+    }
+    output.push_str(&new_lines[position..].concat());
+    Ok(output)
+}
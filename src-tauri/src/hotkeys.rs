@@ -0,0 +1,549 @@
+use crate::clipboard::{truncate_preview, ClipboardHistoryState};
+use crate::clipboard_commands::{
+    copy_selected_text_to_clipboard, redo_clipboard_change, undo_last_clipboard_change,
+};
+use crate::rephrase::templates::RephraseTemplatesState;
+use crate::rephrase_commands::{notify, rephrase_to_clipboard};
+use crate::storage::{load_json_file, save_json_file};
+use crate::transform_commands::run_script_on_clipboard;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tokio::sync::RwLock;
+
+const HOTKEYS_FILE: &str = "hotkey_bindings.json";
+
+// The copy-and-clean shortcut the UI registers once accessibility permission is granted
+pub const MAIN_SHORTCUT: &str = "CommandOrControl+Shift+C";
+
+// Restores the clipboard from before the app's last write
+pub const DEFAULT_UNDO_SHORTCUT: &str = "CommandOrControl+Alt+Z";
+
+// What a global shortcut does when pressed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HotkeyAction {
+    // Copy the current selection and clean it with a cleanup preset
+    CopyAndClean {
+        #[serde(default)]
+        preset: Option<String>,
+        #[serde(default)]
+        auto_rephrase: bool,
+        // Rephrase profile to use; the default profile when unset
+        #[serde(default)]
+        rephrase_profile: Option<String>,
+    },
+    // Copy the current selection, clean it and rephrase it with a rephrase template
+    RephraseWithTemplate {
+        template: String,
+        #[serde(default)]
+        preset: Option<String>,
+        // Values for the template's placeholders that have no default
+        #[serde(default)]
+        variables: HashMap<String, String>,
+    },
+    // Run a script from the scripts folder on the current clipboard text
+    RunScript {
+        script: String,
+    },
+    // Restore the clipboard content from before the app's last write
+    UndoClipboardChange,
+    // Reapply the last undone clipboard write
+    RedoClipboardChange,
+}
+
+impl Default for HotkeyAction {
+    // Behaviour of the main shortcut when the user hasn't bound it to something else.
+    // Copies are only sent to a rephrase service once the user binds it to do so.
+    fn default() -> Self {
+        HotkeyAction::CopyAndClean {
+            preset: None,
+            auto_rephrase: false,
+            rephrase_profile: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    // Accelerator string, e.g. "CommandOrControl+Alt+C"
+    pub shortcut: String,
+    #[serde(flatten)]
+    pub action: HotkeyAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeyBindings {
+    pub bindings: Vec<HotkeyBinding>,
+}
+
+impl Default for HotkeyBindings {
+    // Fresh installs get the clipboard undo shortcut; users can rebind or unbind it
+    fn default() -> Self {
+        HotkeyBindings {
+            bindings: vec![HotkeyBinding {
+                shortcut: DEFAULT_UNDO_SHORTCUT.to_string(),
+                action: HotkeyAction::UndoClipboardChange,
+            }],
+        }
+    }
+}
+
+impl HotkeyBindings {
+    pub fn find(&self, shortcut: &Shortcut) -> Option<&HotkeyAction> {
+        self.bindings
+            .iter()
+            .find(|binding| {
+                Shortcut::from_str(&binding.shortcut)
+                    .map(|parsed| parsed.id() == shortcut.id())
+                    .unwrap_or(false)
+            })
+            .map(|binding| &binding.action)
+    }
+
+    // The action for a pressed shortcut: its binding, the default action for the main
+    // shortcut, or None for shortcuts the app doesn't know about
+    pub fn action_for(&self, shortcut: &Shortcut) -> Option<HotkeyAction> {
+        if let Some(action) = self.find(shortcut) {
+            return Some(action.clone());
+        }
+        Shortcut::from_str(MAIN_SHORTCUT)
+            .ok()
+            .filter(|main| main.id() == shortcut.id())
+            .map(|_| HotkeyAction::default())
+    }
+
+    pub fn upsert(&mut self, binding: HotkeyBinding) {
+        match self
+            .bindings
+            .iter_mut()
+            .find(|b| b.shortcut == binding.shortcut)
+        {
+            Some(existing) => *existing = binding,
+            None => self.bindings.push(binding),
+        }
+    }
+
+    pub fn remove(&mut self, shortcut: &str) -> bool {
+        let original_len = self.bindings.len();
+        self.bindings.retain(|b| b.shortcut != shortcut);
+        self.bindings.len() != original_len
+    }
+}
+
+pub type HotkeyBindingsState = Arc<RwLock<HotkeyBindings>>;
+
+pub fn load_hotkeys_from_file() -> io::Result<HotkeyBindings> {
+    load_json_file(HOTKEYS_FILE)
+}
+
+pub fn save_hotkeys_to_file(bindings: &HotkeyBindings) -> io::Result<()> {
+    save_json_file(HOTKEYS_FILE, bindings)
+}
+
+// Register all persisted bindings with the OS; failures are logged and skipped
+pub fn register_saved_hotkeys(app: &AppHandle, bindings: &HotkeyBindings) {
+    for binding in &bindings.bindings {
+        if let Err(e) = app.global_shortcut().register(binding.shortcut.as_str()) {
+            eprintln!(
+                "[Hotkeys] Failed to register saved shortcut {}: {}",
+                binding.shortcut, e
+            );
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_hotkey_bindings(
+    bindings_state: tauri::State<'_, HotkeyBindingsState>,
+) -> Result<Vec<HotkeyBinding>, String> {
+    let bindings = bindings_state.read().await;
+    Ok(bindings.bindings.clone())
+}
+
+#[tauri::command]
+pub async fn bind_hotkey(
+    shortcut: String,
+    action: HotkeyAction,
+    app: AppHandle,
+    bindings_state: tauri::State<'_, HotkeyBindingsState>,
+) -> Result<(), String> {
+    Shortcut::from_str(&shortcut).map_err(|e| format!("Invalid shortcut '{}': {}", shortcut, e))?;
+
+    // A shortcut has nowhere to ask for template values, so they must all be known now
+    if let HotkeyAction::RephraseWithTemplate {
+        template,
+        variables,
+        ..
+    } = &action
+    {
+        let templates_state = app.state::<RephraseTemplatesState>();
+        let templates = templates_state.read().await;
+        let template = templates
+            .get(template)
+            .ok_or_else(|| format!("Rephrase template '{}' not found", template))?;
+        let missing = template.missing_variables(variables);
+        if !missing.is_empty() {
+            return Err(format!(
+                "Missing values for template variables: {}",
+                missing.join(", ")
+            ));
+        }
+    }
+
+    if !app.global_shortcut().is_registered(shortcut.as_str()) {
+        app.global_shortcut()
+            .register(shortcut.as_str())
+            .map_err(|e| format!("Failed to register shortcut '{}': {}", shortcut, e))?;
+    }
+
+    let mut bindings = bindings_state.write().await;
+    bindings.upsert(HotkeyBinding { shortcut, action });
+
+    if let Err(e) = save_hotkeys_to_file(&bindings) {
+        eprintln!("Failed to save hotkey bindings: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unbind_hotkey(
+    shortcut: String,
+    app: AppHandle,
+    bindings_state: tauri::State<'_, HotkeyBindingsState>,
+) -> Result<bool, String> {
+    let mut bindings = bindings_state.write().await;
+    let removed = bindings.remove(&shortcut);
+
+    if removed {
+        if let Err(e) = app.global_shortcut().unregister(shortcut.as_str()) {
+            eprintln!(
+                "[Hotkeys] Failed to unregister shortcut {}: {}",
+                shortcut, e
+            );
+        }
+        if let Err(e) = save_hotkeys_to_file(&bindings) {
+            eprintln!("Failed to save hotkey bindings: {}", e);
+        }
+    }
+
+    Ok(removed)
+}
+
+// Dispatch a pressed global shortcut to its bound action
+pub async fn handle_shortcut(app_handle: AppHandle, shortcut: Shortcut) {
+    let action = {
+        let bindings_state = app_handle.state::<HotkeyBindingsState>();
+        let bindings = bindings_state.read().await;
+        bindings.action_for(&shortcut)
+    };
+
+    let Some(action) = action else {
+        println!(
+            "[Hotkeys] Ignoring unbound shortcut {}",
+            shortcut.into_string()
+        );
+        return;
+    };
+
+    match action {
+        HotkeyAction::CopyAndClean {
+            preset,
+            auto_rephrase,
+            rephrase_profile,
+        } => {
+            let plan = auto_rephrase.then_some(RephrasePlan {
+                profile: rephrase_profile,
+                template: None,
+                variables: HashMap::new(),
+            });
+            copy_and_clean(app_handle, preset, plan).await
+        }
+        HotkeyAction::RephraseWithTemplate {
+            template,
+            preset,
+            variables,
+        } => {
+            let plan = RephrasePlan {
+                profile: None,
+                template: Some(template),
+                variables,
+            };
+            copy_and_clean(app_handle, preset, Some(plan)).await
+        }
+        HotkeyAction::RunScript { script } => run_script(app_handle, script).await,
+        HotkeyAction::UndoClipboardChange => undo_or_redo(app_handle, false).await,
+        HotkeyAction::RedoClipboardChange => undo_or_redo(app_handle, true).await,
+    }
+}
+
+async fn undo_or_redo(app_handle: AppHandle, redo: bool) {
+    let result = if redo {
+        redo_clipboard_change(app_handle.clone()).await
+    } else {
+        undo_last_clipboard_change(app_handle.clone()).await
+    };
+
+    let (title, body) = match result {
+        Ok(change) if redo => (
+            format!("↪️ Redid {}", change.label),
+            truncate_preview(&change.after, 100),
+        ),
+        Ok(change) => (
+            format!("↩️ Undid {}", change.label),
+            truncate_preview(&change.before, 100),
+        ),
+        Err(e) => {
+            println!("Clipboard undo/redo skipped: {}", e);
+            ("ℹ️ Clipboard unchanged".to_string(), e)
+        }
+    };
+
+    notify(&app_handle, &title, &body);
+}
+
+async fn run_script(app_handle: AppHandle, script: String) {
+    let (title, body) = match run_script_on_clipboard(&app_handle, script.clone(), None).await {
+        Ok(entry) => {
+            println!("Ran script '{}': {} characters", script, entry.char_count);
+            (
+                format!("✅ Ran '{}'", script),
+                truncate_preview(&entry.content, 100),
+            )
+        }
+        Err(e) => {
+            eprintln!("Script '{}' failed: {}", script, e);
+            (format!("❌ Script '{}' failed", script), e)
+        }
+    };
+
+    notify(&app_handle, &title, &body);
+}
+
+// Set while a rephrase started from a shortcut runs, so pressing it again doesn't send
+// another request for the same selection
+static REPHRASING: AtomicBool = AtomicBool::new(false);
+
+// Clears `REPHRASING` when dropped, however the rephrase ends
+struct RephraseInFlight;
+
+impl RephraseInFlight {
+    fn start() -> Option<Self> {
+        REPHRASING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| RephraseInFlight)
+    }
+}
+
+impl Drop for RephraseInFlight {
+    fn drop(&mut self) {
+        REPHRASING.store(false, Ordering::Release);
+    }
+}
+
+// How to rephrase copied text; unset fields fall back to the template's or default profile
+struct RephrasePlan {
+    profile: Option<String>,
+    template: Option<String>,
+    variables: HashMap<String, String>,
+}
+
+async fn rephrase(app_handle: AppHandle, text: String, plan: RephrasePlan) {
+    let result = rephrase_to_clipboard(
+        &app_handle,
+        text,
+        plan.profile.as_deref(),
+        plan.template.as_deref(),
+        &plan.variables,
+    )
+    .await;
+    let (title, body) = match result {
+        Ok(entry) => {
+            if let Err(e) = app_handle.emit("rephrase-completed", &entry) {
+                eprintln!("Failed to emit rephrase completed event: {}", e);
+            }
+            (
+                "✨ Text Rephrased".to_string(),
+                truncate_preview(&entry.content, 100),
+            )
+        }
+        Err(e) => {
+            eprintln!("Auto-rephrase failed: {}", e);
+            ("❌ Rephrase failed".to_string(), e)
+        }
+    };
+
+    notify(&app_handle, &title, &body);
+}
+
+// `plan` is None when the result shouldn't be rephrased
+async fn copy_and_clean(app_handle: AppHandle, preset: Option<String>, plan: Option<RephrasePlan>) {
+    let history_state = app_handle.state::<ClipboardHistoryState>();
+    match copy_selected_text_to_clipboard(app_handle.clone(), history_state, preset).await {
+        Ok(text) => {
+            if !text.is_empty() {
+                println!(
+                    "Successfully copied and cleaned text: {} characters",
+                    text.len()
+                );
+
+                // Rephrase in the background so the cleaned text is usable meanwhile
+                if let Some(plan) = plan {
+                    match RephraseInFlight::start() {
+                        Some(in_flight) => {
+                            let app_handle = app_handle.clone();
+                            let text = text.clone();
+                            tauri::async_runtime::spawn(async move {
+                                rephrase(app_handle, text, plan).await;
+                                drop(in_flight);
+                            });
+                        }
+                        None => println!("A rephrase is still running; not starting another"),
+                    }
+                }
+
+                // Send success notification with cleaned text preview
+                let preview = truncate_preview(&text, 100);
+                notify(
+                    &app_handle,
+                    "✅ Text Copied & Cleaned!",
+                    &format!("Cleaned text ({} chars): {}", text.len(), preview),
+                );
+            } else {
+                println!("Empty text result from clipboard operation");
+                // Show notification for empty result
+                notify(
+                    &app_handle,
+                    "ℹ️ No Text to Clean",
+                    "The selected text was empty or contained only whitespace.",
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("Error in global shortcut handler: {}", e);
+            // The error notifications are already handled in copy_selected_text_to_clipboard
+            // Just log the error here for debugging
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(shortcut: &str, action: HotkeyAction) -> HotkeyBinding {
+        HotkeyBinding {
+            shortcut: shortcut.to_string(),
+            action,
+        }
+    }
+
+    fn script(name: &str) -> HotkeyAction {
+        HotkeyAction::RunScript {
+            script: name.to_string(),
+        }
+    }
+
+    fn shortcut(accelerator: &str) -> Shortcut {
+        Shortcut::from_str(accelerator).unwrap()
+    }
+
+    fn no_bindings() -> HotkeyBindings {
+        HotkeyBindings {
+            bindings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn clipboard_undo_is_bound_by_default() {
+        let bindings: HotkeyBindings = serde_json::from_str("{}").unwrap();
+
+        assert_eq!(
+            bindings.action_for(&shortcut(DEFAULT_UNDO_SHORTCUT)),
+            Some(HotkeyAction::UndoClipboardChange)
+        );
+
+        // Unbinding it sticks across restarts
+        let saved = serde_json::to_string(&no_bindings()).unwrap();
+        let bindings: HotkeyBindings = serde_json::from_str(&saved).unwrap();
+        assert_eq!(bindings.action_for(&shortcut(DEFAULT_UNDO_SHORTCUT)), None);
+    }
+
+    #[test]
+    fn find_matches_equivalent_accelerators() {
+        let mut bindings = no_bindings();
+        bindings.upsert(binding("CommandOrControl+Alt+U", script("upper")));
+
+        assert_eq!(
+            bindings.find(&shortcut("CommandOrControl+Alt+U")),
+            Some(&script("upper"))
+        );
+        assert_eq!(
+            bindings.find(&shortcut("Alt+CommandOrControl+KeyU")),
+            Some(&script("upper"))
+        );
+        assert_eq!(bindings.find(&shortcut("CommandOrControl+Alt+L")), None);
+    }
+
+    #[test]
+    fn upsert_replaces_the_binding_for_the_same_shortcut() {
+        let mut bindings = no_bindings();
+        bindings.upsert(binding("Alt+1", script("first")));
+        bindings.upsert(binding("Alt+2", script("second")));
+        bindings.upsert(binding("Alt+1", HotkeyAction::UndoClipboardChange));
+
+        assert_eq!(
+            bindings.bindings,
+            vec![
+                binding("Alt+1", HotkeyAction::UndoClipboardChange),
+                binding("Alt+2", script("second")),
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_reports_whether_a_binding_was_removed() {
+        let mut bindings = no_bindings();
+        bindings.upsert(binding("Alt+1", script("first")));
+
+        assert!(bindings.remove("Alt+1"));
+        assert!(!bindings.remove("Alt+1"));
+        assert!(bindings.bindings.is_empty());
+    }
+
+    #[test]
+    fn unbound_shortcuts_only_fall_back_for_the_main_shortcut() {
+        let mut bindings = no_bindings();
+
+        assert_eq!(
+            bindings.action_for(&shortcut(MAIN_SHORTCUT)),
+            Some(HotkeyAction::default())
+        );
+        assert_eq!(
+            bindings.action_for(&shortcut("CommandOrControl+Alt+Q")),
+            None
+        );
+
+        assert!(matches!(
+            HotkeyAction::default(),
+            HotkeyAction::CopyAndClean {
+                auto_rephrase: false,
+                ..
+            }
+        ));
+
+        bindings.upsert(binding(MAIN_SHORTCUT, script("upper")));
+        assert_eq!(
+            bindings.action_for(&shortcut(MAIN_SHORTCUT)),
+            Some(script("upper"))
+        );
+    }
+}
@@ -10,15 +10,56 @@ mod clipboard_monitor;
 mod window;
 mod clipboard_commands;
 mod system;
+mod storage;
+mod transforms;
+mod transform_commands;
+mod hotkeys;
+mod diff;
+mod undo;
+mod rephrase;
+mod rephrase_commands;
+mod lineage;
 
 // Import system functions
 use system::request_input_monitoring_permission;
 
 // Re-export types for external use
 pub use config::{EnvironmentConfig, RephraseRequest, RephraseResponse};
-pub use clipboard::{ClipboardEntry, ClipboardHistory, ClipboardHistoryState};
+pub use clipboard::{detect_content_type, truncate_preview, ClipboardEntry, ClipboardHistory, ClipboardHistoryState};
 pub use clipboard_monitor::ClipboardMonitorState;
 pub use window::WindowState;
+pub use transforms::{cleanup_markdown, cleanup_text, Pipeline, PipelineStep, Transform, TransformStep};
+pub use transforms::unicode::{UnicodeReport, UnicodeScrub};
+pub use transforms::presets::{CleanupPreset, CleanupPresetStore, CleanupPresetsState};
+pub use transforms::links::{CleanUrls, UrlRulesState, UrlRulesStore};
+pub use transforms::replace::{
+    ReplaceDryRun, ReplaceRule, ReplaceRulesState, ReplaceRulesStore, RuleMatchCount,
+};
+pub use transforms::data::{DataAction, DataError, DataFormat, FormatData};
+pub use transforms::encoding::{CaseStyle, Codec, ConvertCase, DecodeJwt, DecodeText, EncodeText};
+pub use transforms::lines::{
+    AlignColumns, DedupeLines, JoinLines, NumberLines, RemoveLineNumbers, ReverseLines,
+    ShuffleLines, SortLines, SortMode, SplitOnDelimiter,
+};
+pub use transforms::scripts::{eval_script, RunScript, ScriptEntry, ScriptInfo, ScriptLimits};
+pub use transforms::tables::{ConvertTable, Table, TableFormat, TableInfo};
+pub use diff::{diff_texts, revert_hunks, ChangeKind, ContentDiff, DiffHunk, DiffLine, DiffSpan};
+pub use rephrase::{
+    cache_key, system_prompt, ApiGuard, ApiGuardConfig, ApiKeyUpdate, CacheStats, CacheSummary,
+    CachedProvider, CapLevel, CircuitBreaker, CircuitState, ClipifyProvider, JobStatus,
+    MeteredProvider, OllamaProvider, OpenAiProvider, PeriodUsage, ProviderConfig, RephraseCache,
+    RephraseCacheConfig,
+    RephraseCacheState, RephraseCandidate, RephraseClient, RephraseClientConfig,
+    RephraseClientState, RephraseError, RephraseJob, RephraseJobMark, RephraseProfile,
+    RephraseProfileStore, RephraseProfilesState, RephraseProvider, RephraseQueue,
+    RephraseQueueState, RephraseTemplate, RephraseTemplateStore, RephraseTemplatesState,
+    RephraseUsageState, ServiceStatus, ServiceStatusAlert, SseDecoder, ThrottleReason, TokenBucket,
+    UsageCap, UsageCapAlert, UsageCapStatus, UsageLog, UsageOutcome, UsageRecord, UsageSummary,
+    MAX_CANDIDATES, REPHRASE_ENDPOINT, USAGE_RETENTION_DAYS,
+};
+pub use lineage::{entry_lineage, EntryLineage, EntryLink, LineageNode, LineageOperation};
+pub use undo::{ClipboardChange, ClipboardUndoStack, ClipboardUndoState, UndoStatus, MAX_UNDO_STEPS};
+pub use hotkeys::{HotkeyAction, HotkeyBinding, HotkeyBindingsState};
 
 // Deep link protocol verification types
 #[derive(Debug, Serialize, Deserialize)]
@@ -131,10 +172,12 @@ pub type DeepLinkEventStoreState = Arc<DeepLinkEventStore>;
 // Import functions from modules
 use clipboard_commands::{
     get_clipboard_history, clear_clipboard_history, paste_from_history, 
-    trigger_clipboard_copy, rephrase_text,
+    trigger_clipboard_copy,
     add_to_clipboard_history, remove_from_clipboard_history, 
     search_clipboard_history, get_clipboard_entry_by_id, copy_selected_text_to_clipboard,
-    start_clipboard_monitoring, stop_clipboard_monitoring
+    start_clipboard_monitoring, stop_clipboard_monitoring, get_entry_diff, revert_diff_hunks,
+    get_entry_lineage, edit_clipboard_entry,
+    undo_last_clipboard_change, redo_clipboard_change, get_clipboard_undo_status
 };
 use system::{
     check_accessibility_permissions, get_macos_version, get_accessibility_instructions, 
@@ -142,6 +185,40 @@ use system::{
 };
 use window::{show_main_window, hide_main_window, toggle_window_visibility};
 use clipboard::load_history_from_file;
+use transform_commands::{
+    get_cleanup_presets, save_cleanup_preset, delete_cleanup_preset,
+    get_default_cleanup_preset, set_default_cleanup_preset, get_code_auto_detect,
+    set_code_auto_detect, get_transform_catalog,
+    cleanup_text_with_preset, scrub_unicode, get_url_rules, save_url_rules, clean_url,
+    format_data, validate_data, detect_data_format, encode_text, decode_text, decode_jwt,
+    convert_case, apply_transform,
+    convert_table, detect_table, get_replace_rules, save_replace_rules, dry_run_replace_rules,
+    list_scripts, get_scripts_folder, run_script
+};
+use transforms::presets::load_presets_from_file;
+use transforms::links::load_url_rules_from_file;
+use transforms::replace::load_replace_rules_from_file;
+use rephrase_commands::{
+    rephrase_text, get_rephrase_profiles, save_rephrase_profile, delete_rephrase_profile,
+    get_default_rephrase_profile, set_default_rephrase_profile, test_rephrase_profile,
+    stream_rephrase, cancel_rephrase, RephraseRequestsState,
+    get_rephrase_jobs, queue_rephrase, retry_rephrase_job, cancel_rephrase_job, start_rephrase_queue,
+    get_rephrase_cache_stats, purge_rephrase_cache, set_rephrase_cache_config,
+    get_rephrase_templates, save_rephrase_template, delete_rephrase_template, render_rephrase_template,
+    generate_rephrase_candidates, choose_rephrase_candidate,
+    get_rephrase_usage_daily, get_rephrase_usage_weekly, get_rephrase_usage_by_provider,
+    get_rephrase_usage_cap, set_rephrase_usage_cap, clear_rephrase_usage,
+    get_rephrase_service_status, service_status_changed, move_api_keys_to_secure_store
+};
+use rephrase::profiles::load_profiles_from_file;
+use rephrase::queue::load_queue_from_file;
+use rephrase::cache::load_cache_from_file;
+use rephrase::templates::load_templates_from_file;
+use rephrase::usage::load_usage_from_file;
+use hotkeys::{
+    get_hotkey_bindings, bind_hotkey, unbind_hotkey, handle_shortcut,
+    load_hotkeys_from_file, register_saved_hotkeys, HotkeyBindings
+};
 
 // Deep link plugin is initialized via tauri_plugin_deep_link::init() in the builder
 
@@ -640,51 +717,13 @@ pub fn run() {
         .plugin(tauri_plugin_global_shortcut::Builder::new()
             .with_handler(|app, shortcut, event| {
                 println!("Global shortcut triggered: {shortcut:?} with event {event:?}");
+                if event.state() != tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                    return;
+                }
                 let app_handle = app.clone();
+                let shortcut = *shortcut;
                 tauri::async_runtime::spawn(async move {
-                    let history_state = app_handle.state::<ClipboardHistoryState>();
-                    match copy_selected_text_to_clipboard(app_handle.clone(), history_state).await {
-                        Ok(text) => {
-                            if !text.is_empty() {
-                                println!("Successfully copied and cleaned text: {} characters", text.len());
-                                
-                                // Emit event to frontend to trigger auto-rephrase
-                                if let Err(e) = app_handle.emit("auto-rephrase-request", &text) {
-                                    eprintln!("Failed to emit auto-rephrase event: {}", e);
-                                }
-                                
-                                // Send success notification with cleaned text preview
-                                let preview = if text.len() > 100 { 
-                                    format!("{}...", &text[..97]) 
-                                } else { 
-                                    text.clone() 
-                                };
-                                
-                                if let Err(e) = tauri_plugin_notification::NotificationExt::notification(&app_handle)
-                                    .builder()
-                                    .title("✅ Text Copied & Cleaned!")
-                                    .body(&format!("Cleaned text ({} chars): {}", text.len(), preview))
-                                    .show() {
-                                    eprintln!("Failed to show success notification: {}", e);
-                                }
-                            } else {
-                                println!("Empty text result from clipboard operation");
-                                // Show notification for empty result
-                                if let Err(e) = tauri_plugin_notification::NotificationExt::notification(&app_handle)
-                                    .builder()
-                                    .title("ℹ️ No Text to Clean")
-                                    .body("The selected text was empty or contained only whitespace.")
-                                    .show() {
-                                    eprintln!("Failed to show empty text notification: {}", e);
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            eprintln!("Error in global shortcut handler: {}", e);
-                            // The error notifications are already handled in copy_selected_text_to_clipboard
-                            // Just log the error here for debugging
-                        }
-                    }
+                    handle_shortcut(app_handle, shortcut).await;
                 });
             })
             .build())
@@ -799,6 +838,102 @@ pub fn run() {
             let history_state = Arc::new(RwLock::new(history));
             app.manage(history_state.clone());
             
+            // Initialize cleanup presets
+            let presets = load_presets_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load cleanup presets: {}", e);
+                CleanupPresetStore::default()
+            });
+            
+            // Initialize URL cleaning rules
+            let url_rules = load_url_rules_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load URL rules: {}", e);
+                UrlRulesStore::default()
+            });
+            let presets_state: CleanupPresetsState = Arc::new(RwLock::new(presets));
+            app.manage(presets_state);
+            let url_rules_state: UrlRulesState = Arc::new(RwLock::new(url_rules));
+            app.manage(url_rules_state);
+            
+            // Initialize find/replace rules
+            let replace_rules = load_replace_rules_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load replace rules: {}", e);
+                ReplaceRulesStore::default()
+            });
+            let replace_rules_state: ReplaceRulesState = Arc::new(RwLock::new(replace_rules));
+            app.manage(replace_rules_state);
+            
+            // Initialize undo/redo of the app's clipboard writes
+            let undo_state: ClipboardUndoState = Arc::new(RwLock::new(ClipboardUndoStack::default()));
+            app.manage(undo_state);
+            
+            // Initialize the rephrase API client
+            let api_base_url = EnvironmentConfig::from_env().api_base_url;
+            // Rate limit and circuit breaker around all calls to the API
+            let guard_app = app.handle().clone();
+            let api_guard = ApiGuard::new(ApiGuardConfig::default()).with_alert(Arc::new(
+                move |status: &ServiceStatus| service_status_changed(&guard_app, status),
+            ));
+            let rephrase_client = RephraseClient::new(&api_base_url, RephraseClientConfig::default())
+                .map_err(|e| format!("Failed to create rephrase client: {}", e))?
+                .with_guard(Arc::new(api_guard));
+            let rephrase_client_state: RephraseClientState = Arc::new(rephrase_client);
+            app.manage(rephrase_client_state);
+            
+            // Initialize rephrase profiles
+            let mut rephrase_profiles = load_profiles_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load rephrase profiles: {}", e);
+                RephraseProfileStore::default()
+            });
+            move_api_keys_to_secure_store(app.handle(), &mut rephrase_profiles);
+            let rephrase_profiles_state: RephraseProfilesState = Arc::new(RwLock::new(rephrase_profiles));
+            app.manage(rephrase_profiles_state);
+            
+            // Initialize rephrase templates
+            let rephrase_templates = load_templates_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load rephrase templates: {}", e);
+                RephraseTemplateStore::default()
+            });
+            let rephrase_templates_state: RephraseTemplatesState = Arc::new(RwLock::new(rephrase_templates));
+            app.manage(rephrase_templates_state);
+            
+            // Initialize the rephrase result cache
+            let rephrase_cache = load_cache_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load rephrase cache: {}", e);
+                RephraseCache::default()
+            });
+            let rephrase_cache_state: RephraseCacheState = Arc::new(RwLock::new(rephrase_cache));
+            app.manage(rephrase_cache_state);
+            
+            // Initialize the log of rephrase calls
+            let rephrase_usage = load_usage_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load rephrase usage: {}", e);
+                UsageLog::default()
+            });
+            let rephrase_usage_state: RephraseUsageState = Arc::new(RwLock::new(rephrase_usage));
+            app.manage(rephrase_usage_state);
+            
+            // Initialize tracking of streaming rephrases
+            let rephrase_requests_state: RephraseRequestsState = Arc::new(RwLock::new(std::collections::HashMap::new()));
+            app.manage(rephrase_requests_state);
+            
+            // Initialize the offline rephrase queue and retry what's left in it
+            let rephrase_queue = load_queue_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load rephrase queue: {}", e);
+                RephraseQueue::default()
+            });
+            let rephrase_queue_state: RephraseQueueState = Arc::new(RwLock::new(rephrase_queue));
+            app.manage(rephrase_queue_state);
+            start_rephrase_queue(app.handle().clone());
+            
+            // Initialize hotkey bindings and register any saved shortcuts
+            let hotkey_bindings = load_hotkeys_from_file().unwrap_or_else(|e| {
+                eprintln!("Failed to load hotkey bindings: {}", e);
+                HotkeyBindings::default()
+            });
+            register_saved_hotkeys(app.handle(), &hotkey_bindings);
+            let hotkeys_state: HotkeyBindingsState = Arc::new(RwLock::new(hotkey_bindings));
+            app.manage(hotkeys_state);
+            
             // Initialize clipboard monitor state
             let monitor_state: ClipboardMonitorState = Arc::new(RwLock::new(None));
             app.manage(monitor_state);
@@ -852,7 +987,7 @@ pub fn run() {
                             let app_handle = app.clone();
                             tauri::async_runtime::spawn(async move {
                                 let history_state = app_handle.state::<ClipboardHistoryState>();
-                                if let Err(e) = copy_selected_text_to_clipboard(app_handle.clone(), history_state).await {
+                                if let Err(e) = copy_selected_text_to_clipboard(app_handle.clone(), history_state, None).await {
                                     eprintln!("Error cleaning clipboard: {}", e);
                                 }
                             });
@@ -861,7 +996,7 @@ pub fn run() {
                             let app_handle = app.clone();
                             tauri::async_runtime::spawn(async move {
                                 let history_state = app_handle.state::<ClipboardHistoryState>();
-                                if let Err(e) = copy_selected_text_to_clipboard(app_handle.clone(), history_state).await {
+                                if let Err(e) = copy_selected_text_to_clipboard(app_handle.clone(), history_state, None).await {
                                     eprintln!("Error triggering shortcut: {}", e);
                                 }
                             });
@@ -909,9 +1044,8 @@ pub fn run() {
                                          let preview = entry.preview.clone();
                                          drop(history); // Release the lock
                                          
-                                         // Copy to clipboard using clipboard plugin
-                                         use tauri_plugin_clipboard_manager::ClipboardExt;
-                                         if let Err(e) = app_handle.clipboard().write_text(content) {
+                                         // Copy to clipboard, recording the write for undo
+                                         if let Err(e) = clipboard_commands::write_clipboard_text(&app_handle, &content, "paste").await {
                                              eprintln!("Error copying to clipboard: {}", e);
                                          } else {
                                              // Show notification that content was copied
@@ -972,11 +1106,98 @@ pub fn run() {
              search_clipboard_history,
              get_clipboard_entry_by_id,
              paste_from_history,
+             get_entry_diff,
+             revert_diff_hunks,
+             get_entry_lineage,
+             edit_clipboard_entry,
+             undo_last_clipboard_change,
+             redo_clipboard_change,
+             get_clipboard_undo_status,
              trigger_clipboard_copy,
-             rephrase_text,
              start_clipboard_monitoring,
              stop_clipboard_monitoring,
              
+             // Rephrase commands
+             rephrase_text,
+             get_rephrase_profiles,
+             save_rephrase_profile,
+             delete_rephrase_profile,
+             get_default_rephrase_profile,
+             set_default_rephrase_profile,
+             test_rephrase_profile,
+             stream_rephrase,
+             cancel_rephrase,
+             get_rephrase_jobs,
+             queue_rephrase,
+             retry_rephrase_job,
+             cancel_rephrase_job,
+             get_rephrase_cache_stats,
+             purge_rephrase_cache,
+             set_rephrase_cache_config,
+             get_rephrase_templates,
+             save_rephrase_template,
+             delete_rephrase_template,
+             render_rephrase_template,
+             generate_rephrase_candidates,
+             choose_rephrase_candidate,
+             get_rephrase_usage_daily,
+             get_rephrase_usage_weekly,
+             get_rephrase_usage_by_provider,
+             get_rephrase_usage_cap,
+             set_rephrase_usage_cap,
+             clear_rephrase_usage,
+             get_rephrase_service_status,
+             
+             // Cleanup preset commands
+             get_cleanup_presets,
+             save_cleanup_preset,
+             delete_cleanup_preset,
+             get_default_cleanup_preset,
+             set_default_cleanup_preset,
+             get_code_auto_detect,
+             set_code_auto_detect,
+             get_transform_catalog,
+             cleanup_text_with_preset,
+             scrub_unicode,
+             
+             // URL rule commands
+             get_url_rules,
+             save_url_rules,
+             clean_url,
+             
+             // Replace rule commands
+             get_replace_rules,
+             save_replace_rules,
+             dry_run_replace_rules,
+             
+             // Structured data commands
+             format_data,
+             validate_data,
+             detect_data_format,
+             
+             // Encoding commands
+             encode_text,
+             decode_text,
+             decode_jwt,
+             convert_case,
+             
+             // Transform commands
+             apply_transform,
+             
+             // Table commands
+             convert_table,
+             detect_table,
+             
+             // Script commands
+             list_scripts,
+             get_scripts_folder,
+             run_script,
+             
+             // Hotkey binding commands
+             get_hotkey_bindings,
+             bind_hotkey,
+             unbind_hotkey,
+             
              // Window commands
              show_main_window,
              hide_main_window,
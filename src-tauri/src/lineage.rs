@@ -0,0 +1,119 @@
+// How history entries derive from each other: each derived entry links to its parent
+// with the operation that produced it, so a text can be traced from the original copy
+// through cleanup, rephrasing and edits.
+use crate::clipboard::ClipboardEntry;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineageOperation {
+    Cleanup,
+    Transform,
+    Revert,
+    Rephrase,
+    RephraseCandidate,
+    ManualEdit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryLink {
+    pub parent_id: String,
+    pub operation: LineageOperation,
+    // Settings of the operation, e.g. the cleanup preset or rephrase style
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EntryLink {
+    pub fn new(parent_id: &str, operation: LineageOperation) -> Self {
+        EntryLink {
+            parent_id: parent_id.to_string(),
+            operation,
+            params: BTreeMap::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_param(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.params.insert(name.to_string(), value.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineageNode {
+    pub entry: ClipboardEntry,
+    pub children: Vec<LineageNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryLineage {
+    // The oldest ancestor still in history, with all its descendants
+    pub root: LineageNode,
+    // Entry ids from the root down to the requested entry
+    pub path: Vec<String>,
+    // Set when the root's own parent has been removed from history
+    pub missing_parent_id: Option<String>,
+}
+
+fn find<'a>(entries: &'a [ClipboardEntry], id: &str) -> Option<&'a ClipboardEntry> {
+    entries.iter().find(|entry| entry.id == id)
+}
+
+fn build_node(
+    entries: &[ClipboardEntry],
+    entry: &ClipboardEntry,
+    seen: &mut HashSet<String>,
+) -> LineageNode {
+    seen.insert(entry.id.clone());
+    let mut children: Vec<&ClipboardEntry> = entries
+        .iter()
+        .filter(|child| {
+            child
+                .parent
+                .as_ref()
+                .is_some_and(|link| link.parent_id == entry.id)
+                && !seen.contains(&child.id)
+        })
+        .collect();
+    children.sort_by_key(|child| child.timestamp);
+
+    LineageNode {
+        entry: entry.clone(),
+        children: children
+            .into_iter()
+            .map(|child| build_node(entries, child, seen))
+            .collect(),
+    }
+}
+
+// The full tree an entry belongs to, or None if the entry isn't in history
+pub fn entry_lineage(entries: &[ClipboardEntry], id: &str) -> Option<EntryLineage> {
+    let mut current = find(entries, id)?;
+    let mut path = vec![current.id.clone()];
+    let mut missing_parent_id = None;
+    while let Some(link) = &current.parent {
+        match find(entries, &link.parent_id) {
+            // Links are only ever made to older entries, but stay safe against cycles
+            Some(parent) if !path.contains(&parent.id) => {
+                path.push(parent.id.clone());
+                current = parent;
+            }
+            Some(_) => break,
+            None => {
+                missing_parent_id = Some(link.parent_id.clone());
+                break;
+            }
+        }
+    }
+    path.reverse();
+
+    Some(EntryLineage {
+        root: build_node(entries, current, &mut HashSet::new()),
+        path,
+        missing_parent_id,
+    })
+}
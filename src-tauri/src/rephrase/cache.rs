@@ -0,0 +1,255 @@
+// Rephrase results kept on disk, so rephrasing the same text with the same options and
+// provider again doesn't spend quota. Entries are keyed by a hash; source text isn't stored.
+use super::{ProviderConfig, RephraseError, RephraseProvider};
+use crate::config::{RephraseRequest, RephraseResponse};
+use crate::storage::{load_json_file, save_json_file};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const CACHE_FILE: &str = "rephrase_cache.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RephraseCacheConfig {
+    pub enabled: bool,
+    pub ttl_hours: u32,
+    pub max_entries: usize,
+    // Total size of the cached texts
+    pub max_bytes: usize,
+}
+
+impl Default for RephraseCacheConfig {
+    fn default() -> Self {
+        RephraseCacheConfig {
+            enabled: true,
+            ttl_hours: 7 * 24,
+            max_entries: 500,
+            max_bytes: 2 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedRephrase {
+    pub key: String,
+    pub response: RephraseResponse,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+}
+
+impl CachedRephrase {
+    fn size(&self) -> usize {
+        self.key.len() + self.response.rephrased_text.len()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    // Dropped to stay within the size limits
+    pub evictions: u64,
+    pub expired: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheSummary {
+    pub entries: usize,
+    pub bytes: usize,
+    pub hit_rate: f64,
+    pub stats: CacheStats,
+    pub config: RephraseCacheConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RephraseCache {
+    pub config: RephraseCacheConfig,
+    pub entries: Vec<CachedRephrase>,
+    pub stats: CacheStats,
+}
+
+// Hash everything that affects the result: the provider setup and the request
+pub fn cache_key(provider: &ProviderConfig, request: &RephraseRequest) -> String {
+    let material = serde_json::to_string(&(provider, request)).unwrap_or_default();
+    Sha256::digest(material.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+impl RephraseCache {
+    pub fn get(&mut self, key: &str, now: DateTime<Utc>) -> Option<RephraseResponse> {
+        if !self.config.enabled {
+            return None;
+        }
+        self.remove_expired(now);
+        match self.entries.iter_mut().find(|entry| entry.key == key) {
+            Some(entry) => {
+                entry.last_used_at = now;
+                self.stats.hits += 1;
+                Some(entry.response.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: String, response: RephraseResponse, now: DateTime<Utc>) {
+        if !self.config.enabled {
+            return;
+        }
+        self.entries.retain(|entry| entry.key != key);
+        self.entries.push(CachedRephrase {
+            key,
+            response,
+            created_at: now,
+            last_used_at: now,
+        });
+        self.enforce_limits(now);
+    }
+
+    pub fn remove_expired(&mut self, now: DateTime<Utc>) {
+        let ttl = Duration::hours(self.config.ttl_hours as i64);
+        let before = self.entries.len();
+        self.entries.retain(|entry| now - entry.created_at < ttl);
+        self.stats.expired += (before - self.entries.len()) as u64;
+    }
+
+    // Drop expired entries, then the least recently used until within the limits
+    pub fn enforce_limits(&mut self, now: DateTime<Utc>) {
+        self.remove_expired(now);
+        self.entries.sort_by_key(|entry| entry.last_used_at);
+        let mut bytes = self.bytes();
+        let mut evict = 0;
+        while evict < self.entries.len()
+            && (self.entries.len() - evict > self.config.max_entries
+                || bytes > self.config.max_bytes)
+        {
+            bytes -= self.entries[evict].size();
+            evict += 1;
+        }
+        self.entries.drain(..evict);
+        self.stats.evictions += evict as u64;
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.entries.iter().map(CachedRephrase::size).sum()
+    }
+
+    // Remove every cached result; returns how many there were
+    pub fn purge(&mut self) -> usize {
+        let removed = self.entries.len();
+        self.entries.clear();
+        removed
+    }
+
+    pub fn summary(&self) -> CacheSummary {
+        let lookups = self.stats.hits + self.stats.misses;
+        CacheSummary {
+            entries: self.entries.len(),
+            bytes: self.bytes(),
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                self.stats.hits as f64 / lookups as f64
+            },
+            stats: self.stats.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+pub type RephraseCacheState = Arc<RwLock<RephraseCache>>;
+
+pub fn load_cache_from_file() -> io::Result<RephraseCache> {
+    load_json_file(CACHE_FILE)
+}
+
+pub fn save_cache_to_file(cache: &RephraseCache) -> io::Result<()> {
+    save_json_file(CACHE_FILE, cache)
+}
+
+// Serves repeated requests from the cache and stores new results
+pub struct CachedProvider {
+    pub inner: Box<dyn RephraseProvider>,
+    pub provider: ProviderConfig,
+    pub cache: RephraseCacheState,
+}
+
+impl CachedProvider {
+    // Lookups only touch memory; hit counts and recency are written with the next insert
+    async fn lookup(&self, key: &str) -> Option<RephraseResponse> {
+        let mut cache = self.cache.write().await;
+        cache.get(key, Utc::now())
+    }
+
+    async fn store(&self, key: String, response: &RephraseResponse) {
+        let mut cache = self.cache.write().await;
+        cache.insert(key, response.clone(), Utc::now());
+        if let Err(e) = save_cache_to_file(&cache) {
+            eprintln!("Failed to save rephrase cache: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl RephraseProvider for CachedProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn rephrase(&self, request: &RephraseRequest) -> Result<RephraseResponse, RephraseError> {
+        let key = cache_key(&self.provider, request);
+        if let Some(response) = self.lookup(&key).await {
+            return Ok(response);
+        }
+        let response = self.inner.rephrase(request).await?;
+        self.store(key, &response).await;
+        Ok(response)
+    }
+
+    async fn rephrase_streaming(
+        &self,
+        request: &RephraseRequest,
+        on_delta: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<RephraseResponse, RephraseError> {
+        let key = cache_key(&self.provider, request);
+        if let Some(response) = self.lookup(&key).await {
+            on_delta(&response.rephrased_text);
+            return Ok(response);
+        }
+        let response = self.inner.rephrase_streaming(request, on_delta).await?;
+        self.store(key, &response).await;
+        Ok(response)
+    }
+
+    fn batches_candidates(&self) -> bool {
+        self.inner.batches_candidates()
+    }
+
+    // Alternatives are wanted precisely because they differ, so they're never cached
+    async fn rephrase_variant(
+        &self,
+        request: &RephraseRequest,
+        variant: u32,
+    ) -> Result<RephraseResponse, RephraseError> {
+        self.inner.rephrase_variant(request, variant).await
+    }
+
+    async fn rephrase_candidates(
+        &self,
+        request: &RephraseRequest,
+        count: usize,
+    ) -> Result<Vec<RephraseResponse>, RephraseError> {
+        self.inner.rephrase_candidates(request, count).await
+    }
+}
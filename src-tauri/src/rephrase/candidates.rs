@@ -0,0 +1,48 @@
+// Alternative rephrasings of one text, offered side by side so the user can pick one
+use crate::config::{RephraseRequest, RephraseResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// More than this rarely differ meaningfully and multiplies quota use
+pub const MAX_CANDIDATES: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RephraseCandidate {
+    pub id: String,
+    pub text: String,
+    // Provider identifier, e.g. "openai_compatible"
+    pub provider: String,
+    // Rephrase profile the candidate was made with; None for the default profile
+    #[serde(default)]
+    pub profile: Option<String>,
+    pub style: String,
+    pub context: String,
+    pub target_audience: String,
+    #[serde(default)]
+    pub processing_time_ms: Option<u64>,
+    pub char_count: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RephraseCandidate {
+    pub fn new(
+        response: RephraseResponse,
+        request: &RephraseRequest,
+        provider: &str,
+        profile: Option<String>,
+    ) -> Self {
+        RephraseCandidate {
+            id: Uuid::new_v4().to_string(),
+            char_count: response.rephrased_text.chars().count(),
+            text: response.rephrased_text,
+            provider: provider.to_string(),
+            profile,
+            style: request.style.clone(),
+            context: request.context.clone(),
+            target_audience: request.target_audience.clone(),
+            processing_time_ms: response.processing_time_ms,
+            created_at: Utc::now(),
+        }
+    }
+}
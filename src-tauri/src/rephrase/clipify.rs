@@ -0,0 +1,181 @@
+// Client for the Clipify rephrase API, so rephrasing works without the webview
+use super::{
+    is_event_stream, parse_body, ApiGuard, RephraseClientConfig, RephraseError, RephraseProvider,
+    SseDecoder, Transport,
+};
+use crate::config::{RephraseRequest, RephraseResponse};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub const REPHRASE_ENDPOINT: &str = "/api/v1/protected/rephrase";
+
+// One event of a streamed rephrase: `delta` while text arrives, then the full response
+#[derive(Debug, Deserialize)]
+struct StreamEvent {
+    #[serde(default)]
+    delta: Option<String>,
+    #[serde(default)]
+    rephrased_text: Option<String>,
+    #[serde(default)]
+    processing_time_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RephraseClient {
+    transport: Transport,
+    base_url: String,
+}
+
+impl RephraseClient {
+    pub fn new(base_url: &str, config: RephraseClientConfig) -> Result<Self, RephraseError> {
+        Ok(Self {
+            transport: Transport::new(config)?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    // Send every request through `guard`
+    pub fn with_guard(mut self, guard: Arc<ApiGuard>) -> Self {
+        self.transport.guard = Some(guard);
+        self
+    }
+
+    pub fn guard(&self) -> Option<&Arc<ApiGuard>> {
+        self.transport.guard.as_ref()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn config(&self) -> &RephraseClientConfig {
+        &self.transport.config
+    }
+
+    fn url(&self, jwt_token: &str) -> Result<String, RephraseError> {
+        if jwt_token.trim().is_empty() {
+            return Err(RephraseError::Unauthorized {
+                message: "JWT token is required for rephrasing".to_string(),
+            });
+        }
+        Ok(format!("{}{}", self.base_url, REPHRASE_ENDPOINT))
+    }
+
+    // Rephrase `request` with the user's JWT, retrying transient failures with backoff
+    pub async fn rephrase(
+        &self,
+        jwt_token: &str,
+        request: &RephraseRequest,
+    ) -> Result<RephraseResponse, RephraseError> {
+        let url = self.url(jwt_token)?;
+        let body = self
+            .transport
+            .post_json("clipify", &url, Some(jwt_token), request)
+            .await?;
+        parse_body(&body)
+    }
+
+    // Like `rephrase`, asking for server-sent events. A plain JSON answer is accepted too
+    // and delivered as a single delta.
+    pub async fn rephrase_streaming(
+        &self,
+        jwt_token: &str,
+        request: &RephraseRequest,
+        on_delta: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<RephraseResponse, RephraseError> {
+        let url = self.url(jwt_token)?;
+        let started = Instant::now();
+        let response = self
+            .transport
+            .post_streaming(
+                "clipify",
+                &url,
+                Some(jwt_token),
+                request,
+                "text/event-stream, application/json",
+            )
+            .await?;
+
+        if !is_event_stream(&response) {
+            let body = self.transport.read_body(response).await?;
+            let response: RephraseResponse = parse_body(&body)?;
+            on_delta(&response.rephrased_text);
+            return Ok(response);
+        }
+
+        let mut text = String::new();
+        let mut processing_time_ms = None;
+        let mut decoder = SseDecoder::default();
+        let mut handle_event = |data: String| -> Result<(), RephraseError> {
+            if data == "[DONE]" {
+                return Ok(());
+            }
+            let event: StreamEvent = parse_body(&data)?;
+            if let Some(delta) = event.delta {
+                on_delta(&delta);
+                text.push_str(&delta);
+            }
+            if let Some(full) = event.rephrased_text {
+                text = full;
+            }
+            processing_time_ms = event.processing_time_ms.or(processing_time_ms);
+            Ok(())
+        };
+        self.transport
+            .read_lines(response, |line| match decoder.push_line(line) {
+                Some(data) => handle_event(data),
+                None => Ok(()),
+            })
+            .await?;
+        if let Some(data) = decoder.finish() {
+            handle_event(data)?;
+        }
+
+        Ok(RephraseResponse {
+            processing_time_ms: processing_time_ms
+                .or_else(|| Some(started.elapsed().as_millis() as u64)),
+            rephrased_text: text,
+        })
+    }
+}
+
+pub type RephraseClientState = Arc<RephraseClient>;
+
+// The Clipify backend as a provider, signed in as the current user
+pub struct ClipifyProvider {
+    pub client: Arc<RephraseClient>,
+    pub jwt_token: String,
+}
+
+#[async_trait]
+impl RephraseProvider for ClipifyProvider {
+    fn name(&self) -> &'static str {
+        "clipify"
+    }
+
+    async fn rephrase(&self, request: &RephraseRequest) -> Result<RephraseResponse, RephraseError> {
+        self.client.rephrase(&self.jwt_token, request).await
+    }
+
+    async fn rephrase_streaming(
+        &self,
+        request: &RephraseRequest,
+        on_delta: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<RephraseResponse, RephraseError> {
+        self.client
+            .rephrase_streaming(&self.jwt_token, request, on_delta)
+            .await
+    }
+
+    // The backend takes no seed, but it samples each request anew, so alternatives are
+    // plain repeated requests and the identical ones get dropped
+    async fn rephrase_variant(
+        &self,
+        request: &RephraseRequest,
+        _variant: u32,
+    ) -> Result<RephraseResponse, RephraseError> {
+        self.rephrase(request).await
+    }
+}
@@ -0,0 +1,320 @@
+// Client-side protection for the Clipify API. A token bucket caps how often requests go
+// out, and a circuit breaker stops sending for a while once the service keeps failing,
+// so hotkey presses can't hammer a backend that is down.
+use super::{RephraseError, ThrottleReason};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiGuardConfig {
+    // Requests that may go out at once after a quiet period
+    pub burst: u32,
+    // Sustained request rate
+    pub requests_per_minute: u32,
+    // Consecutive failures after which the circuit opens
+    pub failure_threshold: u32,
+    // How long to stop sending once open, before letting a single request through
+    pub open_secs: u64,
+}
+
+impl Default for ApiGuardConfig {
+    fn default() -> Self {
+        ApiGuardConfig {
+            burst: 5,
+            requests_per_minute: 20,
+            failure_threshold: 5,
+            open_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, per_minute: u32, now: Instant) -> Self {
+        TokenBucket {
+            capacity: capacity.max(1) as f64,
+            per_second: per_minute.max(1) as f64 / 60.0,
+            tokens: capacity.max(1) as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+    }
+
+    // Ok if a token is available, otherwise how long until one is
+    pub fn check(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.per_second,
+            ))
+        }
+    }
+
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.check(now)?;
+        self.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    // Requests go out normally
+    Closed,
+    // The service kept failing; requests are held back
+    Open,
+    // Trying a single request to see whether the service recovered
+    HalfOpen,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    open_for: Duration,
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+    // When the request testing a half-open circuit went out
+    probe_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, open_for: Duration, now: Instant) -> Self {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            open_for,
+            state: CircuitState::Closed,
+            failures: 0,
+            opened_at: now,
+            probe_started: None,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    // Time left before an open circuit lets a request through
+    pub fn retry_after(&self, now: Instant) -> Option<Duration> {
+        match self.state {
+            CircuitState::Closed => None,
+            CircuitState::Open => Some(
+                self.open_for
+                    .saturating_sub(now.saturating_duration_since(self.opened_at)),
+            ),
+            CircuitState::HalfOpen => self
+                .probe_started
+                .map(|started| {
+                    self.open_for
+                        .saturating_sub(now.saturating_duration_since(started))
+                })
+                .filter(|left| !left.is_zero()),
+        }
+    }
+
+    // Ok with the new state if letting a request through changed it, otherwise how long
+    // to wait. A half-open circuit lets one request through at a time; a probe that never
+    // reported back is given up after the open period.
+    pub fn allow(&mut self, now: Instant) -> Result<Option<CircuitState>, Duration> {
+        if let Some(wait) = self.retry_after(now).filter(|wait| !wait.is_zero()) {
+            return Err(wait);
+        }
+        match self.state {
+            CircuitState::Closed => Ok(None),
+            CircuitState::Open => {
+                self.state = CircuitState::HalfOpen;
+                self.probe_started = Some(now);
+                Ok(Some(CircuitState::HalfOpen))
+            }
+            CircuitState::HalfOpen => {
+                self.probe_started = Some(now);
+                Ok(None)
+            }
+        }
+    }
+
+    // The new state if it changed
+    pub fn on_success(&mut self) -> Option<CircuitState> {
+        self.failures = 0;
+        self.probe_started = None;
+        (self.state != CircuitState::Closed).then(|| {
+            self.state = CircuitState::Closed;
+            self.state
+        })
+    }
+
+    // The new state if it changed
+    pub fn on_failure(&mut self, now: Instant) -> Option<CircuitState> {
+        self.failures = self.failures.saturating_add(1);
+        self.probe_started = None;
+        let open = match self.state {
+            CircuitState::Closed => self.failures >= self.threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        open.then(|| {
+            self.state = CircuitState::Open;
+            self.opened_at = now;
+            self.state
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    // While held back, time until requests go out again
+    pub retry_after_ms: Option<u64>,
+}
+
+impl ServiceStatus {
+    pub fn is_degraded(&self) -> bool {
+        self.state != CircuitState::Closed
+    }
+}
+
+// Called whenever the circuit changes state
+pub type ServiceStatusAlert = Arc<dyn Fn(&ServiceStatus) + Send + Sync>;
+
+struct GuardState {
+    bucket: TokenBucket,
+    breaker: CircuitBreaker,
+}
+
+impl GuardState {
+    fn status(&self, now: Instant) -> ServiceStatus {
+        ServiceStatus {
+            state: self.breaker.state(),
+            consecutive_failures: self.breaker.failures(),
+            retry_after_ms: self
+                .breaker
+                .retry_after(now)
+                .map(|wait| wait.as_millis() as u64),
+        }
+    }
+}
+
+// Rate limit and circuit breaker shared by every client of one API
+pub struct ApiGuard {
+    config: ApiGuardConfig,
+    state: Mutex<GuardState>,
+    on_change: Option<ServiceStatusAlert>,
+}
+
+impl fmt::Debug for ApiGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiGuard")
+            .field("config", &self.config)
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+impl ApiGuard {
+    pub fn new(config: ApiGuardConfig) -> Self {
+        let now = Instant::now();
+        ApiGuard {
+            state: Mutex::new(GuardState {
+                bucket: TokenBucket::new(config.burst, config.requests_per_minute, now),
+                breaker: CircuitBreaker::new(
+                    config.failure_threshold,
+                    Duration::from_secs(config.open_secs),
+                    now,
+                ),
+            }),
+            config,
+            on_change: None,
+        }
+    }
+
+    pub fn with_alert(mut self, on_change: ServiceStatusAlert) -> Self {
+        self.on_change = Some(on_change);
+        self
+    }
+
+    pub fn config(&self) -> &ApiGuardConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> ServiceStatus {
+        self.state.lock().unwrap().status(Instant::now())
+    }
+
+    fn notify(&self, status: Option<ServiceStatus>) {
+        if let (Some(status), Some(on_change)) = (status, &self.on_change) {
+            on_change(&status);
+        }
+    }
+
+    // Take a slot for one request, or fail with how long to hold back
+    pub fn acquire(&self) -> Result<(), RephraseError> {
+        let now = Instant::now();
+        let changed = {
+            let mut state = self.state.lock().unwrap();
+            // Rate limit first, so a waiting half-open circuit doesn't use up its probe
+            if let Err(wait) = state.bucket.check(now) {
+                return Err(RephraseError::Throttled {
+                    reason: ThrottleReason::RateLimit,
+                    retry_after_ms: wait.as_millis() as u64,
+                });
+            }
+            let changed = match state.breaker.allow(now) {
+                Ok(changed) => changed,
+                Err(wait) => {
+                    return Err(RephraseError::Throttled {
+                        reason: ThrottleReason::ServiceDegraded,
+                        retry_after_ms: wait.as_millis() as u64,
+                    })
+                }
+            };
+            let _ = state.bucket.try_acquire(now);
+            changed.map(|_| state.status(now))
+        };
+        self.notify(changed);
+        Ok(())
+    }
+
+    // Report how a request went. Only failures that point at the service being down or
+    // overloaded count against it; rejected requests show it is up.
+    pub fn record<T>(&self, result: &Result<T, RephraseError>) {
+        let now = Instant::now();
+        let changed = {
+            let mut state = self.state.lock().unwrap();
+            let changed = match result {
+                Err(
+                    RephraseError::Network { .. }
+                    | RephraseError::Timeout
+                    | RephraseError::Server { .. }
+                    | RephraseError::RateLimited { .. },
+                ) => state.breaker.on_failure(now),
+                Err(RephraseError::Throttled { .. }) => None,
+                _ => state.breaker.on_success(),
+            };
+            changed.map(|_| state.status(now))
+        };
+        self.notify(changed);
+    }
+}
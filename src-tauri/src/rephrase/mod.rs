@@ -0,0 +1,624 @@
+// Rephrasing through pluggable providers: the Clipify backend, any OpenAI-compatible
+// chat completions endpoint, or a local Ollama server. Providers are configured in
+// rephrase profiles, so text can be kept off hosted services where needed.
+
+pub mod cache;
+pub mod candidates;
+pub mod clipify;
+pub mod guard;
+pub mod ollama;
+pub mod openai;
+pub mod profiles;
+pub mod queue;
+pub mod templates;
+pub mod usage;
+
+use crate::config::{RephraseRequest, RephraseResponse};
+use async_trait::async_trait;
+use futures::future::join_all;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+pub use cache::{
+    cache_key, CacheStats, CacheSummary, CachedProvider, RephraseCache, RephraseCacheConfig,
+    RephraseCacheState,
+};
+pub use candidates::{RephraseCandidate, MAX_CANDIDATES};
+pub use clipify::{ClipifyProvider, RephraseClient, RephraseClientState, REPHRASE_ENDPOINT};
+pub use guard::{
+    ApiGuard, ApiGuardConfig, CircuitBreaker, CircuitState, ServiceStatus, ServiceStatusAlert,
+    TokenBucket,
+};
+pub use ollama::{OllamaProvider, DEFAULT_OLLAMA_URL};
+pub use openai::OpenAiProvider;
+pub use profiles::{
+    ApiKeyUpdate, ProviderConfig, RephraseProfile, RephraseProfileStore, RephraseProfilesState,
+};
+pub use queue::{JobStatus, RephraseJob, RephraseJobMark, RephraseQueue, RephraseQueueState};
+pub use templates::{RephraseTemplate, RephraseTemplateStore, RephraseTemplatesState};
+pub use usage::{
+    CapLevel, MeteredProvider, PeriodUsage, RephraseUsageState, UsageCap, UsageCapAlert,
+    UsageCapStatus, UsageLog, UsageOutcome, UsageRecord, UsageSummary, USAGE_RETENTION_DAYS,
+};
+
+#[async_trait]
+pub trait RephraseProvider: Send + Sync {
+    // Short identifier used in logs, e.g. "clipify" or "ollama"
+    fn name(&self) -> &'static str;
+
+    async fn rephrase(&self, request: &RephraseRequest) -> Result<RephraseResponse, RephraseError>;
+
+    // Rephrase, passing each piece of text to `on_delta` as it arrives. Providers that
+    // can't stream deliver the whole text as one piece.
+    async fn rephrase_streaming(
+        &self,
+        request: &RephraseRequest,
+        on_delta: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<RephraseResponse, RephraseError> {
+        let response = self.rephrase(request).await?;
+        on_delta(&response.rephrased_text);
+        Ok(response)
+    }
+
+    // Whether `rephrase_candidates` asks for all candidates in a single request
+    fn batches_candidates(&self) -> bool {
+        false
+    }
+
+    // One of several alternative rephrasings. `variant` is an arbitrary number that different
+    // variants differ in, e.g. to seed sampling; providers with no way to vary their output
+    // can't offer alternatives.
+    async fn rephrase_variant(
+        &self,
+        _request: &RephraseRequest,
+        _variant: u32,
+    ) -> Result<RephraseResponse, RephraseError> {
+        Err(RephraseError::Unsupported {
+            message: format!("{} can't suggest alternative rephrasings", self.name()),
+        })
+    }
+
+    // Up to `count` different rephrasings, by default one `rephrase_variant` request each
+    async fn rephrase_candidates(
+        &self,
+        request: &RephraseRequest,
+        count: usize,
+    ) -> Result<Vec<RephraseResponse>, RephraseError> {
+        variant_candidates(self, request, count).await
+    }
+}
+
+// Sampling temperature for alternatives; high enough that different seeds give different text
+const VARIANT_TEMPERATURE: f64 = 0.8;
+
+// Send `count` variants at once, numbered from a random offset so asking again gives new
+// candidates. Repeated texts are dropped and failures are only an error when no variant
+// succeeded.
+pub async fn variant_candidates<P: RephraseProvider + ?Sized>(
+    provider: &P,
+    request: &RephraseRequest,
+    count: usize,
+) -> Result<Vec<RephraseResponse>, RephraseError> {
+    let offset = Uuid::new_v4().as_u128() as u32;
+    let variants = (0..count as u32)
+        .map(|variant| provider.rephrase_variant(request, offset.wrapping_add(variant)));
+    let results = join_all(variants).await;
+    let mut responses: Vec<RephraseResponse> = Vec::new();
+    let mut first_error = None;
+    for result in results {
+        match result {
+            Ok(response) => {
+                if !responses
+                    .iter()
+                    .any(|r| r.rephrased_text == response.rephrased_text)
+                {
+                    responses.push(response);
+                }
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if responses.is_empty() => Err(e),
+        _ => Ok(responses),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RephraseError {
+    // No JWT or API key was available, or the provider rejected it
+    Unauthorized {
+        message: String,
+    },
+    // The provider asked us to slow down; `retry_after_ms` comes from Retry-After
+    RateLimited {
+        retry_after_ms: Option<u64>,
+    },
+    // The request didn't complete within the configured timeout
+    Timeout,
+    // Connection failures, DNS errors and the like
+    Network {
+        message: String,
+    },
+    // 5xx responses
+    Server {
+        status: u16,
+        message: String,
+    },
+    // Other 4xx responses, e.g. text over the plan's word limit or an unknown model
+    Rejected {
+        status: u16,
+        message: String,
+    },
+    // A 2xx response whose body isn't what the provider should return
+    InvalidResponse {
+        message: String,
+    },
+    // Held back on our side without being sent
+    Throttled {
+        reason: ThrottleReason,
+        retry_after_ms: u64,
+    },
+    // The provider can't do what was asked, e.g. suggest alternatives
+    Unsupported {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleReason {
+    // The client-side rate limit was reached
+    RateLimit,
+    // The circuit breaker is open after repeated failures
+    ServiceDegraded,
+}
+
+impl RephraseError {
+    // Whether trying the same request again could succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            RephraseError::RateLimited { .. }
+                | RephraseError::Timeout
+                | RephraseError::Network { .. }
+                | RephraseError::Server { .. }
+                | RephraseError::Throttled { .. }
+        )
+    }
+
+    // Short label for the kind of error, e.g. for usage records
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RephraseError::Unauthorized { .. } => "unauthorized",
+            RephraseError::RateLimited { .. } => "rate_limited",
+            RephraseError::Timeout => "timeout",
+            RephraseError::Network { .. } => "network",
+            RephraseError::Server { .. } => "server",
+            RephraseError::Rejected { .. } => "rejected",
+            RephraseError::InvalidResponse { .. } => "invalid_response",
+            RephraseError::Throttled { .. } => "throttled",
+            RephraseError::Unsupported { .. } => "unsupported",
+        }
+    }
+
+    fn from_reqwest(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            RephraseError::Timeout
+        } else if error.is_decode() {
+            RephraseError::InvalidResponse {
+                message: error.to_string(),
+            }
+        } else {
+            RephraseError::Network {
+                message: error.to_string(),
+            }
+        }
+    }
+}
+
+impl fmt::Display for RephraseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RephraseError::Unauthorized { message } => {
+                write!(f, "Not signed in or session expired: {}", message)
+            }
+            RephraseError::RateLimited { .. } => {
+                write!(f, "Too many rephrase requests, try again later")
+            }
+            RephraseError::Timeout => write!(f, "Rephrase request timed out"),
+            RephraseError::Network { message } => {
+                write!(f, "Could not reach the rephrase service: {}", message)
+            }
+            RephraseError::Server { status, message } => {
+                write!(f, "Rephrase service error ({}): {}", status, message)
+            }
+            RephraseError::Rejected { status, message } => {
+                write!(f, "Rephrase request rejected ({}): {}", status, message)
+            }
+            RephraseError::InvalidResponse { message } => {
+                write!(f, "Invalid response from rephrase service: {}", message)
+            }
+            RephraseError::Throttled {
+                reason,
+                retry_after_ms,
+            } => {
+                let secs = retry_after_ms.div_ceil(1000).max(1);
+                match reason {
+                    ThrottleReason::RateLimit => {
+                        write!(f, "Rephrasing too often, try again in {}s", secs)
+                    }
+                    ThrottleReason::ServiceDegraded => write!(
+                        f,
+                        "The rephrase service is degraded, try again in {}s",
+                        secs
+                    ),
+                }
+            }
+            RephraseError::Unsupported { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for RephraseError {}
+
+// HTTP settings shared by all providers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RephraseClientConfig {
+    // Whole-request timeout, per attempt
+    pub timeout_ms: u64,
+    // Streamed responses may take longer overall, as long as text keeps arriving
+    // at least every `timeout_ms`
+    pub stream_timeout_ms: u64,
+    pub connect_timeout_ms: u64,
+    // Attempts after the first one, for retryable errors only
+    pub max_retries: u32,
+    // Delay before the first retry; doubles on each further retry
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RephraseClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 30_000,
+            stream_timeout_ms: 300_000,
+            connect_timeout_ms: 10_000,
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8_000,
+        }
+    }
+}
+
+impl RephraseClientConfig {
+    // Delay before retry number `retry` (0-based), honouring a server-sent Retry-After
+    pub fn backoff(&self, retry: u32, error: &RephraseError) -> Duration {
+        let exponential = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << retry.min(20))
+            .min(self.max_backoff_ms);
+        let delay = match error {
+            RephraseError::RateLimited {
+                retry_after_ms: Some(retry_after),
+            } => (*retry_after).min(self.max_backoff_ms).max(exponential),
+            _ => exponential,
+        };
+        Duration::from_millis(delay)
+    }
+
+    // Run `attempt` until it succeeds, fails permanently or runs out of retries
+    async fn with_retries<T, F, Fut>(
+        &self,
+        provider: &str,
+        mut attempt: F,
+    ) -> Result<T, RephraseError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, RephraseError>>,
+    {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Ok(value) => return Ok(value),
+                // Held back requests are left to the caller rather than retried in a loop
+                Err(e @ RephraseError::Throttled { .. }) => return Err(e),
+                Err(e) if e.is_retryable() && retry < self.max_retries => {
+                    let delay = self.backoff(retry, &e);
+                    println!(
+                        "[Rephrase] {} attempt {} failed ({}), retrying in {} ms",
+                        provider,
+                        retry + 1,
+                        e,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// An HTTP client with a provider's timeouts and retry policy
+#[derive(Debug, Clone)]
+struct Transport {
+    http: reqwest::Client,
+    config: RephraseClientConfig,
+    // Rate limit and circuit breaker every request attempt goes through, if any
+    guard: Option<Arc<ApiGuard>>,
+}
+
+impl Transport {
+    fn new(config: RephraseClientConfig) -> Result<Self, RephraseError> {
+        let http = reqwest::Client::builder()
+            .read_timeout(Duration::from_millis(config.timeout_ms))
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .build()
+            .map_err(|e| RephraseError::Network {
+                message: e.to_string(),
+            })?;
+        Ok(Self {
+            http,
+            config,
+            guard: None,
+        })
+    }
+
+    // POST a JSON body and return the response body, retrying transient failures
+    async fn post_json<T: Serialize + ?Sized>(
+        &self,
+        provider: &str,
+        url: &str,
+        bearer_token: Option<&str>,
+        body: &T,
+    ) -> Result<String, RephraseError> {
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        self.config
+            .with_retries(provider, || async {
+                let response = self.send(url, bearer_token, body, timeout, None).await?;
+                self.read_body(response).await
+            })
+            .await
+    }
+
+    // POST a JSON body and return the response once its headers arrive, so the body can
+    // be read as it streams in with `read_lines` or `read_body`. Only failures before that
+    // point are retried.
+    async fn post_streaming<T: Serialize + ?Sized>(
+        &self,
+        provider: &str,
+        url: &str,
+        bearer_token: Option<&str>,
+        body: &T,
+        accept: &str,
+    ) -> Result<reqwest::Response, RephraseError> {
+        let timeout = Duration::from_millis(self.config.stream_timeout_ms);
+        self.config
+            .with_retries(provider, || {
+                self.send(url, bearer_token, body, timeout, Some(accept))
+            })
+            .await
+    }
+
+    async fn send<T: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        bearer_token: Option<&str>,
+        body: &T,
+        timeout: Duration,
+        accept: Option<&str>,
+    ) -> Result<reqwest::Response, RephraseError> {
+        if let Some(guard) = &self.guard {
+            guard.acquire()?;
+        }
+        let result = self
+            .send_once(url, bearer_token, body, timeout, accept)
+            .await;
+        // A response is only reported once its body has been read, so a stream that
+        // breaks off counts against the service
+        if result.is_err() {
+            self.record(&result);
+        }
+        result
+    }
+
+    fn record<T>(&self, result: &Result<T, RephraseError>) {
+        if let Some(guard) = &self.guard {
+            guard.record(result);
+        }
+    }
+
+    // Read the whole body of a response from `send`
+    async fn read_body(&self, response: reqwest::Response) -> Result<String, RephraseError> {
+        let result = response.text().await.map_err(RephraseError::from_reqwest);
+        self.record(&result);
+        result
+    }
+
+    // Pass each line of a streamed body to `on_line` as soon as it is complete
+    async fn read_lines<F>(
+        &self,
+        response: reqwest::Response,
+        on_line: F,
+    ) -> Result<(), RephraseError>
+    where
+        F: FnMut(&str) -> Result<(), RephraseError>,
+    {
+        let result = split_lines(response, on_line).await;
+        self.record(&result);
+        result
+    }
+
+    async fn send_once<T: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        bearer_token: Option<&str>,
+        body: &T,
+        timeout: Duration,
+        accept: Option<&str>,
+    ) -> Result<reqwest::Response, RephraseError> {
+        let mut request = self.http.post(url).timeout(timeout).json(body);
+        if let Some(token) = bearer_token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        let response = request.send().await.map_err(RephraseError::from_reqwest)?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let retry_after_ms = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(|seconds| seconds * 1000);
+        let message = error_message(&response.text().await.unwrap_or_default(), status);
+
+        Err(match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                RephraseError::Unauthorized { message }
+            }
+            StatusCode::TOO_MANY_REQUESTS => RephraseError::RateLimited { retry_after_ms },
+            StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => RephraseError::Timeout,
+            _ if status.is_server_error() => RephraseError::Server {
+                status: status.as_u16(),
+                message,
+            },
+            _ => RephraseError::Rejected {
+                status: status.as_u16(),
+                message,
+            },
+        })
+    }
+}
+
+// Whether a response is a server-sent event stream rather than a single JSON body
+fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"))
+}
+
+async fn split_lines<F>(
+    mut response: reqwest::Response,
+    mut on_line: F,
+) -> Result<(), RephraseError>
+where
+    F: FnMut(&str) -> Result<(), RephraseError>,
+{
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(RephraseError::from_reqwest)?
+    {
+        buffer.extend_from_slice(&chunk);
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            on_line(decode_line(&line)?)?;
+        }
+    }
+    if !buffer.is_empty() {
+        on_line(decode_line(&buffer)?)?;
+    }
+    Ok(())
+}
+
+fn decode_line(line: &[u8]) -> Result<&str, RephraseError> {
+    std::str::from_utf8(line)
+        .map(|line| line.trim_end_matches(['\r', '\n']))
+        .map_err(|e| RephraseError::InvalidResponse {
+            message: e.to_string(),
+        })
+}
+
+// Collects the `data:` lines of server-sent events; each blank line ends an event
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    // Feed one line; returns the event's data when the line completes an event
+    pub fn push_line(&mut self, line: &str) -> Option<String> {
+        if line.is_empty() {
+            return self.finish();
+        }
+        if let Some(value) = line.strip_prefix("data:") {
+            self.data
+                .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+        // Comments (":"), "event:", "id:" and "retry:" fields aren't needed
+        None
+    }
+
+    // Data of an event left open when the stream ended
+    pub fn finish(&mut self) -> Option<String> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut self.data).join("\n"))
+    }
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, RephraseError> {
+    serde_json::from_str(body).map_err(|e| RephraseError::InvalidResponse {
+        message: e.to_string(),
+    })
+}
+
+// APIs report errors as {"error": "..."}, {"error": {"message": "..."}} or {"message": "..."};
+// fall back to the raw body
+fn error_message(body: &str, status: StatusCode) -> String {
+    let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+    let message = parsed.as_ref().and_then(|value| {
+        let nested = value.get("error").and_then(|error| error.get("message"));
+        ["error", "message", "detail"]
+            .iter()
+            .filter_map(|key| value.get(key))
+            .chain(nested)
+            .find_map(|v| v.as_str())
+    });
+    match message {
+        Some(message) => message.to_string(),
+        None if !body.trim().is_empty() => body.trim().to_string(),
+        None => status
+            .canonical_reason()
+            .unwrap_or("Unknown error")
+            .to_string(),
+    }
+}
+
+// Instructions for chat-model providers, built from the request's style options
+pub fn system_prompt(request: &RephraseRequest) -> String {
+    let mut prompt = format!(
+        "Rephrase the user's text in a {} style for {}. Context: {}.",
+        request.style, request.target_audience, request.context
+    );
+    if request.preserve_length {
+        prompt.push_str(" Keep roughly the same length as the original.");
+    }
+    prompt.push_str(
+        " Keep the original meaning and language. Reply with only the rephrased text, without quotes or commentary.",
+    );
+    prompt
+}
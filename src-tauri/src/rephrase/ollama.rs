@@ -0,0 +1,131 @@
+// A local Ollama server (or anything speaking its /api/chat API), so text never
+// leaves the machine
+use super::{
+    parse_body, system_prompt, RephraseClientConfig, RephraseError, RephraseProvider, Transport,
+    VARIANT_TEMPERATURE,
+};
+use crate::config::{RephraseRequest, RephraseResponse};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+// A whole response, or one line of a streamed one
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    message: ChatMessage,
+    // Nanoseconds the server spent on the request; sent with the last line when streaming
+    #[serde(default)]
+    total_duration: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+pub struct OllamaProvider {
+    transport: Transport,
+    url: String,
+    model: String,
+}
+
+impl OllamaProvider {
+    pub fn new(
+        base_url: &str,
+        model: &str,
+        config: RephraseClientConfig,
+    ) -> Result<Self, RephraseError> {
+        Ok(Self {
+            transport: Transport::new(config)?,
+            url: format!("{}/api/chat", base_url.trim_end_matches('/')),
+            model: model.to_string(),
+        })
+    }
+
+    fn body(&self, request: &RephraseRequest, stream: bool) -> Value {
+        json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": system_prompt(request) },
+                { "role": "user", "content": request.text },
+            ],
+            "stream": stream,
+        })
+    }
+
+    async fn complete(&self, body: &Value) -> Result<RephraseResponse, RephraseError> {
+        let response = self
+            .transport
+            .post_json(self.name(), &self.url, None, body)
+            .await?;
+        let response: ChatResponse = parse_body(&response)?;
+
+        Ok(RephraseResponse {
+            processing_time_ms: response.total_duration.map(|ns| ns / 1_000_000),
+            rephrased_text: response.message.content.trim().to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl RephraseProvider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    async fn rephrase(&self, request: &RephraseRequest) -> Result<RephraseResponse, RephraseError> {
+        self.complete(&self.body(request, false)).await
+    }
+
+    async fn rephrase_variant(
+        &self,
+        request: &RephraseRequest,
+        variant: u32,
+    ) -> Result<RephraseResponse, RephraseError> {
+        let mut body = self.body(request, false);
+        body["options"] = json!({ "seed": variant, "temperature": VARIANT_TEMPERATURE });
+        self.complete(&body).await
+    }
+
+    // Ollama streams newline-delimited JSON objects
+    async fn rephrase_streaming(
+        &self,
+        request: &RephraseRequest,
+        on_delta: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<RephraseResponse, RephraseError> {
+        let response = self
+            .transport
+            .post_streaming(
+                self.name(),
+                &self.url,
+                None,
+                &self.body(request, true),
+                "application/x-ndjson",
+            )
+            .await?;
+
+        let mut text = String::new();
+        let mut total_duration = None;
+        self.transport
+            .read_lines(response, |line| {
+                if line.trim().is_empty() {
+                    return Ok(());
+                }
+                let chunk: ChatResponse = parse_body(line)?;
+                if !chunk.message.content.is_empty() {
+                    on_delta(&chunk.message.content);
+                    text.push_str(&chunk.message.content);
+                }
+                total_duration = chunk.total_duration.or(total_duration);
+                Ok(())
+            })
+            .await?;
+
+        Ok(RephraseResponse {
+            processing_time_ms: total_duration.map(|ns| ns / 1_000_000),
+            rephrased_text: text.trim().to_string(),
+        })
+    }
+}
@@ -0,0 +1,222 @@
+// Any server implementing OpenAI's chat completions API: OpenAI itself, Azure-style
+// gateways, LM Studio, vLLM, llama.cpp's server and the like
+use super::{
+    parse_body, system_prompt, variant_candidates, RephraseClientConfig, RephraseError,
+    RephraseProvider, SseDecoder, Transport, VARIANT_TEMPERATURE,
+};
+use crate::config::{RephraseRequest, RephraseResponse};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Instant;
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    // `message` in whole responses, `delta` in streamed chunks
+    #[serde(default, alias = "delta")]
+    message: Option<ChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl ChatCompletion {
+    fn content(self) -> Option<String> {
+        self.contents().into_iter().next()
+    }
+
+    fn contents(self) -> Vec<String> {
+        self.choices
+            .into_iter()
+            .filter_map(|choice| choice.message.and_then(|message| message.content))
+            .collect()
+    }
+}
+
+pub struct OpenAiProvider {
+    transport: Transport,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    temperature: Option<f64>,
+}
+
+impl OpenAiProvider {
+    // `base_url` is the server root, with or without a trailing "/v1"
+    pub fn new(
+        base_url: &str,
+        model: &str,
+        api_key: Option<String>,
+        temperature: Option<f64>,
+        config: RephraseClientConfig,
+    ) -> Result<Self, RephraseError> {
+        let root = base_url.trim_end_matches('/').trim_end_matches("/v1");
+        Ok(Self {
+            transport: Transport::new(config)?,
+            url: format!("{}/v1/chat/completions", root),
+            model: model.to_string(),
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            temperature,
+        })
+    }
+
+    async fn complete(&self, body: &Value) -> Result<ChatCompletion, RephraseError> {
+        let response = self
+            .transport
+            .post_json(self.name(), &self.url, self.api_key.as_deref(), body)
+            .await?;
+        parse_body(&response)
+    }
+
+    async fn rephrase_with(&self, body: &Value) -> Result<RephraseResponse, RephraseError> {
+        let started = Instant::now();
+        let text =
+            self.complete(body)
+                .await?
+                .content()
+                .ok_or_else(|| RephraseError::InvalidResponse {
+                    message: "Completion has no message content".to_string(),
+                })?;
+        Ok(RephraseResponse {
+            processing_time_ms: Some(started.elapsed().as_millis() as u64),
+            rephrased_text: text.trim().to_string(),
+        })
+    }
+
+    fn body(&self, request: &RephraseRequest, stream: bool) -> Value {
+        let mut body = json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": system_prompt(request) },
+                { "role": "user", "content": request.text },
+            ],
+            "stream": stream,
+        });
+        if let Some(temperature) = self.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        body
+    }
+}
+
+#[async_trait]
+impl RephraseProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai_compatible"
+    }
+
+    async fn rephrase(&self, request: &RephraseRequest) -> Result<RephraseResponse, RephraseError> {
+        self.rephrase_with(&self.body(request, false)).await
+    }
+
+    fn batches_candidates(&self) -> bool {
+        true
+    }
+
+    // Seeded where the server supports it, at the profile's temperature or one that varies
+    async fn rephrase_variant(
+        &self,
+        request: &RephraseRequest,
+        variant: u32,
+    ) -> Result<RephraseResponse, RephraseError> {
+        let mut body = self.body(request, false);
+        body["seed"] = json!(variant);
+        body["temperature"] = json!(self.temperature.unwrap_or(VARIANT_TEMPERATURE));
+        self.rephrase_with(&body).await
+    }
+
+    // Ask for all of them in one completion with `n`, and make up the rest with variants
+    // when the server sends back fewer choices
+    async fn rephrase_candidates(
+        &self,
+        request: &RephraseRequest,
+        count: usize,
+    ) -> Result<Vec<RephraseResponse>, RephraseError> {
+        let started = Instant::now();
+        let mut body = self.body(request, false);
+        body["n"] = json!(count);
+        let texts = self.complete(&body).await?.contents();
+        let processing_time_ms = Some(started.elapsed().as_millis() as u64);
+        let missing = count.saturating_sub(texts.len());
+
+        let mut responses: Vec<RephraseResponse> = texts
+            .into_iter()
+            .map(|text| RephraseResponse {
+                processing_time_ms,
+                rephrased_text: text.trim().to_string(),
+            })
+            .collect();
+        if missing > 0 {
+            match variant_candidates(self, request, missing).await {
+                Ok(variants) => responses.extend(variants),
+                Err(e) if responses.is_empty() => return Err(e),
+                Err(_) => {}
+            }
+        }
+
+        let mut candidates: Vec<RephraseResponse> = Vec::new();
+        for response in responses {
+            if !candidates
+                .iter()
+                .any(|c| c.rephrased_text == response.rephrased_text)
+            {
+                candidates.push(response);
+            }
+        }
+        if candidates.is_empty() {
+            return Err(RephraseError::InvalidResponse {
+                message: "Completion has no message content".to_string(),
+            });
+        }
+        Ok(candidates)
+    }
+
+    async fn rephrase_streaming(
+        &self,
+        request: &RephraseRequest,
+        on_delta: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<RephraseResponse, RephraseError> {
+        let started = Instant::now();
+        let response = self
+            .transport
+            .post_streaming(
+                self.name(),
+                &self.url,
+                self.api_key.as_deref(),
+                &self.body(request, true),
+                "text/event-stream",
+            )
+            .await?;
+
+        let mut text = String::new();
+        let mut decoder = SseDecoder::default();
+        self.transport
+            .read_lines(response, |line| {
+                let Some(data) = decoder.push_line(line) else {
+                    return Ok(());
+                };
+                if data == "[DONE]" {
+                    return Ok(());
+                }
+                if let Some(delta) = parse_body::<ChatCompletion>(&data)?.content() {
+                    on_delta(&delta);
+                    text.push_str(&delta);
+                }
+                Ok(())
+            })
+            .await?;
+
+        Ok(RephraseResponse {
+            processing_time_ms: Some(started.elapsed().as_millis() as u64),
+            rephrased_text: text.trim().to_string(),
+        })
+    }
+}
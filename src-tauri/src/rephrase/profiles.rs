@@ -0,0 +1,291 @@
+use super::{
+    ClipifyProvider, OllamaProvider, OpenAiProvider, RephraseClient, RephraseClientConfig,
+    RephraseClientState, RephraseError, RephraseProvider, DEFAULT_OLLAMA_URL,
+};
+use crate::storage::{load_json_file, save_json_file};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use url::Url;
+
+const PROFILES_FILE: &str = "rephrase_profiles.json";
+
+pub const CLIPIFY_PROFILE: &str = "clipify";
+
+fn default_ollama_url() -> String {
+    DEFAULT_OLLAMA_URL.to_string()
+}
+
+// Which service rephrases text, and where to reach it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum ProviderConfig {
+    // The Clipify backend; `base_url` overrides the environment's API URL
+    Clipify {
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible {
+        base_url: String,
+        model: String,
+        // A key entered with the profile, or read from an old profile file. Keys live in
+        // the secure store, so this is never written out or sent back to the webview.
+        #[serde(default, skip_serializing)]
+        api_key: Option<String>,
+        // Whether the secure store holds a key for this profile
+        #[serde(default)]
+        has_api_key: bool,
+        #[serde(default)]
+        temperature: Option<f64>,
+    },
+    Ollama {
+        #[serde(default = "default_ollama_url")]
+        base_url: String,
+        model: String,
+    },
+}
+
+// What saving a profile does to its API key in the secure store
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiKeyUpdate {
+    Keep,
+    Set(String),
+    Remove,
+}
+
+impl ProviderConfig {
+    // Take the API key entered with the profile out of it. No key keeps the stored one,
+    // an empty key removes it, and providers without keys never keep one.
+    pub fn take_api_key(&mut self) -> ApiKeyUpdate {
+        match self {
+            ProviderConfig::OpenAiCompatible { api_key, .. } => match api_key.take() {
+                None => ApiKeyUpdate::Keep,
+                Some(key) if key.trim().is_empty() => ApiKeyUpdate::Remove,
+                Some(key) => ApiKeyUpdate::Set(key.trim().to_string()),
+            },
+            _ => ApiKeyUpdate::Remove,
+        }
+    }
+
+    pub fn has_api_key(&self) -> bool {
+        matches!(
+            self,
+            ProviderConfig::OpenAiCompatible {
+                has_api_key: true,
+                ..
+            }
+        )
+    }
+
+    fn set_has_api_key(&mut self, has_key: bool) {
+        if let ProviderConfig::OpenAiCompatible { has_api_key, .. } = self {
+            *has_api_key = has_key;
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let (base_url, model) = match self {
+            ProviderConfig::Clipify { base_url } => (base_url.as_deref(), None),
+            ProviderConfig::OpenAiCompatible {
+                base_url, model, ..
+            }
+            | ProviderConfig::Ollama { base_url, model } => (Some(base_url.as_str()), Some(model)),
+        };
+        if let Some(base_url) = base_url {
+            Url::parse(base_url).map_err(|e| format!("Invalid URL '{}': {}", base_url, e))?;
+        }
+        if model.is_some_and(|model| model.trim().is_empty()) {
+            return Err("A model name is required".to_string());
+        }
+        Ok(())
+    }
+
+    // Create the provider. `credential` is the JWT for Clipify and the API key for
+    // OpenAI-compatible APIs. The shared Clipify client is reused unless the profile points
+    // it elsewhere or changes its HTTP settings; a client for the same API still shares
+    // its rate limit and circuit breaker.
+    pub fn build(
+        &self,
+        settings: &RephraseClientConfig,
+        clipify: &RephraseClientState,
+        credential: Option<String>,
+    ) -> Result<Box<dyn RephraseProvider>, RephraseError> {
+        Ok(match self {
+            ProviderConfig::Clipify { base_url } => {
+                let base_url = base_url.as_deref().unwrap_or(clipify.base_url());
+                let same_api = base_url.trim_end_matches('/') == clipify.base_url();
+                let client = if same_api && settings == clipify.config() {
+                    clipify.clone()
+                } else {
+                    let mut client = RephraseClient::new(base_url, settings.clone())?;
+                    if let Some(guard) = clipify.guard().filter(|_| same_api) {
+                        client = client.with_guard(guard.clone());
+                    }
+                    Arc::new(client)
+                };
+                Box::new(ClipifyProvider {
+                    client,
+                    jwt_token: credential.unwrap_or_default(),
+                })
+            }
+            ProviderConfig::OpenAiCompatible {
+                base_url,
+                model,
+                temperature,
+                ..
+            } => Box::new(OpenAiProvider::new(
+                base_url,
+                model,
+                credential,
+                *temperature,
+                settings.clone(),
+            )?),
+            ProviderConfig::Ollama { base_url, model } => {
+                Box::new(OllamaProvider::new(base_url, model, settings.clone())?)
+            }
+        })
+    }
+}
+
+// A named provider setup that rephrase commands and hotkeys can pick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RephraseProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(flatten)]
+    pub provider: ProviderConfig,
+    #[serde(default)]
+    pub settings: RephraseClientConfig,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+// Profiles shipped with the app; they cannot be overwritten or deleted
+pub fn builtin_profiles() -> Vec<RephraseProfile> {
+    vec![RephraseProfile {
+        name: CLIPIFY_PROFILE.to_string(),
+        description: "Rephrase with the Clipify service using your account".to_string(),
+        provider: ProviderConfig::Clipify { base_url: None },
+        settings: RephraseClientConfig::default(),
+        builtin: true,
+    }]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RephraseProfileStore {
+    // User-defined profiles only; builtins are merged in at lookup time
+    pub profiles: Vec<RephraseProfile>,
+    pub default_profile: String,
+}
+
+impl Default for RephraseProfileStore {
+    fn default() -> Self {
+        RephraseProfileStore {
+            profiles: Vec::new(),
+            default_profile: CLIPIFY_PROFILE.to_string(),
+        }
+    }
+}
+
+impl RephraseProfileStore {
+    pub fn all(&self) -> Vec<RephraseProfile> {
+        let mut profiles = builtin_profiles();
+        profiles.extend(self.profiles.iter().cloned());
+        profiles
+    }
+
+    pub fn get(&self, name: &str) -> Option<RephraseProfile> {
+        self.all().into_iter().find(|p| p.name == name)
+    }
+
+    // Resolve an explicit profile, or the user's default when none is given
+    pub fn profile_for(&self, name: Option<&str>) -> Result<RephraseProfile, String> {
+        let name = name.unwrap_or(&self.default_profile);
+        self.get(name)
+            .ok_or_else(|| format!("Rephrase profile '{}' not found", name))
+    }
+
+    // Add or replace a user profile. The returned update says what to do with the API key
+    // that came with it, which is taken out of the profile.
+    pub fn upsert(&mut self, mut profile: RephraseProfile) -> Result<ApiKeyUpdate, String> {
+        if profile.name.trim().is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+        if builtin_profiles().iter().any(|p| p.name == profile.name) {
+            return Err(format!(
+                "Cannot overwrite built-in profile '{}'",
+                profile.name
+            ));
+        }
+        profile.provider.validate()?;
+
+        let update = profile.provider.take_api_key();
+        let had_key = self
+            .profiles
+            .iter()
+            .any(|p| p.name == profile.name && p.provider.has_api_key());
+        profile.provider.set_has_api_key(match update {
+            ApiKeyUpdate::Keep => had_key,
+            ApiKeyUpdate::Set(_) => true,
+            ApiKeyUpdate::Remove => false,
+        });
+
+        profile.builtin = false;
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+        Ok(update)
+    }
+
+    // Take keys still stored in profiles (from files written before keys moved to the
+    // secure store), returning them by profile name
+    pub fn take_api_keys(&mut self) -> Vec<(String, String)> {
+        let mut keys = Vec::new();
+        for profile in &mut self.profiles {
+            if let ApiKeyUpdate::Set(key) = profile.provider.take_api_key() {
+                profile.provider.set_has_api_key(true);
+                keys.push((profile.name.clone(), key));
+            }
+        }
+        keys
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<bool, String> {
+        if builtin_profiles().iter().any(|p| p.name == name) {
+            return Err(format!("Cannot delete built-in profile '{}'", name));
+        }
+
+        let original_len = self.profiles.len();
+        self.profiles.retain(|p| p.name != name);
+
+        // Fall back to Clipify if the default was removed
+        if self.default_profile == name {
+            self.default_profile = CLIPIFY_PROFILE.to_string();
+        }
+
+        Ok(self.profiles.len() != original_len)
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<(), String> {
+        if self.get(name).is_none() {
+            return Err(format!("Rephrase profile '{}' not found", name));
+        }
+        self.default_profile = name.to_string();
+        Ok(())
+    }
+}
+
+pub type RephraseProfilesState = Arc<RwLock<RephraseProfileStore>>;
+
+pub fn load_profiles_from_file() -> io::Result<RephraseProfileStore> {
+    load_json_file(PROFILES_FILE)
+}
+
+pub fn save_profiles_to_file(store: &RephraseProfileStore) -> io::Result<()> {
+    save_json_file(PROFILES_FILE, store)
+}
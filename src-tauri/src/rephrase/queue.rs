@@ -0,0 +1,222 @@
+// Rephrase jobs that couldn't run because the provider was unreachable. They are kept on
+// disk and retried in the background until they succeed, fail for good or are cancelled.
+use super::RephraseError;
+use crate::config::RephraseRequest;
+use crate::storage::{load_json_file, save_json_file};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+const QUEUE_FILE: &str = "rephrase_queue.json";
+
+// How often to check for connectivity while the provider is unreachable
+pub const OFFLINE_RETRY_SECS: i64 = 15;
+// Server errors and rate limits count as attempts; connectivity errors don't
+pub const MAX_JOB_ATTEMPTS: u32 = 8;
+const MAX_JOB_BACKOFF_SECS: i64 = 30 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RephraseJob {
+    pub id: String,
+    // The history entry whose text is rephrased, if it came from history
+    pub entry_id: Option<String>,
+    // The text and the options to rephrase it with
+    pub request: RephraseRequest,
+    // None for the default rephrase profile
+    pub profile: Option<String>,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+// What happened to a queued rephrase, recorded on the entry it was made from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RephraseJobMark {
+    pub job_id: String,
+    pub status: JobStatus,
+    // The history entry holding the rephrased text, once completed
+    #[serde(default)]
+    pub result_entry_id: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RephraseJob {
+    pub fn mark(&self, status: JobStatus, result_entry_id: Option<String>) -> RephraseJobMark {
+        RephraseJobMark {
+            job_id: self.id.clone(),
+            status,
+            result_entry_id,
+            error: self.last_error.clone(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RephraseQueue {
+    pub jobs: Vec<RephraseJob>,
+}
+
+impl RephraseQueue {
+    pub fn enqueue(
+        &mut self,
+        entry_id: Option<String>,
+        request: RephraseRequest,
+        profile: Option<String>,
+        now: DateTime<Utc>,
+    ) -> RephraseJob {
+        let job = RephraseJob {
+            id: Uuid::new_v4().to_string(),
+            entry_id,
+            request,
+            profile,
+            status: JobStatus::Queued,
+            attempts: 0,
+            last_error: None,
+            created_at: now,
+            next_attempt_at: now,
+        };
+        self.jobs.push(job.clone());
+        job
+    }
+
+    pub fn get(&self, id: &str) -> Option<&RephraseJob> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    // Claim the oldest job that is due, marking it running
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Option<RephraseJob> {
+        let job = self
+            .jobs
+            .iter_mut()
+            .filter(|job| job.status == JobStatus::Queued && job.next_attempt_at <= now)
+            .min_by_key(|job| job.next_attempt_at)?;
+        job.status = JobStatus::Running;
+        Some(job.clone())
+    }
+
+    // Remove a finished job; None if it was cancelled meanwhile
+    pub fn complete(&mut self, id: &str) -> Option<RephraseJob> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        let mut job = self.jobs.remove(index);
+        job.status = JobStatus::Completed;
+        Some(job)
+    }
+
+    // Record a failed attempt. Retryable errors put the job back in the queue, others
+    // (and running out of attempts) leave it failed until retried by hand.
+    pub fn fail(
+        &mut self,
+        id: &str,
+        error: &RephraseError,
+        now: DateTime<Utc>,
+    ) -> Option<RephraseJob> {
+        let job = self.jobs.iter_mut().find(|job| job.id == id)?;
+        job.last_error = Some(error.to_string());
+
+        let delay = match error {
+            RephraseError::Network { .. } | RephraseError::Timeout => {
+                Some(Duration::seconds(OFFLINE_RETRY_SECS))
+            }
+            // Never sent, so it doesn't count as an attempt
+            RephraseError::Throttled { retry_after_ms, .. } => {
+                Some(Duration::milliseconds(*retry_after_ms as i64).max(Duration::seconds(1)))
+            }
+            RephraseError::RateLimited { .. } | RephraseError::Server { .. } => {
+                job.attempts += 1;
+                (job.attempts < MAX_JOB_ATTEMPTS).then(|| job_backoff(job.attempts, error))
+            }
+            _ => None,
+        };
+        match delay {
+            Some(delay) => {
+                job.status = JobStatus::Queued;
+                job.next_attempt_at = now + delay;
+            }
+            None => job.status = JobStatus::Failed,
+        }
+        Some(job.clone())
+    }
+
+    // Stop retrying a job that can't succeed as configured, e.g. its profile was deleted
+    pub fn give_up(&mut self, id: &str, message: String) -> Option<RephraseJob> {
+        let job = self.jobs.iter_mut().find(|job| job.id == id)?;
+        job.status = JobStatus::Failed;
+        job.last_error = Some(message);
+        Some(job.clone())
+    }
+
+    // Queue a job again right away, with a fresh attempt count
+    pub fn retry(&mut self, id: &str, now: DateTime<Utc>) -> Result<RephraseJob, String> {
+        let job = self
+            .jobs
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| format!("Rephrase job {} not found", id))?;
+        if job.status == JobStatus::Running {
+            return Err(format!("Rephrase job {} is already running", id));
+        }
+        job.status = JobStatus::Queued;
+        job.attempts = 0;
+        job.next_attempt_at = now;
+        Ok(job.clone())
+    }
+
+    pub fn cancel(&mut self, id: &str) -> Option<RephraseJob> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        let mut job = self.jobs.remove(index);
+        job.status = JobStatus::Cancelled;
+        Some(job)
+    }
+
+    // Jobs left running when the app quit never finished; queue them again
+    pub fn requeue_interrupted(&mut self) {
+        for job in &mut self.jobs {
+            if job.status == JobStatus::Running {
+                job.status = JobStatus::Queued;
+            }
+        }
+    }
+}
+
+// Wait the server's Retry-After if given, otherwise 30s doubling per attempt, capped
+fn job_backoff(attempts: u32, error: &RephraseError) -> Duration {
+    if let RephraseError::RateLimited {
+        retry_after_ms: Some(ms),
+    } = error
+    {
+        return Duration::milliseconds(*ms as i64);
+    }
+    let secs = 30i64.saturating_mul(1 << attempts.saturating_sub(1).min(16));
+    Duration::seconds(secs.min(MAX_JOB_BACKOFF_SECS))
+}
+
+pub type RephraseQueueState = Arc<RwLock<RephraseQueue>>;
+
+pub fn load_queue_from_file() -> io::Result<RephraseQueue> {
+    let mut queue: RephraseQueue = load_json_file(QUEUE_FILE)?;
+    queue.requeue_interrupted();
+    Ok(queue)
+}
+
+pub fn save_queue_to_file(queue: &RephraseQueue) -> io::Result<()> {
+    save_json_file(QUEUE_FILE, queue)
+}
@@ -0,0 +1,230 @@
+// Saved combinations of rephrase options, e.g. "formal email" or "release note". The
+// options may contain {{variable}} placeholders filled in when the template is used.
+use crate::config::RephraseRequest;
+use crate::storage::{load_json_file, save_json_file};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+
+const TEMPLATES_FILE: &str = "rephrase_templates.json";
+
+fn placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").unwrap())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RephraseTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub style: String,
+    #[serde(default)]
+    pub context: String,
+    #[serde(default)]
+    pub target_audience: String,
+    #[serde(default)]
+    pub preserve_length: bool,
+    // Placeholder names with their default values; an empty default must be filled in
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    // Rephrase profile to use; the default profile when unset
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+impl RephraseTemplate {
+    fn fields(&self) -> [&str; 3] {
+        [&self.style, &self.context, &self.target_audience]
+    }
+
+    // Names of all placeholders used in the template's options
+    pub fn placeholders(&self) -> BTreeSet<String> {
+        self.fields()
+            .iter()
+            .flat_map(|field| placeholder_regex().captures_iter(field))
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Template name cannot be empty".to_string());
+        }
+        if self.style.trim().is_empty() {
+            return Err("Template style cannot be empty".to_string());
+        }
+        let undeclared: Vec<String> = self
+            .placeholders()
+            .into_iter()
+            .filter(|name| !self.variables.contains_key(name))
+            .collect();
+        if !undeclared.is_empty() {
+            return Err(format!(
+                "Undeclared template variables: {}",
+                undeclared.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    fn value_of<'v>(
+        &'v self,
+        name: &str,
+        values: &'v HashMap<String, String>,
+    ) -> Option<&'v String> {
+        values
+            .get(name)
+            .or_else(|| self.variables.get(name))
+            .filter(|value| !value.trim().is_empty())
+    }
+
+    // Placeholders that neither `values` nor the defaults fill in
+    pub fn missing_variables(&self, values: &HashMap<String, String>) -> Vec<String> {
+        self.placeholders()
+            .into_iter()
+            .filter(|name| self.value_of(name, values).is_none())
+            .collect()
+    }
+
+    // Build the request for `text`, filling placeholders from `values` or the defaults
+    pub fn render(
+        &self,
+        text: &str,
+        values: &HashMap<String, String>,
+    ) -> Result<RephraseRequest, String> {
+        let missing = self.missing_variables(values);
+        if !missing.is_empty() {
+            return Err(format!(
+                "Missing values for template variables: {}",
+                missing.join(", ")
+            ));
+        }
+
+        let fill = |field: &str| {
+            placeholder_regex()
+                .replace_all(field, |caps: &regex::Captures| {
+                    self.value_of(&caps[1], values).cloned().unwrap_or_default()
+                })
+                .into_owned()
+        };
+        Ok(RephraseRequest {
+            text: text.to_string(),
+            style: fill(&self.style),
+            context: fill(&self.context),
+            target_audience: fill(&self.target_audience),
+            preserve_length: self.preserve_length,
+        })
+    }
+}
+
+fn builtin_template(
+    name: &str,
+    description: &str,
+    options: [&str; 3],
+    variables: &[(&str, &str)],
+) -> RephraseTemplate {
+    RephraseTemplate {
+        name: name.to_string(),
+        description: description.to_string(),
+        style: options[0].to_string(),
+        context: options[1].to_string(),
+        target_audience: options[2].to_string(),
+        preserve_length: false,
+        variables: variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        profile: None,
+        builtin: true,
+    }
+}
+
+// Templates shipped with the app; they cannot be overwritten or deleted
+pub fn builtin_templates() -> Vec<RephraseTemplate> {
+    vec![
+        builtin_template(
+            "formal email",
+            "A polite, complete email",
+            ["formal", "An email to {{recipient}}", "{{recipient}}"],
+            &[("recipient", "a colleague")],
+        ),
+        builtin_template(
+            "slack casual",
+            "A short, friendly chat message",
+            ["casual", "A Slack message", "Teammates"],
+            &[],
+        ),
+        builtin_template(
+            "release note",
+            "A concise, user-facing change description",
+            [
+                "concise",
+                "Release notes for {{product}}",
+                "Users of {{product}}",
+            ],
+            &[("product", "")],
+        ),
+    ]
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RephraseTemplateStore {
+    // User-defined templates only; builtins are merged in at lookup time
+    pub templates: Vec<RephraseTemplate>,
+}
+
+impl RephraseTemplateStore {
+    pub fn all(&self) -> Vec<RephraseTemplate> {
+        let mut templates = builtin_templates();
+        templates.extend(self.templates.iter().cloned());
+        templates
+    }
+
+    pub fn get(&self, name: &str) -> Option<RephraseTemplate> {
+        self.all().into_iter().find(|t| t.name == name)
+    }
+
+    pub fn upsert(&mut self, mut template: RephraseTemplate) -> Result<(), String> {
+        template.validate()?;
+        if builtin_templates().iter().any(|t| t.name == template.name) {
+            return Err(format!(
+                "Cannot overwrite built-in template '{}'",
+                template.name
+            ));
+        }
+
+        template.builtin = false;
+        match self.templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<bool, String> {
+        if builtin_templates().iter().any(|t| t.name == name) {
+            return Err(format!("Cannot delete built-in template '{}'", name));
+        }
+
+        let original_len = self.templates.len();
+        self.templates.retain(|t| t.name != name);
+        Ok(self.templates.len() != original_len)
+    }
+}
+
+pub type RephraseTemplatesState = Arc<RwLock<RephraseTemplateStore>>;
+
+pub fn load_templates_from_file() -> io::Result<RephraseTemplateStore> {
+    load_json_file(TEMPLATES_FILE)
+}
+
+pub fn save_templates_to_file(store: &RephraseTemplateStore) -> io::Result<()> {
+    save_json_file(TEMPLATES_FILE, store)
+}
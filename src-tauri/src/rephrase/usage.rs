@@ -0,0 +1,393 @@
+// Every call made to a rephrase provider, kept locally so usage can be summarised per day,
+// week and provider, with an optional soft cap that warns before a daily limit is passed.
+// Days are UTC days, like all timestamps in history.
+use super::{variant_candidates, RephraseError, RephraseProvider};
+use crate::config::{RephraseRequest, RephraseResponse};
+use crate::storage::{load_json_file, save_json_file};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+const USAGE_FILE: &str = "rephrase_usage.json";
+
+// Older records are dropped; summaries never look further back
+pub const USAGE_RETENTION_DAYS: i64 = 90;
+const MAX_USAGE_RECORDS: usize = 20_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageOutcome {
+    Success,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    // Provider identifier, e.g. "clipify"
+    pub provider: String,
+    pub input_chars: usize,
+    pub output_chars: usize,
+    // Wall-clock time of the call, including retries
+    pub latency_ms: u64,
+    // Time the provider reported spending, when it does
+    #[serde(default)]
+    pub processing_time_ms: Option<u64>,
+    pub outcome: UsageOutcome,
+    // Kind of failure, e.g. "rate_limited"
+    #[serde(default)]
+    pub error: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl UsageRecord {
+    fn date(&self) -> NaiveDate {
+        self.at.date_naive()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageSummary {
+    pub calls: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub input_chars: u64,
+    pub output_chars: u64,
+    pub total_latency_ms: u64,
+    pub average_latency_ms: u64,
+}
+
+impl UsageSummary {
+    fn add(&mut self, record: &UsageRecord) {
+        self.calls += 1;
+        match record.outcome {
+            UsageOutcome::Success => self.succeeded += 1,
+            UsageOutcome::Failed => self.failed += 1,
+        }
+        self.input_chars += record.input_chars as u64;
+        self.output_chars += record.output_chars as u64;
+        self.total_latency_ms += record.latency_ms;
+        self.average_latency_ms = self.total_latency_ms / self.calls;
+    }
+
+    fn of<'a>(records: impl Iterator<Item = &'a UsageRecord>) -> Self {
+        let mut summary = UsageSummary::default();
+        for record in records {
+            summary.add(record);
+        }
+        summary
+    }
+}
+
+// Usage of one day, or of the week starting on Monday `start`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodUsage {
+    pub start: NaiveDate,
+    pub summary: UsageSummary,
+}
+
+// Local daily limits. Going over only warns; calls are never blocked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageCap {
+    pub max_calls_per_day: Option<u64>,
+    // Characters sent for rephrasing
+    pub max_chars_per_day: Option<u64>,
+    // Share of a limit at which to start warning
+    pub warn_at_percent: u8,
+}
+
+impl Default for UsageCap {
+    fn default() -> Self {
+        UsageCap {
+            max_calls_per_day: None,
+            max_chars_per_day: None,
+            warn_at_percent: 80,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapLevel {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageCapStatus {
+    pub level: CapLevel,
+    pub calls_today: u64,
+    pub chars_today: u64,
+    pub cap: UsageCap,
+}
+
+impl UsageCap {
+    fn level(&self, used: u64, limit: Option<u64>) -> CapLevel {
+        match limit {
+            Some(limit) if used > limit => CapLevel::Exceeded,
+            Some(limit) if used * 100 >= limit * self.warn_at_percent.min(100) as u64 => {
+                CapLevel::Warning
+            }
+            _ => CapLevel::Ok,
+        }
+    }
+
+    fn status(&self, today: &UsageSummary) -> UsageCapStatus {
+        let level = self
+            .level(today.calls, self.max_calls_per_day)
+            .max(self.level(today.input_chars, self.max_chars_per_day));
+        UsageCapStatus {
+            level,
+            calls_today: today.calls,
+            chars_today: today.input_chars,
+            cap: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageLog {
+    pub cap: UsageCap,
+    pub records: Vec<UsageRecord>,
+}
+
+impl UsageLog {
+    // Add a call. Returns the cap status when the call moved it to a higher level.
+    pub fn record(&mut self, record: UsageRecord) -> Option<UsageCapStatus> {
+        let now = record.at;
+        let before = self.cap_status(now).level;
+        self.records.push(record);
+        self.prune(now);
+
+        let status = self.cap_status(now);
+        (status.level > before).then_some(status)
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::days(USAGE_RETENTION_DAYS);
+        self.records.retain(|record| record.at >= cutoff);
+        if self.records.len() > MAX_USAGE_RECORDS {
+            let excess = self.records.len() - MAX_USAGE_RECORDS;
+            self.records.drain(..excess);
+        }
+    }
+
+    fn between(&self, first: NaiveDate, last: NaiveDate) -> UsageSummary {
+        UsageSummary::of(
+            self.records
+                .iter()
+                .filter(|record| (first..=last).contains(&record.date())),
+        )
+    }
+
+    pub fn cap_status(&self, now: DateTime<Utc>) -> UsageCapStatus {
+        let today = now.date_naive();
+        self.cap.status(&self.between(today, today))
+    }
+
+    // The last `days` days up to today, oldest first, including days without calls
+    pub fn daily(&self, days: u32, now: DateTime<Utc>) -> Vec<PeriodUsage> {
+        let today = now.date_naive();
+        (0..days as i64)
+            .rev()
+            .map(|ago| {
+                let day = today - Duration::days(ago);
+                PeriodUsage {
+                    start: day,
+                    summary: self.between(day, day),
+                }
+            })
+            .collect()
+    }
+
+    // The last `weeks` weeks up to the current one, oldest first. Weeks start on Monday.
+    pub fn weekly(&self, weeks: u32, now: DateTime<Utc>) -> Vec<PeriodUsage> {
+        let today = now.date_naive();
+        let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        (0..weeks as i64)
+            .rev()
+            .map(|ago| {
+                let start = this_week - Duration::weeks(ago);
+                PeriodUsage {
+                    start,
+                    summary: self.between(start, start + Duration::days(6)),
+                }
+            })
+            .collect()
+    }
+
+    // Usage per provider since `since`, or over all kept records
+    pub fn by_provider(&self, since: Option<DateTime<Utc>>) -> BTreeMap<String, UsageSummary> {
+        let mut providers: BTreeMap<String, UsageSummary> = BTreeMap::new();
+        for record in &self.records {
+            if since.is_some_and(|since| record.at < since) {
+                continue;
+            }
+            providers
+                .entry(record.provider.clone())
+                .or_default()
+                .add(record);
+        }
+        providers
+    }
+
+    // Forget all calls; returns how many there were
+    pub fn clear(&mut self) -> usize {
+        let removed = self.records.len();
+        self.records.clear();
+        removed
+    }
+}
+
+pub type RephraseUsageState = Arc<RwLock<UsageLog>>;
+
+// Called when usage reaches a higher soft cap level
+pub type UsageCapAlert = Arc<dyn Fn(&UsageCapStatus) + Send + Sync>;
+
+pub fn load_usage_from_file() -> io::Result<UsageLog> {
+    load_json_file(USAGE_FILE)
+}
+
+pub fn save_usage_to_file(usage: &UsageLog) -> io::Result<()> {
+    save_json_file(USAGE_FILE, usage)
+}
+
+// Records every call that reaches the wrapped provider
+pub struct MeteredProvider {
+    pub inner: Box<dyn RephraseProvider>,
+    pub usage: RephraseUsageState,
+    pub on_cap: Option<UsageCapAlert>,
+}
+
+impl MeteredProvider {
+    async fn record(
+        &self,
+        request: &RephraseRequest,
+        started: Instant,
+        result: Result<(usize, Option<u64>), &RephraseError>,
+    ) {
+        // Calls held back or refused before reaching the provider aren't usage
+        if let Err(RephraseError::Throttled { .. } | RephraseError::Unsupported { .. }) = result {
+            return;
+        }
+        let (outcome, output_chars, processing_time_ms, error) = match result {
+            Ok((output_chars, processing_time_ms)) => (
+                UsageOutcome::Success,
+                output_chars,
+                processing_time_ms,
+                None,
+            ),
+            Err(e) => (UsageOutcome::Failed, 0, None, Some(e.kind().to_string())),
+        };
+        let record = UsageRecord {
+            provider: self.inner.name().to_string(),
+            input_chars: request.text.chars().count(),
+            output_chars,
+            latency_ms: started.elapsed().as_millis() as u64,
+            processing_time_ms,
+            outcome,
+            error,
+            at: Utc::now(),
+        };
+
+        let status = {
+            let mut usage = self.usage.write().await;
+            let status = usage.record(record);
+            if let Err(e) = save_usage_to_file(&usage) {
+                eprintln!("Failed to save rephrase usage: {}", e);
+            }
+            status
+        };
+        if let (Some(status), Some(on_cap)) = (status, &self.on_cap) {
+            on_cap(&status);
+        }
+    }
+
+    async fn record_response(
+        &self,
+        request: &RephraseRequest,
+        started: Instant,
+        result: &Result<RephraseResponse, RephraseError>,
+    ) {
+        let result = result.as_ref().map(|response| {
+            (
+                response.rephrased_text.chars().count(),
+                response.processing_time_ms,
+            )
+        });
+        self.record(request, started, result).await;
+    }
+}
+
+#[async_trait]
+impl RephraseProvider for MeteredProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn rephrase(&self, request: &RephraseRequest) -> Result<RephraseResponse, RephraseError> {
+        let started = Instant::now();
+        let result = self.inner.rephrase(request).await;
+        self.record_response(request, started, &result).await;
+        result
+    }
+
+    async fn rephrase_streaming(
+        &self,
+        request: &RephraseRequest,
+        on_delta: &(dyn for<'s> Fn(&'s str) + Send + Sync),
+    ) -> Result<RephraseResponse, RephraseError> {
+        let started = Instant::now();
+        let result = self.inner.rephrase_streaming(request, on_delta).await;
+        self.record_response(request, started, &result).await;
+        result
+    }
+
+    fn batches_candidates(&self) -> bool {
+        self.inner.batches_candidates()
+    }
+
+    async fn rephrase_variant(
+        &self,
+        request: &RephraseRequest,
+        variant: u32,
+    ) -> Result<RephraseResponse, RephraseError> {
+        let started = Instant::now();
+        let result = self.inner.rephrase_variant(request, variant).await;
+        self.record_response(request, started, &result).await;
+        result
+    }
+
+    // Every request that goes out is recorded: one per variant, or a single call with the
+    // texts of all candidates as its output when the provider batches them
+    async fn rephrase_candidates(
+        &self,
+        request: &RephraseRequest,
+        count: usize,
+    ) -> Result<Vec<RephraseResponse>, RephraseError> {
+        if !self.inner.batches_candidates() {
+            return variant_candidates(self, request, count).await;
+        }
+        let started = Instant::now();
+        let result = self.inner.rephrase_candidates(request, count).await;
+        let summary = result.as_ref().map(|responses| {
+            let chars = responses
+                .iter()
+                .map(|response| response.rephrased_text.chars().count())
+                .sum();
+            (
+                chars,
+                responses.iter().filter_map(|r| r.processing_time_ms).max(),
+            )
+        });
+        self.record(request, started, summary).await;
+        result
+    }
+}
@@ -0,0 +1,1144 @@
+use crate::clipboard::{save_history_to_file, ClipboardEntry, ClipboardHistoryState};
+use crate::clipboard_commands::{copy_transformed_text, write_clipboard_text};
+use crate::config::{RephraseRequest, RephraseResponse};
+use crate::lineage::{EntryLink, LineageOperation};
+use crate::rephrase::cache::save_cache_to_file;
+use crate::rephrase::profiles::save_profiles_to_file;
+use crate::rephrase::queue::{save_queue_to_file, OFFLINE_RETRY_SECS};
+use crate::rephrase::templates::save_templates_to_file;
+use crate::rephrase::usage::save_usage_to_file;
+use crate::rephrase::{
+    ApiKeyUpdate, CacheSummary, CachedProvider, CapLevel, CircuitState, JobStatus, MeteredProvider,
+    PeriodUsage, ProviderConfig, RephraseCacheConfig, RephraseCacheState, RephraseCandidate,
+    RephraseClientState, RephraseError, RephraseJob, RephraseJobMark, RephraseProfile,
+    RephraseProfileStore, RephraseProfilesState, RephraseProvider, RephraseQueueState,
+    RephraseTemplate, RephraseTemplatesState, RephraseUsageState, ServiceStatus, UsageCap,
+    UsageCapStatus, UsageSummary, MAX_CANDIDATES, USAGE_RETENTION_DAYS,
+};
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+use tokio::sync::RwLock;
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+// Where the frontend keeps the signed-in user's tokens
+const TOKEN_STORE: &str = "secure-tokens.dat";
+const ACCESS_TOKEN_KEY: &str = "access_token";
+// Prefix of the keys that hold OpenAI-compatible API keys, followed by the profile name
+const API_KEY_PREFIX: &str = "api_key:";
+
+// Streaming rephrases that are still running, by request id, so they can be cancelled
+pub type RephraseRequestsState = Arc<RwLock<HashMap<String, AbortHandle>>>;
+
+// Payload of "rephrase-chunk": the new piece of text and everything received so far
+#[derive(Debug, Clone, Serialize)]
+pub struct RephraseChunk {
+    pub request_id: String,
+    pub delta: String,
+    pub text: String,
+}
+
+// Payload of "rephrase-finished", "rephrase-failed" and "rephrase-cancelled"
+#[derive(Debug, Clone, Serialize)]
+pub struct RephraseOutcome {
+    pub request_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<RephraseResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn emit_outcome(
+    app: &AppHandle,
+    event: &str,
+    request_id: &str,
+    response: Option<RephraseResponse>,
+    error: Option<String>,
+) {
+    let outcome = RephraseOutcome {
+        request_id: request_id.to_string(),
+        response,
+        error,
+    };
+    if let Err(e) = app.emit(event, &outcome) {
+        eprintln!("Failed to emit {} event: {}", event, e);
+    }
+}
+
+// Run a rephrase in its own task, emitting "rephrase-chunk" as text arrives and one of
+// "rephrase-finished", "rephrase-failed" or "rephrase-cancelled" at the end. The outer
+// error means the request never completed; the inner one is the provider's answer.
+pub async fn stream_with_provider(
+    app: &AppHandle,
+    provider: Box<dyn RephraseProvider>,
+    request: RephraseRequest,
+    request_id: String,
+) -> Result<Result<RephraseResponse, RephraseError>, String> {
+    let requests = app.state::<RephraseRequestsState>().inner().clone();
+    let mut in_flight = requests.write().await;
+    if in_flight.contains_key(&request_id) {
+        return Err(format!(
+            "Rephrase request {} is already running",
+            request_id
+        ));
+    }
+
+    let emitter = app.clone();
+    let id = request_id.clone();
+    let task = tokio::spawn(async move {
+        let received = Mutex::new(String::new());
+        let on_delta = |delta: &str| {
+            let mut received = received.lock().unwrap();
+            received.push_str(delta);
+            let chunk = RephraseChunk {
+                request_id: id.clone(),
+                delta: delta.to_string(),
+                text: received.clone(),
+            };
+            if let Err(e) = emitter.emit("rephrase-chunk", &chunk) {
+                eprintln!("Failed to emit rephrase chunk: {}", e);
+            }
+        };
+        provider.rephrase_streaming(&request, &on_delta).await
+    });
+    in_flight.insert(request_id.clone(), task.abort_handle());
+    drop(in_flight);
+
+    let result = task.await;
+    requests.write().await.remove(&request_id);
+
+    match result {
+        Ok(Ok(response)) => {
+            emit_outcome(
+                app,
+                "rephrase-finished",
+                &request_id,
+                Some(response.clone()),
+                None,
+            );
+            Ok(Ok(response))
+        }
+        Ok(Err(e)) => {
+            emit_outcome(
+                app,
+                "rephrase-failed",
+                &request_id,
+                None,
+                Some(e.to_string()),
+            );
+            Ok(Err(e))
+        }
+        Err(e) if e.is_cancelled() => {
+            println!("[Rephrase] Request {} was cancelled", request_id);
+            emit_outcome(app, "rephrase-cancelled", &request_id, None, None);
+            Err(format!("Rephrase request {} was cancelled", request_id))
+        }
+        Err(e) => {
+            let error = format!("Rephrase task failed: {}", e);
+            emit_outcome(
+                app,
+                "rephrase-failed",
+                &request_id,
+                None,
+                Some(error.clone()),
+            );
+            Err(error)
+        }
+    }
+}
+
+fn token_store(app: &AppHandle) -> Option<Arc<Store<Wry>>> {
+    app.store(TOKEN_STORE)
+        .map_err(|e| eprintln!("Failed to open token store: {}", e))
+        .ok()
+}
+
+// The access token saved by the frontend after sign-in
+pub fn stored_jwt_token(app: &AppHandle) -> Option<String> {
+    let store = token_store(app)?;
+    store
+        .get(ACCESS_TOKEN_KEY)
+        .and_then(|value| value.as_str().map(|token| token.to_string()))
+        .filter(|token| !token.is_empty())
+}
+
+// The API key saved for an OpenAI-compatible profile
+pub fn stored_api_key(app: &AppHandle, profile: &str) -> Option<String> {
+    let store = token_store(app)?;
+    store
+        .get(format!("{}{}", API_KEY_PREFIX, profile))
+        .and_then(|value| value.as_str().map(|key| key.to_string()))
+        .filter(|key| !key.is_empty())
+}
+
+fn update_stored_api_key(app: &AppHandle, profile: &str, update: ApiKeyUpdate) {
+    let Some(store) = token_store(app) else {
+        return;
+    };
+    let key = format!("{}{}", API_KEY_PREFIX, profile);
+    match update {
+        ApiKeyUpdate::Keep => return,
+        ApiKeyUpdate::Set(api_key) => store.set(key, api_key),
+        ApiKeyUpdate::Remove => {
+            if !store.delete(key) {
+                return;
+            }
+        }
+    }
+    if let Err(e) = store.save() {
+        eprintln!("Failed to save token store: {}", e);
+    }
+}
+
+// Move API keys left in the profile file by older versions into the secure store
+pub fn move_api_keys_to_secure_store(app: &AppHandle, profiles: &mut RephraseProfileStore) {
+    let keys = profiles.take_api_keys();
+    if keys.is_empty() {
+        return;
+    }
+    for (profile, api_key) in keys {
+        update_stored_api_key(app, &profile, ApiKeyUpdate::Set(api_key));
+    }
+    if let Err(e) = save_profiles_to_file(profiles) {
+        eprintln!("Failed to save rephrase profiles: {}", e);
+    }
+}
+
+// The JWT or API key a profile's provider signs requests with
+fn profile_credential(app: &AppHandle, profile: &RephraseProfile) -> Option<String> {
+    match &profile.provider {
+        ProviderConfig::Clipify { .. } => stored_jwt_token(app),
+        ProviderConfig::OpenAiCompatible { has_api_key, .. } if *has_api_key => {
+            stored_api_key(app, &profile.name)
+        }
+        _ => None,
+    }
+}
+
+// Tell the frontend and the tray when the Clipify API circuit opens or closes
+pub fn service_status_changed(app: &AppHandle, status: &ServiceStatus) {
+    println!("[Rephrase] Service state changed to {:?}", status.state);
+    if let Err(e) = app.emit("rephrase-service-status", status) {
+        eprintln!("Failed to emit rephrase service status event: {}", e);
+    }
+    let tooltip = match status.state {
+        CircuitState::Closed => "Clipify - Professional Text Cleanup Tool",
+        CircuitState::Open | CircuitState::HalfOpen => "Clipify - Rephrase service degraded",
+    };
+    if let Some(tray) = app.tray_by_id("clipify-tray") {
+        if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+            eprintln!("Failed to update tray tooltip: {}", e);
+        }
+    }
+}
+
+// Warn through an event and a notification when usage nears or passes the soft cap
+fn alert_usage_cap(app: &AppHandle, status: &UsageCapStatus) {
+    if let Err(e) = app.emit("rephrase-usage-cap", status) {
+        eprintln!("Failed to emit rephrase usage cap event: {}", e);
+    }
+    let title = match status.level {
+        CapLevel::Ok => return,
+        CapLevel::Warning => "⚠️ Nearing daily rephrase limit",
+        CapLevel::Exceeded => "🚫 Daily rephrase limit exceeded",
+    };
+    let body = format!(
+        "{} calls and {} characters today",
+        status.calls_today, status.chars_today
+    );
+    notify(app, title, &body);
+}
+
+// Record every call that reaches a provider in the usage log
+fn metered(app: &AppHandle, inner: Box<dyn RephraseProvider>) -> Box<dyn RephraseProvider> {
+    let alert_app = app.clone();
+    Box::new(MeteredProvider {
+        inner,
+        usage: app.state::<RephraseUsageState>().inner().clone(),
+        on_cap: Some(Arc::new(move |status: &UsageCapStatus| {
+            alert_usage_cap(&alert_app, status)
+        })),
+    })
+}
+
+// Put the result cache in front of a metered provider, so cache hits aren't counted
+fn with_cache(
+    app: &AppHandle,
+    provider: ProviderConfig,
+    inner: Box<dyn RephraseProvider>,
+) -> Box<dyn RephraseProvider> {
+    Box::new(CachedProvider {
+        inner: metered(app, inner),
+        provider,
+        cache: app.state::<RephraseCacheState>().inner().clone(),
+    })
+}
+
+// Build the provider for a profile, or for the default profile when none is given
+pub async fn provider_for_profile(
+    app: &AppHandle,
+    profile: Option<&str>,
+    jwt_token: Option<String>,
+) -> Result<Box<dyn RephraseProvider>, String> {
+    let profile = {
+        let profiles_state = app.state::<RephraseProfilesState>();
+        let profiles = profiles_state.read().await;
+        profiles.profile_for(profile)?
+    };
+    let credential = match profile.provider {
+        ProviderConfig::Clipify { .. } => jwt_token.filter(|token| !token.is_empty()),
+        _ => None,
+    }
+    .or_else(|| profile_credential(app, &profile));
+
+    let clipify = app.state::<RephraseClientState>();
+    let provider = profile
+        .provider
+        .build(&profile.settings, &clipify, credential)
+        .map_err(|e| e.to_string())?;
+    Ok(with_cache(app, profile.provider, provider))
+}
+
+// Rephrase text natively with a rephrase profile. For Clipify, the token defaults to the
+// signed-in user's and `api_base_url` overrides the environment's API URL. The stored
+// token is only sent to the configured API URL; any other URL needs `jwt_token`.
+#[tauri::command]
+pub async fn rephrase_text(
+    text: String,
+    jwt_token: Option<String>,
+    api_base_url: Option<String>,
+    profile: Option<String>,
+    app: AppHandle,
+) -> Result<RephraseResponse, String> {
+    if text.trim().is_empty() {
+        return Err("There is no text to rephrase".to_string());
+    }
+
+    let provider = match api_base_url {
+        Some(base_url) => {
+            let clipify = app.state::<RephraseClientState>();
+            let configured =
+                base_url.trim_end_matches('/') == clipify.base_url().trim_end_matches('/');
+            let jwt_token = jwt_token
+                .filter(|token| !token.is_empty())
+                .or_else(|| configured.then(|| stored_jwt_token(&app)).flatten());
+            let config = ProviderConfig::Clipify {
+                base_url: Some(base_url),
+            };
+            let provider = config
+                .build(clipify.config(), &clipify, jwt_token)
+                .map_err(|e| e.to_string())?;
+            with_cache(&app, config, provider)
+        }
+        None => provider_for_profile(&app, profile.as_deref(), jwt_token).await?,
+    };
+
+    provider
+        .rephrase(&RephraseRequest::new(&text))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// The request for `text` and the profile to send it with. A template supplies the
+// options, with `variables` filling its placeholders, and its profile unless one is given.
+pub async fn request_for_text(
+    app: &AppHandle,
+    text: &str,
+    template: Option<&str>,
+    variables: &HashMap<String, String>,
+    profile: Option<String>,
+) -> Result<(RephraseRequest, Option<String>), String> {
+    let Some(name) = template else {
+        return Ok((RephraseRequest::new(text), profile));
+    };
+    let template = {
+        let templates_state = app.state::<RephraseTemplatesState>();
+        let templates = templates_state.read().await;
+        templates
+            .get(name)
+            .ok_or_else(|| format!("Rephrase template '{}' not found", name))?
+    };
+    let request = template.render(text, variables)?;
+    Ok((request, profile.or(template.profile)))
+}
+
+// How an entry was rephrased from `parent_id`, for its lineage
+fn rephrase_link(
+    parent_id: &str,
+    request: &RephraseRequest,
+    provider: &str,
+    profile: Option<&str>,
+) -> EntryLink {
+    let mut link = EntryLink::new(parent_id, LineageOperation::Rephrase)
+        .with_param("provider", provider)
+        .with_param("style", request.style.as_str())
+        .with_param("context", request.context.as_str())
+        .with_param("target_audience", request.target_audience.as_str())
+        .with_param("preserve_length", request.preserve_length.to_string());
+    if let Some(profile) = profile {
+        link = link.with_param("profile", profile);
+    }
+    link
+}
+
+// Rephrase text with a profile or template, streaming progress to the frontend, then copy
+// the result and add it to history. The request can be cancelled by the id in its events.
+pub async fn rephrase_to_clipboard(
+    app: &AppHandle,
+    text: String,
+    profile: Option<&str>,
+    template: Option<&str>,
+    variables: &HashMap<String, String>,
+) -> Result<ClipboardEntry, String> {
+    let (request, profile) = request_for_text(
+        app,
+        &text,
+        template,
+        variables,
+        profile.map(|profile| profile.to_string()),
+    )
+    .await?;
+    let provider = provider_for_profile(app, profile.as_deref(), None).await?;
+    let provider_name = provider.name();
+    let request_id = Uuid::new_v4().to_string();
+    let entry_id = entry_id_for_text(app, &text).await;
+    let response = match stream_with_provider(app, provider, request.clone(), request_id).await? {
+        Ok(response) => response,
+        // Keep the attempt so it isn't lost while the service is unreachable
+        Err(e) if e.is_retryable() => {
+            enqueue_rephrase(app, entry_id, request, profile, Some(&e)).await;
+            return Err(format!("{}. Queued to retry automatically", e));
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    if let Some(ms) = response.processing_time_ms {
+        println!(
+            "[Rephrase] {} rephrased {} characters in {} ms",
+            provider_name,
+            text.len(),
+            ms
+        );
+    }
+    let link = entry_id.map(|parent_id| {
+        let mut link = rephrase_link(&parent_id, &request, provider_name, profile.as_deref());
+        if let Some(template) = template {
+            link = link.with_param("template", template);
+        }
+        link
+    });
+    copy_transformed_text(app, text, response.rephrased_text, "rephrase", link).await
+}
+
+// Rephrase text with a profile or template, emitting events as the result streams in.
+// Pass a `request_id` to be able to cancel the request with `cancel_rephrase`.
+#[tauri::command]
+pub async fn stream_rephrase(
+    text: String,
+    profile: Option<String>,
+    request_id: Option<String>,
+    template: Option<String>,
+    variables: Option<HashMap<String, String>>,
+    app: AppHandle,
+) -> Result<RephraseResponse, String> {
+    if text.trim().is_empty() {
+        return Err("There is no text to rephrase".to_string());
+    }
+
+    let (request, profile) = request_for_text(
+        &app,
+        &text,
+        template.as_deref(),
+        &variables.unwrap_or_default(),
+        profile,
+    )
+    .await?;
+    let provider = provider_for_profile(&app, profile.as_deref(), None).await?;
+    let request_id = request_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    stream_with_provider(&app, provider, request, request_id)
+        .await?
+        .map_err(|e| e.to_string())
+}
+
+// Stop a streaming rephrase; returns false if no request with that id is running
+#[tauri::command]
+pub async fn cancel_rephrase(
+    request_id: String,
+    requests_state: tauri::State<'_, RephraseRequestsState>,
+) -> Result<bool, String> {
+    let requests = requests_state.read().await;
+    match requests.get(&request_id) {
+        Some(handle) => {
+            handle.abort();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+async fn entry_text(app: &AppHandle, entry_id: &str) -> Result<String, String> {
+    let history_state = app.state::<ClipboardHistoryState>();
+    let history = history_state.read().await;
+    history
+        .entries
+        .iter()
+        .find(|entry| entry.id == entry_id)
+        .map(|entry| entry.content.clone())
+        .ok_or_else(|| "Entry not found".to_string())
+}
+
+// The history entry holding exactly `text`; history never holds duplicates
+async fn entry_id_for_text(app: &AppHandle, text: &str) -> Option<String> {
+    let history_state = app.state::<ClipboardHistoryState>();
+    let history = history_state.read().await;
+    history
+        .entries
+        .iter()
+        .find(|entry| entry.content == text)
+        .map(|entry| entry.id.clone())
+}
+
+// Record a queued rephrase's status on the entry it was made from
+async fn mark_entry(app: &AppHandle, job: &RephraseJob, mark: RephraseJobMark) {
+    let Some(entry_id) = &job.entry_id else {
+        return;
+    };
+    let history_state = app.state::<ClipboardHistoryState>();
+    let mut history = history_state.write().await;
+    if let Some(entry) = history.entries.iter_mut().find(|e| &e.id == entry_id) {
+        entry.rephrase_job = Some(mark);
+        if let Err(e) = save_history_to_file(&history) {
+            eprintln!("Failed to save clipboard history: {}", e);
+        }
+    }
+}
+
+async fn job_updated(app: &AppHandle, job: &RephraseJob, result_entry_id: Option<String>) {
+    mark_entry(app, job, job.mark(job.status, result_entry_id)).await;
+    if let Err(e) = app.emit("rephrase-job-updated", job) {
+        eprintln!("Failed to emit rephrase job update: {}", e);
+    }
+}
+
+async fn save_queue(app: &AppHandle) {
+    let queue_state = app.state::<RephraseQueueState>();
+    let queue = queue_state.read().await;
+    if let Err(e) = save_queue_to_file(&queue) {
+        eprintln!("Failed to save rephrase queue: {}", e);
+    }
+}
+
+// Queue a rephrase. `error` is the failure of an attempt already made, which schedules
+// the first retry as if the job had run.
+pub async fn enqueue_rephrase(
+    app: &AppHandle,
+    entry_id: Option<String>,
+    request: RephraseRequest,
+    profile: Option<String>,
+    error: Option<&RephraseError>,
+) -> RephraseJob {
+    let job = {
+        let queue_state = app.state::<RephraseQueueState>();
+        let mut queue = queue_state.write().await;
+        let job = queue.enqueue(entry_id, request, profile, Utc::now());
+        match error {
+            Some(error) => queue.fail(&job.id, error, Utc::now()).unwrap_or(job),
+            None => job,
+        }
+    };
+    save_queue(app).await;
+    println!("[RephraseQueue] Queued job {}", job.id);
+    job_updated(app, &job, None).await;
+    job
+}
+
+// Run every due job until the queue is empty or the service turns out to be unreachable
+pub async fn process_rephrase_queue(app: &AppHandle) {
+    let queue_state = app.state::<RephraseQueueState>().inner().clone();
+    loop {
+        let Some(job) = queue_state.write().await.take_due(Utc::now()) else {
+            break;
+        };
+        save_queue(app).await;
+
+        let provider = match provider_for_profile(app, job.profile.as_deref(), None).await {
+            Ok(provider) => provider,
+            Err(e) => {
+                let failed = queue_state.write().await.give_up(&job.id, e);
+                save_queue(app).await;
+                if let Some(failed) = failed {
+                    job_updated(app, &failed, None).await;
+                }
+                continue;
+            }
+        };
+
+        match provider.rephrase(&job.request).await {
+            Ok(response) => {
+                let completed = queue_state.write().await.complete(&job.id);
+                save_queue(app).await;
+                // Cancelled while running; drop the result
+                let Some(completed) = completed else {
+                    continue;
+                };
+                let entry =
+                    add_rephrased_entry(app, &completed, provider.name(), response.rephrased_text)
+                        .await;
+                println!("[RephraseQueue] Job {} completed", completed.id);
+                job_updated(app, &completed, Some(entry.id)).await;
+                notify(app, "✨ Queued rephrase finished", &entry.preview);
+            }
+            Err(e) => {
+                // Unreachable or held back, so the jobs after it would fail the same way
+                let offline = matches!(
+                    e,
+                    RephraseError::Network { .. }
+                        | RephraseError::Timeout
+                        | RephraseError::Throttled { .. }
+                );
+                let failed = queue_state.write().await.fail(&job.id, &e, Utc::now());
+                save_queue(app).await;
+                if let Some(failed) = failed {
+                    job_updated(app, &failed, None).await;
+                    if failed.status == JobStatus::Failed {
+                        notify(app, "❌ Queued rephrase failed", &e.to_string());
+                    }
+                }
+                if offline {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Add the result to history without touching the clipboard, which may hold
+// something else by the time a queued job finishes
+async fn add_rephrased_entry(
+    app: &AppHandle,
+    job: &RephraseJob,
+    provider: &str,
+    result: String,
+) -> ClipboardEntry {
+    let mut entry = ClipboardEntry::new(result, true, Some(job.request.text.clone()));
+    entry.parent = job
+        .entry_id
+        .as_deref()
+        .map(|parent_id| rephrase_link(parent_id, &job.request, provider, job.profile.as_deref()));
+    let history_state = app.state::<ClipboardHistoryState>();
+    {
+        let mut history = history_state.write().await;
+        history.add_entry(entry.clone());
+        if let Err(e) = save_history_to_file(&history) {
+            eprintln!("Failed to save clipboard history: {}", e);
+        }
+    }
+    if let Err(e) = app.emit("clipboard-updated", &entry.content) {
+        eprintln!("Failed to emit clipboard update event: {}", e);
+    }
+    entry
+}
+
+pub(crate) fn notify(app: &AppHandle, title: &str, body: &str) {
+    if let Err(e) = tauri_plugin_notification::NotificationExt::notification(app)
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+    {
+        eprintln!("Failed to show notification '{}': {}", title, e);
+    }
+}
+
+// Check the queue periodically for the lifetime of the app
+pub fn start_rephrase_queue(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(OFFLINE_RETRY_SECS as u64));
+        loop {
+            interval.tick().await;
+            process_rephrase_queue(&app).await;
+        }
+    });
+}
+
+// Rephrase Candidate Commands
+
+// Make several rephrasings of a history entry and keep them on it to choose from
+#[tauri::command]
+pub async fn generate_rephrase_candidates(
+    entry_id: String,
+    count: Option<usize>,
+    profile: Option<String>,
+    template: Option<String>,
+    variables: Option<HashMap<String, String>>,
+    app: AppHandle,
+) -> Result<Vec<RephraseCandidate>, String> {
+    let count = count.unwrap_or(3).clamp(1, MAX_CANDIDATES);
+    let text = entry_text(&app, &entry_id).await?;
+    let (request, profile) = request_for_text(
+        &app,
+        &text,
+        template.as_deref(),
+        &variables.unwrap_or_default(),
+        profile,
+    )
+    .await?;
+    let provider = provider_for_profile(&app, profile.as_deref(), None).await?;
+    let responses = provider
+        .rephrase_candidates(&request, count)
+        .await
+        .map_err(|e| e.to_string())?;
+    let candidates: Vec<RephraseCandidate> = responses
+        .into_iter()
+        .map(|response| {
+            RephraseCandidate::new(response, &request, provider.name(), profile.clone())
+        })
+        .collect();
+
+    let history_state = app.state::<ClipboardHistoryState>();
+    let mut history = history_state.write().await;
+    let entry = history
+        .entries
+        .iter_mut()
+        .find(|entry| entry.id == entry_id)
+        .ok_or_else(|| "Entry not found".to_string())?;
+    entry.rephrase_candidates = candidates.clone();
+    if let Err(e) = save_history_to_file(&history) {
+        eprintln!("Failed to save clipboard history: {}", e);
+    }
+
+    Ok(candidates)
+}
+
+// Copy a candidate and record it as a new entry linked to the source. The other
+// candidates move to the new entry, so a different one can still be chosen from there.
+#[tauri::command]
+pub async fn choose_rephrase_candidate(
+    entry_id: String,
+    candidate_id: String,
+    app: AppHandle,
+) -> Result<ClipboardEntry, String> {
+    let history_state = app.state::<ClipboardHistoryState>();
+    let (candidate, source_id, source_content) = {
+        let history = history_state.read().await;
+        let holder = history
+            .entries
+            .iter()
+            .find(|entry| entry.id == entry_id)
+            .ok_or_else(|| "Entry not found".to_string())?;
+        let candidate = holder
+            .rephrase_candidates
+            .iter()
+            .find(|candidate| candidate.id == candidate_id)
+            .cloned()
+            .ok_or_else(|| "Candidate not found".to_string())?;
+        // Choosing again from an earlier choice still links to the original text
+        let source_id = match &holder.parent {
+            Some(link) if link.operation == LineageOperation::RephraseCandidate => {
+                link.parent_id.clone()
+            }
+            _ => holder.id.clone(),
+        };
+        let source_content = history
+            .entries
+            .iter()
+            .find(|entry| entry.id == source_id)
+            .map(|entry| entry.content.clone())
+            .unwrap_or_else(|| holder.original_content.clone());
+        (candidate, source_id, source_content)
+    };
+
+    write_clipboard_text(&app, &candidate.text, "rephrase_candidate").await?;
+
+    let mut history = history_state.write().await;
+    let mut remaining = Vec::new();
+    if let Some(holder) = history.entries.iter_mut().find(|e| e.id == entry_id) {
+        remaining = std::mem::take(&mut holder.rephrase_candidates);
+        remaining.retain(|c| c.id != candidate_id);
+    }
+    let mut entry = ClipboardEntry::new(candidate.text, true, Some(source_content));
+    let mut link = EntryLink::new(&source_id, LineageOperation::RephraseCandidate)
+        .with_param("candidate_id", candidate.id.as_str())
+        .with_param("provider", candidate.provider.as_str())
+        .with_param("style", candidate.style.as_str())
+        .with_param("context", candidate.context.as_str())
+        .with_param("target_audience", candidate.target_audience.as_str());
+    if let Some(profile) = &candidate.profile {
+        link = link.with_param("profile", profile.as_str());
+    }
+    entry.parent = Some(link);
+    entry.rephrase_candidates = remaining;
+    history.add_entry(entry.clone());
+    if let Err(e) = save_history_to_file(&history) {
+        eprintln!("Failed to save clipboard history: {}", e);
+    }
+    drop(history);
+
+    if let Err(e) = app.emit("clipboard-updated", &entry.content) {
+        eprintln!("Failed to emit clipboard update event: {}", e);
+    }
+    Ok(entry)
+}
+
+// Rephrase Queue Commands
+#[tauri::command]
+pub async fn get_rephrase_jobs(
+    queue_state: tauri::State<'_, RephraseQueueState>,
+) -> Result<Vec<RephraseJob>, String> {
+    let queue = queue_state.read().await;
+    Ok(queue.jobs.clone())
+}
+
+// Queue a history entry for rephrasing; it runs right away if the service is reachable
+#[tauri::command]
+pub async fn queue_rephrase(
+    entry_id: String,
+    profile: Option<String>,
+    template: Option<String>,
+    variables: Option<HashMap<String, String>>,
+    app: AppHandle,
+) -> Result<RephraseJob, String> {
+    let text = entry_text(&app, &entry_id).await?;
+    let (request, profile) = request_for_text(
+        &app,
+        &text,
+        template.as_deref(),
+        &variables.unwrap_or_default(),
+        profile,
+    )
+    .await?;
+    let job = enqueue_rephrase(&app, Some(entry_id), request, profile, None).await;
+
+    tauri::async_runtime::spawn(async move {
+        process_rephrase_queue(&app).await;
+    });
+    Ok(job)
+}
+
+#[tauri::command]
+pub async fn retry_rephrase_job(job_id: String, app: AppHandle) -> Result<RephraseJob, String> {
+    let job = {
+        let queue_state = app.state::<RephraseQueueState>();
+        let mut queue = queue_state.write().await;
+        queue.retry(&job_id, Utc::now())?
+    };
+    save_queue(&app).await;
+    job_updated(&app, &job, None).await;
+
+    tauri::async_runtime::spawn(async move {
+        process_rephrase_queue(&app).await;
+    });
+    Ok(job)
+}
+
+#[tauri::command]
+pub async fn cancel_rephrase_job(job_id: String, app: AppHandle) -> Result<bool, String> {
+    let cancelled = {
+        let queue_state = app.state::<RephraseQueueState>();
+        let mut queue = queue_state.write().await;
+        queue.cancel(&job_id)
+    };
+    match cancelled {
+        Some(job) => {
+            save_queue(&app).await;
+            job_updated(&app, &job, None).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// Rephrase Cache Commands
+#[tauri::command]
+pub async fn get_rephrase_cache_stats(
+    cache_state: tauri::State<'_, RephraseCacheState>,
+) -> Result<CacheSummary, String> {
+    let mut cache = cache_state.write().await;
+    cache.remove_expired(Utc::now());
+    Ok(cache.summary())
+}
+
+#[tauri::command]
+pub async fn purge_rephrase_cache(
+    cache_state: tauri::State<'_, RephraseCacheState>,
+) -> Result<usize, String> {
+    let mut cache = cache_state.write().await;
+    let removed = cache.purge();
+
+    // Save to file
+    if let Err(e) = save_cache_to_file(&cache) {
+        eprintln!("Failed to save rephrase cache: {}", e);
+    }
+
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn set_rephrase_cache_config(
+    config: RephraseCacheConfig,
+    cache_state: tauri::State<'_, RephraseCacheState>,
+) -> Result<CacheSummary, String> {
+    let mut cache = cache_state.write().await;
+    cache.config = config;
+    cache.enforce_limits(Utc::now());
+
+    // Save to file
+    if let Err(e) = save_cache_to_file(&cache) {
+        eprintln!("Failed to save rephrase cache: {}", e);
+    }
+
+    Ok(cache.summary())
+}
+
+// Whether requests to the Clipify API are going out or held back after failures
+#[tauri::command]
+pub async fn get_rephrase_service_status(
+    client_state: tauri::State<'_, RephraseClientState>,
+) -> Result<ServiceStatus, String> {
+    client_state
+        .guard()
+        .map(|guard| guard.status())
+        .ok_or_else(|| "The rephrase client has no rate limit configured".to_string())
+}
+
+// Rephrase Usage Commands
+#[tauri::command]
+pub async fn get_rephrase_usage_daily(
+    days: Option<u32>,
+    usage_state: tauri::State<'_, RephraseUsageState>,
+) -> Result<Vec<PeriodUsage>, String> {
+    let days = days.unwrap_or(7).clamp(1, USAGE_RETENTION_DAYS as u32);
+    let usage = usage_state.read().await;
+    Ok(usage.daily(days, Utc::now()))
+}
+
+#[tauri::command]
+pub async fn get_rephrase_usage_weekly(
+    weeks: Option<u32>,
+    usage_state: tauri::State<'_, RephraseUsageState>,
+) -> Result<Vec<PeriodUsage>, String> {
+    let weeks = weeks
+        .unwrap_or(4)
+        .clamp(1, (USAGE_RETENTION_DAYS / 7) as u32);
+    let usage = usage_state.read().await;
+    Ok(usage.weekly(weeks, Utc::now()))
+}
+
+// Usage per provider over the last `days` days, or over all kept records
+#[tauri::command]
+pub async fn get_rephrase_usage_by_provider(
+    days: Option<u32>,
+    usage_state: tauri::State<'_, RephraseUsageState>,
+) -> Result<BTreeMap<String, UsageSummary>, String> {
+    let since = days.map(|days| Utc::now() - chrono::Duration::days(days as i64));
+    let usage = usage_state.read().await;
+    Ok(usage.by_provider(since))
+}
+
+#[tauri::command]
+pub async fn get_rephrase_usage_cap(
+    usage_state: tauri::State<'_, RephraseUsageState>,
+) -> Result<UsageCapStatus, String> {
+    let usage = usage_state.read().await;
+    Ok(usage.cap_status(Utc::now()))
+}
+
+#[tauri::command]
+pub async fn set_rephrase_usage_cap(
+    cap: UsageCap,
+    usage_state: tauri::State<'_, RephraseUsageState>,
+) -> Result<UsageCapStatus, String> {
+    if cap.warn_at_percent > 100 {
+        return Err("Warning threshold must be at most 100%".to_string());
+    }
+    let mut usage = usage_state.write().await;
+    usage.cap = cap;
+
+    // Save to file
+    if let Err(e) = save_usage_to_file(&usage) {
+        eprintln!("Failed to save rephrase usage: {}", e);
+    }
+
+    Ok(usage.cap_status(Utc::now()))
+}
+
+#[tauri::command]
+pub async fn clear_rephrase_usage(
+    usage_state: tauri::State<'_, RephraseUsageState>,
+) -> Result<usize, String> {
+    let mut usage = usage_state.write().await;
+    let removed = usage.clear();
+
+    // Save to file
+    if let Err(e) = save_usage_to_file(&usage) {
+        eprintln!("Failed to save rephrase usage: {}", e);
+    }
+
+    Ok(removed)
+}
+
+// Rephrase Template Commands
+#[tauri::command]
+pub async fn get_rephrase_templates(
+    templates_state: tauri::State<'_, RephraseTemplatesState>,
+) -> Result<Vec<RephraseTemplate>, String> {
+    let templates = templates_state.read().await;
+    Ok(templates.all())
+}
+
+#[tauri::command]
+pub async fn save_rephrase_template(
+    template: RephraseTemplate,
+    templates_state: tauri::State<'_, RephraseTemplatesState>,
+) -> Result<(), String> {
+    let mut templates = templates_state.write().await;
+    templates.upsert(template)?;
+
+    // Save to file
+    if let Err(e) = save_templates_to_file(&templates) {
+        eprintln!("Failed to save rephrase templates: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_rephrase_template(
+    name: String,
+    templates_state: tauri::State<'_, RephraseTemplatesState>,
+) -> Result<bool, String> {
+    let mut templates = templates_state.write().await;
+    let removed = templates.remove(&name)?;
+
+    // Save to file
+    if let Err(e) = save_templates_to_file(&templates) {
+        eprintln!("Failed to save rephrase templates: {}", e);
+    }
+
+    Ok(removed)
+}
+
+// The request a template would send for `text`, to preview it before rephrasing
+#[tauri::command]
+pub async fn render_rephrase_template(
+    name: String,
+    text: String,
+    variables: Option<HashMap<String, String>>,
+    app: AppHandle,
+) -> Result<RephraseRequest, String> {
+    let (request, _) = request_for_text(
+        &app,
+        &text,
+        Some(&name),
+        &variables.unwrap_or_default(),
+        None,
+    )
+    .await?;
+    Ok(request)
+}
+
+// Rephrase Profile Commands
+#[tauri::command]
+pub async fn get_rephrase_profiles(
+    profiles_state: tauri::State<'_, RephraseProfilesState>,
+) -> Result<Vec<RephraseProfile>, String> {
+    let profiles = profiles_state.read().await;
+    Ok(profiles.all())
+}
+
+#[tauri::command]
+pub async fn save_rephrase_profile(
+    profile: RephraseProfile,
+    app: AppHandle,
+    profiles_state: tauri::State<'_, RephraseProfilesState>,
+) -> Result<(), String> {
+    let name = profile.name.clone();
+    let mut profiles = profiles_state.write().await;
+    let api_key = profiles.upsert(profile)?;
+    update_stored_api_key(&app, &name, api_key);
+
+    // Save to file
+    if let Err(e) = save_profiles_to_file(&profiles) {
+        eprintln!("Failed to save rephrase profiles: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_rephrase_profile(
+    name: String,
+    app: AppHandle,
+    profiles_state: tauri::State<'_, RephraseProfilesState>,
+) -> Result<bool, String> {
+    let mut profiles = profiles_state.write().await;
+    let removed = profiles.remove(&name)?;
+    if removed {
+        update_stored_api_key(&app, &name, ApiKeyUpdate::Remove);
+    }
+
+    // Save to file
+    if let Err(e) = save_profiles_to_file(&profiles) {
+        eprintln!("Failed to save rephrase profiles: {}", e);
+    }
+
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn get_default_rephrase_profile(
+    profiles_state: tauri::State<'_, RephraseProfilesState>,
+) -> Result<String, String> {
+    let profiles = profiles_state.read().await;
+    Ok(profiles.default_profile.clone())
+}
+
+#[tauri::command]
+pub async fn set_default_rephrase_profile(
+    name: String,
+    profiles_state: tauri::State<'_, RephraseProfilesState>,
+) -> Result<(), String> {
+    let mut profiles = profiles_state.write().await;
+    profiles.set_default(&name)?;
+
+    // Save to file
+    if let Err(e) = save_profiles_to_file(&profiles) {
+        eprintln!("Failed to save rephrase profiles: {}", e);
+    }
+
+    Ok(())
+}
+
+// Send a short sample through a profile without saving it, to check its settings. An
+// OpenAI-compatible profile uses the key entered with it, or the one saved for its name.
+#[tauri::command]
+pub async fn test_rephrase_profile(
+    mut profile: RephraseProfile,
+    app: AppHandle,
+) -> Result<RephraseResponse, String> {
+    let credential = match profile.provider.take_api_key() {
+        ApiKeyUpdate::Set(api_key) => Some(api_key),
+        ApiKeyUpdate::Keep => stored_api_key(&app, &profile.name),
+        ApiKeyUpdate::Remove => match profile.provider {
+            ProviderConfig::Clipify { .. } => stored_jwt_token(&app),
+            _ => None,
+        },
+    };
+    let clipify = app.state::<RephraseClientState>();
+    let provider = profile
+        .provider
+        .build(&profile.settings, &clipify, credential)
+        .map_err(|e| e.to_string())?;
+    metered(&app, provider)
+        .rephrase(&RephraseRequest::new(
+            "hey, can u send me the report by tmrw?",
+        ))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,40 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Resolve a file inside the app data directory, creating the directory if needed
+pub fn get_data_file_path(file_name: &str) -> io::Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find data directory"))?;
+
+    // Use the proper Tauri app data directory
+    let clipify_dir = data_dir.join("com.suhailmalik.clipify");
+
+    // Create directory if it doesn't exist
+    if !clipify_dir.exists() {
+        fs::create_dir_all(&clipify_dir)?;
+    }
+
+    Ok(clipify_dir.join(file_name))
+}
+
+// Load a JSON document from the app data directory, falling back to the default when missing
+pub fn load_json_file<T: DeserializeOwned + Default>(file_name: &str) -> io::Result<T> {
+    let file_path = get_data_file_path(file_name)?;
+
+    if !file_path.exists() {
+        return Ok(T::default());
+    }
+
+    let json_data = fs::read_to_string(file_path)?;
+    serde_json::from_str(&json_data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn save_json_file<T: Serialize>(file_name: &str, value: &T) -> io::Result<()> {
+    let file_path = get_data_file_path(file_name)?;
+    let json_data = serde_json::to_string_pretty(value)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(file_path, json_data)
+}
@@ -0,0 +1,402 @@
+use crate::clipboard::{detect_content_type, ClipboardEntry};
+use crate::clipboard_commands::{run_pipeline, transform_clipboard_text, transform_clipboard_with};
+use crate::transforms::data::{self, DataAction, DataError, DataFormat, FormatData};
+use crate::transforms::encoding::{
+    CaseStyle, Codec, ConvertCase, DecodeJwt, DecodeText, EncodeText,
+};
+use crate::transforms::links::{save_url_rules_to_file, UrlRulesState, UrlRulesStore};
+use crate::transforms::presets::{save_presets_to_file, CleanupPreset, CleanupPresetsState};
+use crate::transforms::replace::{
+    save_replace_rules_to_file, ReplaceDryRun, ReplaceRule, ReplaceRulesState,
+};
+use crate::transforms::scripts::{self, RunScript, ScriptEntry, ScriptInfo};
+use crate::transforms::tables::{self, ConvertTable, TableInfo};
+use crate::transforms::unicode::{UnicodeReport, UnicodeScrub};
+use crate::transforms::{Transform, TransformStep};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct UnicodeScrubResult {
+    pub text: String,
+    pub report: UnicodeReport,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataValidation {
+    pub format: DataFormat,
+    pub valid: bool,
+    pub error: Option<DataError>,
+}
+
+// Cleanup Preset Commands
+#[tauri::command]
+pub async fn get_cleanup_presets(
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+) -> Result<Vec<CleanupPreset>, String> {
+    let presets = presets_state.read().await;
+    Ok(presets.all())
+}
+
+#[tauri::command]
+pub async fn save_cleanup_preset(
+    preset: CleanupPreset,
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+) -> Result<(), String> {
+    let mut presets = presets_state.write().await;
+    presets.upsert(preset)?;
+
+    // Save to file
+    if let Err(e) = save_presets_to_file(&presets) {
+        eprintln!("Failed to save cleanup presets: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_cleanup_preset(
+    name: String,
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+) -> Result<bool, String> {
+    let mut presets = presets_state.write().await;
+    let removed = presets.remove(&name)?;
+
+    // Save to file
+    if let Err(e) = save_presets_to_file(&presets) {
+        eprintln!("Failed to save cleanup presets: {}", e);
+    }
+
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn get_default_cleanup_preset(
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+) -> Result<String, String> {
+    let presets = presets_state.read().await;
+    Ok(presets.default_preset.clone())
+}
+
+#[tauri::command]
+pub async fn set_default_cleanup_preset(
+    name: String,
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+) -> Result<(), String> {
+    let mut presets = presets_state.write().await;
+    presets.set_default(&name)?;
+
+    // Save to file
+    if let Err(e) = save_presets_to_file(&presets) {
+        eprintln!("Failed to save cleanup presets: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_code_auto_detect(
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+) -> Result<bool, String> {
+    let presets = presets_state.read().await;
+    Ok(presets.auto_detect_code)
+}
+
+// Whether cleanup without a preset switches to the code preset for text that looks like code
+#[tauri::command]
+pub async fn set_code_auto_detect(
+    enabled: bool,
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+) -> Result<(), String> {
+    let mut presets = presets_state.write().await;
+    presets.auto_detect_code = enabled;
+
+    // Save to file
+    if let Err(e) = save_presets_to_file(&presets) {
+        eprintln!("Failed to save cleanup presets: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_transform_catalog() -> Vec<TransformStep> {
+    TransformStep::catalog()
+}
+
+#[tauri::command]
+pub async fn cleanup_text_with_preset(
+    text: String,
+    preset: Option<String>,
+    presets_state: tauri::State<'_, CleanupPresetsState>,
+    url_rules_state: tauri::State<'_, UrlRulesState>,
+    replace_rules_state: tauri::State<'_, ReplaceRulesState>,
+) -> Result<String, String> {
+    let pipeline = {
+        let presets = presets_state.read().await;
+        presets.pipeline_for_text(preset.as_deref(), &text)?
+    };
+    let pipeline = pipeline.with_url_rules(&url_rules_state.read().await.rules);
+    let cleaned = run_pipeline(pipeline, text.clone()).await?;
+    let replace_rules = replace_rules_state.read().await;
+    Ok(replace_rules.apply(&cleaned, &detect_content_type(&text)))
+}
+
+#[tauri::command]
+pub async fn scrub_unicode(
+    text: String,
+    options: Option<UnicodeScrub>,
+) -> Result<UnicodeScrubResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (text, report) = options.unwrap_or_default().scrub(&text);
+        UnicodeScrubResult { text, report }
+    })
+    .await
+    .map_err(|e| format!("Unicode scrub failed: {}", e))
+}
+
+// URL Rule Commands
+#[tauri::command]
+pub async fn get_url_rules(
+    url_rules_state: tauri::State<'_, UrlRulesState>,
+) -> Result<UrlRulesStore, String> {
+    let url_rules = url_rules_state.read().await;
+    Ok(url_rules.clone())
+}
+
+#[tauri::command]
+pub async fn save_url_rules(
+    rules: UrlRulesStore,
+    url_rules_state: tauri::State<'_, UrlRulesState>,
+) -> Result<(), String> {
+    let mut url_rules = url_rules_state.write().await;
+    *url_rules = rules;
+
+    // Save to file
+    if let Err(e) = save_url_rules_to_file(&url_rules) {
+        eprintln!("Failed to save URL rules: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn clean_url(
+    url: String,
+    url_rules_state: tauri::State<'_, UrlRulesState>,
+) -> Result<String, String> {
+    let url_rules = url_rules_state.read().await;
+    url_rules
+        .rules
+        .clean_url(url.trim())
+        .ok_or_else(|| format!("'{}' is not a valid http(s) URL", url.trim()))
+}
+
+// Replace Rule Commands
+#[tauri::command]
+pub async fn get_replace_rules(
+    replace_rules_state: tauri::State<'_, ReplaceRulesState>,
+) -> Result<Vec<ReplaceRule>, String> {
+    let replace_rules = replace_rules_state.read().await;
+    Ok(replace_rules.rules.clone())
+}
+
+#[tauri::command]
+pub async fn save_replace_rules(
+    rules: Vec<ReplaceRule>,
+    replace_rules_state: tauri::State<'_, ReplaceRulesState>,
+) -> Result<Vec<ReplaceRule>, String> {
+    let mut replace_rules = replace_rules_state.write().await;
+    replace_rules.set_rules(rules)?;
+
+    // Save to file
+    if let Err(e) = save_replace_rules_to_file(&replace_rules) {
+        eprintln!("Failed to save replace rules: {}", e);
+    }
+
+    Ok(replace_rules.rules.clone())
+}
+
+// Show what the rules would change in `text` without applying them. The content
+// type is detected from the text unless given.
+#[tauri::command]
+pub async fn dry_run_replace_rules(
+    text: String,
+    content_type: Option<String>,
+    rules: Option<Vec<ReplaceRule>>,
+    replace_rules_state: tauri::State<'_, ReplaceRulesState>,
+) -> Result<ReplaceDryRun, String> {
+    let content_type = content_type.unwrap_or_else(|| detect_content_type(&text));
+    let mut replace_rules = replace_rules_state.read().await.clone();
+    // Unsaved rules from the editor can be previewed before saving
+    if let Some(rules) = rules {
+        replace_rules.rules = rules;
+    }
+    Ok(replace_rules.dry_run(&text, &content_type))
+}
+
+// Structured Data Commands
+// Parsing runs on a blocking thread, as pasted documents can be large
+#[tauri::command]
+pub async fn format_data(
+    text: String,
+    format: Option<DataFormat>,
+    action: DataAction,
+    indent: Option<usize>,
+) -> Result<String, String> {
+    let defaults = FormatData::default();
+    let step = FormatData {
+        format: format.unwrap_or(defaults.format),
+        action,
+        indent: indent.unwrap_or(defaults.indent),
+    };
+    tauri::async_runtime::spawn_blocking(move || step.apply(&text))
+        .await
+        .map_err(|e| format!("Formatting failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn validate_data(
+    text: String,
+    format: Option<DataFormat>,
+) -> Result<DataValidation, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let format = match format {
+            Some(DataFormat::Auto) | None => data::guess_format(&text),
+            Some(format) => format,
+        };
+        let error = data::validate(format, &text).err();
+        DataValidation {
+            format,
+            valid: error.is_none(),
+            error,
+        }
+    })
+    .await
+    .map_err(|e| format!("Validation failed: {}", e))
+}
+
+#[tauri::command]
+pub async fn detect_data_format(text: String) -> Result<Option<DataFormat>, String> {
+    tauri::async_runtime::spawn_blocking(move || data::detect_format(&text))
+        .await
+        .map_err(|e| format!("Format detection failed: {}", e))
+}
+
+// Encoding Commands
+#[tauri::command]
+pub async fn encode_text(
+    app: tauri::AppHandle,
+    codec: Codec,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    transform_clipboard_text(
+        &app,
+        TransformStep::EncodeText(EncodeText { codec }),
+        entry_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn decode_text(
+    app: tauri::AppHandle,
+    codec: Codec,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    transform_clipboard_text(
+        &app,
+        TransformStep::DecodeText(DecodeText { codec }),
+        entry_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn decode_jwt(
+    app: tauri::AppHandle,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    transform_clipboard_text(&app, TransformStep::DecodeJwt(DecodeJwt), entry_id).await
+}
+
+#[tauri::command]
+pub async fn convert_case(
+    app: tauri::AppHandle,
+    style: CaseStyle,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    transform_clipboard_text(
+        &app,
+        TransformStep::ConvertCase(ConvertCase { style }),
+        entry_id,
+    )
+    .await
+}
+
+// Transform Commands
+// Run any single pipeline step, e.g. `{ "kind": "sort_lines", "descending": true }`
+#[tauri::command]
+pub async fn apply_transform(
+    app: tauri::AppHandle,
+    step: TransformStep,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    transform_clipboard_text(&app, step, entry_id).await
+}
+
+// Table Commands
+#[tauri::command]
+pub async fn convert_table(
+    app: tauri::AppHandle,
+    options: Option<ConvertTable>,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    let step = TransformStep::ConvertTable(options.unwrap_or_default());
+    transform_clipboard_text(&app, step, entry_id).await
+}
+
+#[tauri::command]
+pub async fn detect_table(text: String) -> Result<Option<TableInfo>, String> {
+    tauri::async_runtime::spawn_blocking(move || tables::detect_table(&text))
+        .await
+        .map_err(|e| format!("Table detection failed: {}", e))
+}
+
+// Script Commands
+#[tauri::command]
+pub async fn list_scripts() -> Result<Vec<ScriptInfo>, String> {
+    scripts::list_scripts().map_err(|e| format!("Failed to list scripts: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_scripts_folder() -> Result<String, String> {
+    scripts::scripts_dir()
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .map_err(|e| format!("Failed to open scripts folder: {}", e))
+}
+
+#[tauri::command]
+pub async fn run_script(
+    app: tauri::AppHandle,
+    script: String,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    run_script_on_clipboard(&app, script, entry_id).await
+}
+
+// Run a script with the source entry's metadata when the text is in history
+pub async fn run_script_on_clipboard(
+    app: &tauri::AppHandle,
+    script: String,
+    entry_id: Option<String>,
+) -> Result<ClipboardEntry, String> {
+    let script = RunScript { script };
+    transform_clipboard_with(app, entry_id, "run_script", move |source, entry| {
+        let metadata = match entry {
+            Some(entry) => ScriptEntry::from(entry),
+            None => ScriptEntry::for_text(source),
+        };
+        script.run(source, &metadata)
+    })
+    .await
+}
@@ -0,0 +1,378 @@
+use super::Transform;
+use serde::{Deserialize, Serialize};
+
+// Statements that open an indented block when the line ends with ':'
+const BLOCK_KEYWORDS: &[&str] = &[
+    "if", "elif", "else", "for", "while", "def", "class", "try", "except", "finally", "with",
+    "match", "case",
+];
+
+// Keywords followed by a single module or package path, e.g. "import os"
+const IMPORT_KEYWORDS: &[&str] = &["import ", "use ", "package ", "#include ", "require "];
+
+// Comment lines say nothing either way, so they are left out of the count
+const COMMENT_PREFIXES: &[&str] = &["//", "/*", "*/", "* ", "#!"];
+
+// Prompts copied from terminals, REPLs and documentation
+const PROMPTS: &[&str] = &["$ ", "% ", "PS> ", ">>> "];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndentStyle {
+    #[default]
+    Spaces,
+    Tabs,
+    // Leave indentation characters as they are
+    Keep,
+}
+
+// Clean pasted source code without destroying its indentation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodeCleanup {
+    pub indent_style: IndentStyle,
+    pub tab_width: usize,
+    // Remove the indentation shared by every line
+    pub dedent: bool,
+    // Remove "12 | " style line-number gutters copied from editors and docs
+    pub strip_line_numbers: bool,
+    // Remove "$ ", ">>> " style prompts copied from terminals
+    pub strip_prompts: bool,
+}
+
+impl Default for CodeCleanup {
+    fn default() -> Self {
+        CodeCleanup {
+            indent_style: IndentStyle::Spaces,
+            tab_width: 4,
+            dedent: true,
+            strip_line_numbers: true,
+            strip_prompts: true,
+        }
+    }
+}
+
+impl Transform for CodeCleanup {
+    fn name(&self) -> &'static str {
+        "code_cleanup"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+        let mut lines: Vec<String> = normalized
+            .split('\n')
+            .map(|line| line.trim_end().to_string())
+            .collect();
+
+        if self.strip_line_numbers {
+            lines = strip_line_number_gutter(lines);
+        }
+        if self.strip_prompts {
+            lines = strip_prompts(lines);
+        }
+
+        // Fence lines are kept verbatim and each block between them is cleaned on its own
+        let mut output = Vec::with_capacity(lines.len());
+        let mut block = Vec::new();
+        for line in lines {
+            if line.trim_start().starts_with("```") {
+                output.extend(self.clean_block(&block));
+                block.clear();
+                output.push(line);
+            } else {
+                block.push(line);
+            }
+        }
+        output.extend(self.clean_block(&block));
+
+        Ok(output.join("\n"))
+    }
+}
+
+impl CodeCleanup {
+    fn clean_block(&self, lines: &[String]) -> Vec<String> {
+        let tab_width = self.tab_width.max(1);
+        let indents: Vec<Option<usize>> = lines
+            .iter()
+            .map(|line| (!line.trim().is_empty()).then(|| indent_width(line, tab_width)))
+            .collect();
+        let common_indent = if self.dedent {
+            indents.iter().flatten().copied().min().unwrap_or(0)
+        } else {
+            0
+        };
+        let common_prefix = if self.dedent && self.indent_style == IndentStyle::Keep {
+            common_whitespace_prefix(lines)
+        } else {
+            String::new()
+        };
+
+        lines
+            .iter()
+            .zip(indents)
+            .map(|(line, indent)| {
+                let Some(width) = indent else {
+                    return String::new();
+                };
+                let body = line.trim_start_matches([' ', '\t']);
+                match self.indent_style {
+                    IndentStyle::Keep => line
+                        .strip_prefix(common_prefix.as_str())
+                        .unwrap_or(line)
+                        .to_string(),
+                    IndentStyle::Spaces => {
+                        format!("{}{}", " ".repeat(width - common_indent), body)
+                    }
+                    IndentStyle::Tabs => {
+                        let width = width - common_indent;
+                        format!(
+                            "{}{}{}",
+                            "\t".repeat(width / tab_width),
+                            " ".repeat(width % tab_width),
+                            body
+                        )
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+// Visual width of a line's leading whitespace with tabs expanded to tab stops
+fn indent_width(line: &str, tab_width: usize) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += tab_width - (width % tab_width),
+            _ => break,
+        }
+    }
+    width
+}
+
+fn common_whitespace_prefix(lines: &[String]) -> String {
+    let mut prefix: Option<&str> = None;
+    for line in lines.iter().filter(|l| !l.trim().is_empty()) {
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        prefix = Some(match prefix {
+            None => indent,
+            Some(current) => {
+                let shared = current
+                    .bytes()
+                    .zip(indent.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                &current[..shared]
+            }
+        });
+    }
+    prefix.unwrap_or("").to_string()
+}
+
+// Split "  12 | code", "12: code" or "12\tcode" into its number and the code after the gutter
+pub(crate) fn split_line_number(line: &str) -> Option<(u64, &str)> {
+    let trimmed = line.trim_start();
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 || digits > 6 {
+        return None;
+    }
+    let number = trimmed[..digits].parse().ok()?;
+    let rest = &trimmed[digits..];
+    if rest.trim().is_empty() {
+        return Some((number, ""));
+    }
+
+    if let Some(body) = rest.trim_start_matches(' ').strip_prefix(['|', ':']) {
+        return Some((number, body.strip_prefix([' ', '\t']).unwrap_or(body)));
+    }
+    rest.strip_prefix([' ', '\t']).map(|body| (number, body))
+}
+
+fn strip_line_number_gutter(lines: Vec<String>) -> Vec<String> {
+    let numbered: Vec<Option<(u64, &str)>> = lines
+        .iter()
+        .map(|line| {
+            if line.trim().is_empty() {
+                None
+            } else {
+                split_line_number(line)
+            }
+        })
+        .collect();
+    let content_lines = lines.iter().filter(|l| !l.trim().is_empty()).count();
+    let numbers: Vec<u64> = numbered.iter().flatten().map(|(n, _)| *n).collect();
+
+    // Only treat it as a gutter when nearly every line is numbered in increasing order
+    let is_gutter = numbers.len() >= 2
+        && numbers.len() * 10 >= content_lines * 9
+        && numbers.windows(2).all(|pair| pair[1] > pair[0]);
+    if !is_gutter {
+        return lines;
+    }
+
+    numbered
+        .iter()
+        .zip(&lines)
+        .map(|(split, line)| match split {
+            Some((_, body)) => body.to_string(),
+            None => line.clone(),
+        })
+        .collect()
+}
+
+// Only treat it as a transcript when most lines start with the same prompt, so LaTeX
+// "% " comments and the like are left alone. Indentation before the prompt is kept.
+fn strip_prompts(lines: Vec<String>) -> Vec<String> {
+    let starts_with = |prompt: &str| {
+        lines
+            .iter()
+            .filter(|line| line.trim_start().starts_with(prompt))
+            .count()
+    };
+    let content_lines = lines.iter().filter(|l| !l.trim().is_empty()).count();
+    let Some((prompt, count)) = PROMPTS
+        .iter()
+        .map(|&prompt| match prompt {
+            ">>> " => (prompt, starts_with(prompt) + starts_with("... ")),
+            _ => (prompt, starts_with(prompt)),
+        })
+        .max_by_key(|&(_, count)| count)
+    else {
+        return lines;
+    };
+    if count * 2 <= content_lines {
+        return lines;
+    }
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+            let continuation = if prompt == ">>> " {
+                trimmed.strip_prefix("... ")
+            } else {
+                None
+            };
+            trimmed
+                .strip_prefix(prompt)
+                .or(continuation)
+                .map(|rest| format!("{}{}", indent, rest))
+                .unwrap_or(line)
+        })
+        .collect()
+}
+
+// Heuristic used to classify clipboard content as source code. Most lines have to show
+// code syntax, so prose that merely starts with a keyword or "@" isn't caught.
+pub fn looks_like_code(text: &str) -> bool {
+    if text.contains("```") {
+        return true;
+    }
+
+    let lines = strip_line_number_gutter(text.lines().map(str::to_string).collect());
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !COMMENT_PREFIXES.iter().any(|p| line.starts_with(p)))
+        .collect();
+    if lines.len() < 2 {
+        return false;
+    }
+
+    let code_lines = lines.iter().filter(|line| is_code_line(line)).count();
+    code_lines * 3 >= lines.len() * 2
+}
+
+fn is_code_line(line: &str) -> bool {
+    line.ends_with(';')
+        || line.ends_with('{')
+        || line == "}"
+        || line.starts_with("};")
+        || line.starts_with("})")
+        || line.contains(" => ")
+        || line.contains(" -> ")
+        || line.contains("::")
+        || is_call(line)
+        || is_assignment(line)
+        || is_block_header(line)
+        || is_import(line)
+        || is_decorator(line)
+        || is_prompt_command(line)
+}
+
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '$'))
+}
+
+// A name directly followed by its arguments: "print(x)", "greet(name):"
+fn is_call(line: &str) -> bool {
+    let Some(open) = line.find('(') else {
+        return false;
+    };
+    let before = &line[..open];
+    let name_end = before.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+    let tail = line.trim_end_matches([':', ';', ',', '{', ' ']);
+    name_end && tail.ends_with(')')
+}
+
+// "x = 1", "let total = a + b", "self.name = name"
+fn is_assignment(line: &str) -> bool {
+    let Some((target, value)) = line.split_once(" = ") else {
+        return false;
+    };
+    let target = ["let mut ", "let ", "const ", "var "]
+        .iter()
+        .find_map(|keyword| target.strip_prefix(keyword))
+        .unwrap_or(target);
+    let target = target.split_once(':').map_or(target, |(name, _)| name);
+    is_identifier(target.trim_end_matches([']', '[']).trim()) && !value.trim().is_empty()
+}
+
+// "def greet(name):", "else:", "for item in items:"
+fn is_block_header(line: &str) -> bool {
+    let first_word = line
+        .split(|c: char| c.is_whitespace() || c == ':' || c == '(')
+        .next()
+        .unwrap_or("");
+    line.ends_with(':') && BLOCK_KEYWORDS.contains(&first_word)
+}
+
+// "import os", "use std::fmt", "from os import path"
+fn is_import(line: &str) -> bool {
+    if let Some(rest) = line.strip_prefix("from ") {
+        return rest
+            .split_once(" import ")
+            .is_some_and(|(module, names)| is_identifier(module) && !names.trim().is_empty());
+    }
+    IMPORT_KEYWORDS.iter().any(|keyword| {
+        line.strip_prefix(keyword).is_some_and(|path| {
+            let path = path.trim_end_matches(';');
+            !path.is_empty() && !path.contains(char::is_whitespace)
+        })
+    })
+}
+
+// "@dataclass", "@app.route('/')"
+fn is_decorator(line: &str) -> bool {
+    let Some(rest) = line.strip_prefix('@') else {
+        return false;
+    };
+    match rest.split_once('(') {
+        Some((name, _)) => is_identifier(name) && rest.ends_with(')'),
+        None => is_identifier(rest),
+    }
+}
+
+// "$ cargo build", ">>> print(x)" but not "$ 20 each"
+fn is_prompt_command(line: &str) -> bool {
+    PROMPTS.iter().any(|prompt| {
+        line.strip_prefix(prompt).is_some_and(|command| {
+            command.starts_with(|c: char| c.is_ascii_lowercase() || matches!(c, '.' | '/' | '~'))
+        })
+    })
+}
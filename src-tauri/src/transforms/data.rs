@@ -0,0 +1,445 @@
+use super::{sql, xml, yaml, Transform};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Map, Value};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFormat {
+    // Detect JSON, XML or SQL; text in any other format passes through unchanged
+    #[default]
+    Auto,
+    Json,
+    Yaml,
+    Xml,
+    Sql,
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataFormat::Auto => "data",
+            DataFormat::Json => "JSON",
+            DataFormat::Yaml => "YAML",
+            DataFormat::Xml => "XML",
+            DataFormat::Sql => "SQL",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataAction {
+    #[default]
+    Pretty,
+    Minify,
+    // Leave the text as it is but fail on invalid input
+    Validate,
+    SortKeys,
+    ToJson,
+    ToYaml,
+}
+
+// A syntax error with a 1-based location in the input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl DataError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        DataError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    // Locate a byte offset in `text`
+    pub fn at_offset(text: &str, offset: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        DataError::new(line, column, message)
+    }
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+// Pretty-print, minify, validate or convert JSON, YAML, XML and SQL
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatData {
+    pub format: DataFormat,
+    pub action: DataAction,
+    // Spaces per indentation level when pretty-printing; YAML always uses two
+    pub indent: usize,
+}
+
+impl Default for FormatData {
+    fn default() -> Self {
+        FormatData {
+            format: DataFormat::Auto,
+            action: DataAction::Pretty,
+            indent: 2,
+        }
+    }
+}
+
+impl Transform for FormatData {
+    fn name(&self) -> &'static str {
+        "format_data"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let format = match self.format {
+            DataFormat::Auto => match detect_format(text) {
+                Some(format) => format,
+                // YAML is too loose to detect, but converting to JSON is an explicit request
+                None if self.action == DataAction::ToJson => DataFormat::Yaml,
+                None => return Ok(text.to_string()),
+            },
+            format => format,
+        };
+
+        self.format_as(format, text)
+            .map_err(|e| format!("Invalid {} at {}", format, e))?
+            .ok_or_else(|| format!("{:?} is not supported for {}", self.action, format))
+    }
+}
+
+impl FormatData {
+    // None if the action doesn't apply to the format
+    fn format_as(&self, format: DataFormat, text: &str) -> Result<Option<String>, DataError> {
+        let indent = self.indent.clamp(1, 8);
+        let output = match (format, self.action) {
+            (_, DataAction::Validate) => {
+                validate(format, text)?;
+                text.to_string()
+            }
+            (DataFormat::Json, DataAction::Pretty | DataAction::ToJson) => {
+                RawJson::parse(text)?.to_json(Some(indent))
+            }
+            (DataFormat::Json, DataAction::Minify) => RawJson::parse(text)?.to_json(None),
+            (DataFormat::Json, DataAction::SortKeys) => {
+                RawJson::parse(text)?.sorted().to_json(Some(indent))
+            }
+            (DataFormat::Json, DataAction::ToYaml) => yaml::to_yaml(&[parse_json(text)?]),
+            (DataFormat::Yaml, DataAction::Pretty | DataAction::ToYaml) => {
+                yaml::to_yaml(&yaml::parse(text)?)
+            }
+            (DataFormat::Yaml, DataAction::SortKeys) => {
+                let documents: Vec<Value> = yaml::parse(text)?.into_iter().map(sort_keys).collect();
+                yaml::to_yaml(&documents)
+            }
+            // JSON is valid YAML, so the flow style is the minified form
+            (DataFormat::Yaml, DataAction::Minify) => to_json(&yaml::parse_value(text)?, None),
+            (DataFormat::Yaml, DataAction::ToJson) => {
+                to_json(&yaml::parse_value(text)?, Some(indent))
+            }
+            (DataFormat::Xml, DataAction::Pretty) => xml::format(text, Some(indent))?,
+            (DataFormat::Xml, DataAction::Minify) => xml::format(text, None)?,
+            (DataFormat::Sql, DataAction::Pretty) => sql::format(text, Some(indent)),
+            (DataFormat::Sql, DataAction::Minify) => sql::format(text, None),
+            _ => return Ok(None),
+        };
+        Ok(Some(output))
+    }
+}
+
+pub fn validate(format: DataFormat, text: &str) -> Result<(), DataError> {
+    match format {
+        DataFormat::Auto => Ok(()),
+        DataFormat::Json => check_json(text),
+        DataFormat::Yaml => yaml::parse(text).map(|_| ()),
+        DataFormat::Xml => xml::format(text, None).map(|_| ()),
+        DataFormat::Sql => sql::validate(text),
+    }
+}
+
+// Detect JSON objects and arrays, well-formed XML and SQL statements
+pub fn detect_format(text: &str) -> Option<DataFormat> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return check_json(text).is_ok().then_some(DataFormat::Json);
+    }
+    if trimmed.starts_with('<') {
+        return xml::format(text, None).is_ok().then_some(DataFormat::Xml);
+    }
+    sql::looks_like_sql(trimmed).then_some(DataFormat::Sql)
+}
+
+// The format `text` is most likely meant to be, even when it doesn't parse,
+// so validation can point at the error instead of failing to detect anything
+pub fn guess_format(text: &str) -> DataFormat {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        DataFormat::Json
+    } else if trimmed.starts_with('<') {
+        DataFormat::Xml
+    } else if sql::looks_like_sql(trimmed) {
+        DataFormat::Sql
+    } else {
+        DataFormat::Yaml
+    }
+}
+
+// Pretty-printed with `indent` spaces per level, or minified when None
+pub fn to_json(value: &Value, indent: Option<usize>) -> String {
+    let Some(width) = indent else {
+        return value.to_string();
+    };
+    let indent = " ".repeat(width);
+    let mut output = Vec::new();
+    let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    match value.serialize(&mut serializer) {
+        Ok(()) => String::from_utf8(output).unwrap_or_default(),
+        Err(_) => value.to_string(),
+    }
+}
+
+pub fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        Value::Object(entries) => {
+            let mut entries: Map<String, Value> = entries
+                .into_iter()
+                .map(|(key, value)| (key, sort_keys(value)))
+                .collect();
+            entries.sort_keys();
+            Value::Object(entries)
+        }
+        value => value,
+    }
+}
+
+// Parse JSON keeping key order, reporting serde_json's error location
+pub fn parse_json(text: &str) -> Result<Value, DataError> {
+    serde_json::from_str(text).map_err(json_error)
+}
+
+// Check the syntax only, so numbers too large for an f64 are still fine
+fn check_json(text: &str) -> Result<(), DataError> {
+    serde_json::from_str::<IgnoredAny>(text)
+        .map(|_| ())
+        .map_err(json_error)
+}
+
+fn json_error(e: serde_json::Error) -> DataError {
+    let message = e.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    DataError::new(e.line(), e.column(), message)
+}
+
+// JSON as written, so reformatting it never changes a number's precision; serde_json
+// values hold numbers as f64. Repeated keys are kept too.
+#[derive(Debug)]
+enum RawJson {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<RawJson>),
+    Object(Vec<(String, RawJson)>),
+}
+
+impl RawJson {
+    // serde_json checks the syntax first, so errors read the same as for `parse_json`
+    fn parse(text: &str) -> Result<RawJson, DataError> {
+        check_json(text)?;
+        let mut parser = RawJsonParser { text, position: 0 };
+        parser
+            .value()
+            .filter(|_| parser.rest().trim().is_empty())
+            .ok_or_else(|| DataError::at_offset(text, parser.position, "unexpected character"))
+    }
+
+    fn sorted(self) -> RawJson {
+        match self {
+            RawJson::Array(items) => {
+                RawJson::Array(items.into_iter().map(RawJson::sorted).collect())
+            }
+            RawJson::Object(mut entries) => {
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                RawJson::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, value.sorted()))
+                        .collect(),
+                )
+            }
+            value => value,
+        }
+    }
+
+    // Laid out the way `to_json` lays out serde_json values
+    fn to_json(&self, indent: Option<usize>) -> String {
+        let mut output = String::new();
+        self.write(
+            &mut output,
+            indent.map(|width| " ".repeat(width)).as_deref(),
+            0,
+        );
+        output
+    }
+
+    fn write(&self, output: &mut String, indent: Option<&str>, depth: usize) {
+        let (open, close, entries): (char, char, Vec<(Option<&String>, &RawJson)>) = match self {
+            RawJson::Null => return output.push_str("null"),
+            RawJson::Bool(value) => return output.push_str(if *value { "true" } else { "false" }),
+            RawJson::Number(number) => return output.push_str(number),
+            RawJson::String(value) => return output.push_str(&json_string(value)),
+            RawJson::Array(items) => ('[', ']', items.iter().map(|item| (None, item)).collect()),
+            RawJson::Object(entries) => (
+                '{',
+                '}',
+                entries
+                    .iter()
+                    .map(|(key, value)| (Some(key), value))
+                    .collect(),
+            ),
+        };
+        output.push(open);
+        for (index, (key, value)) in entries.iter().enumerate() {
+            if index > 0 {
+                output.push(',');
+            }
+            if let Some(indent) = indent {
+                output.push('\n');
+                output.push_str(&indent.repeat(depth + 1));
+            }
+            if let Some(key) = key {
+                output.push_str(&json_string(key));
+                output.push_str(if indent.is_some() { ": " } else { ":" });
+            }
+            value.write(output, indent, depth + 1);
+        }
+        if let (Some(indent), false) = (indent, entries.is_empty()) {
+            output.push('\n');
+            output.push_str(&indent.repeat(depth));
+        }
+        output.push(close);
+    }
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+// Reads JSON serde_json has already accepted, so it only has to find where things end
+struct RawJsonParser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> RawJsonParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    // Skip whitespace and then `c` if it comes next
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(c);
+        if found {
+            self.position += c.len_utf8();
+        }
+        found
+    }
+
+    fn value(&mut self) -> Option<RawJson> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        for (literal, value) in [
+            ("null", RawJson::Null),
+            ("true", RawJson::Bool(true)),
+            ("false", RawJson::Bool(false)),
+        ] {
+            if rest.starts_with(literal) {
+                self.position += literal.len();
+                return Some(value);
+            }
+        }
+        if rest.starts_with('"') {
+            return self.string().map(RawJson::String);
+        }
+        if self.eat('[') {
+            let mut items = Vec::new();
+            if !self.eat(']') {
+                loop {
+                    items.push(self.value()?);
+                    if self.eat(']') {
+                        break;
+                    }
+                    if !self.eat(',') {
+                        return None;
+                    }
+                }
+            }
+            return Some(RawJson::Array(items));
+        }
+        if self.eat('{') {
+            let mut entries = Vec::new();
+            if !self.eat('}') {
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    if !self.eat(':') {
+                        return None;
+                    }
+                    entries.push((key, self.value()?));
+                    if self.eat('}') {
+                        break;
+                    }
+                    if !self.eat(',') {
+                        return None;
+                    }
+                }
+            }
+            return Some(RawJson::Object(entries));
+        }
+        let length = rest
+            .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(rest.len());
+        self.position += length;
+        (length > 0).then(|| RawJson::Number(rest[..length].to_string()))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let rest = self.rest();
+        let mut escaped = false;
+        let (end, _) = rest.char_indices().skip(1).find(|&(_, c)| {
+            let closes = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            closes
+        })?;
+        self.position += end + 1;
+        serde_json::from_str(&rest[..=end]).ok()
+    }
+}
@@ -0,0 +1,307 @@
+use super::data::{parse_json, to_json};
+use super::html::decode_entities;
+use super::Transform;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use heck::{ToKebabCase, ToLowerCamelCase, ToSnakeCase, ToTitleCase};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// Everything except RFC 3986 unreserved characters, as encodeURIComponent does
+const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Codec {
+    #[default]
+    Base64,
+    Url,
+    HtmlEntities,
+    Hex,
+    // \uXXXX escapes for non-ASCII characters, as in JSON and JavaScript
+    UnicodeEscapes,
+}
+
+impl Codec {
+    pub fn encode(self, text: &str) -> String {
+        match self {
+            Codec::Base64 => STANDARD.encode(text),
+            Codec::Url => utf8_percent_encode(text, URL_COMPONENT).to_string(),
+            Codec::HtmlEntities => encode_html_entities(text),
+            Codec::Hex => text.bytes().map(|b| format!("{:02x}", b)).collect(),
+            Codec::UnicodeEscapes => escape_unicode(text),
+        }
+    }
+
+    pub fn decode(self, text: &str) -> Result<String, String> {
+        match self {
+            Codec::Base64 => decode_base64(text).and_then(utf8),
+            Codec::Url => percent_decode_str(text.trim())
+                .decode_utf8()
+                .map(|decoded| decoded.into_owned())
+                .map_err(|_| "Decoded data is not UTF-8 text".to_string()),
+            Codec::HtmlEntities => Ok(decode_entities(text, false)),
+            Codec::Hex => decode_hex(text).and_then(utf8),
+            Codec::UnicodeEscapes => unescape_unicode(text),
+        }
+    }
+}
+
+fn utf8(bytes: Vec<u8>) -> Result<String, String> {
+    String::from_utf8(bytes).map_err(|_| "Decoded data is not UTF-8 text".to_string())
+}
+
+// Accepts standard and URL-safe alphabets, with or without padding and line breaks
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let compact = compact.trim_end_matches('=');
+    let engine = if compact.contains(['-', '_']) {
+        URL_SAFE_NO_PAD
+    } else {
+        STANDARD_NO_PAD
+    };
+    engine
+        .decode(compact)
+        .map_err(|e| format!("Invalid Base64: {}", e))
+}
+
+// Accepts "0x" prefixes and space, colon or dash separators between bytes
+fn decode_hex(text: &str) -> Result<Vec<u8>, String> {
+    let trimmed = text.trim();
+    let trimmed = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    let digits: Vec<u8> = trimmed
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace() && !matches!(b, b':' | b'-'))
+        .collect();
+
+    if let Some(bad) = digits.iter().find(|b| !b.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Invalid hex: unexpected character '{}'",
+            *bad as char
+        ));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err("Invalid hex: odd number of digits".to_string());
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).unwrap_or("00");
+            u8::from_str_radix(pair, 16).unwrap_or(0)
+        })
+        .collect())
+}
+
+fn encode_html_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+fn escape_unicode(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        // A literal backslash before "u" would otherwise decode as an escape
+        if c == '\\' && chars.peek() == Some(&'u') {
+            output.push_str("\\u005c");
+        } else if c.is_ascii() {
+            output.push(c);
+        } else {
+            // Characters outside the BMP become a surrogate pair
+            let mut units = [0u16; 2];
+            for unit in c.encode_utf16(&mut units) {
+                output.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    output
+}
+
+// Decode \uXXXX (including surrogate pairs) and \u{X...} escapes, leaving other backslashes alone
+fn unescape_unicode(text: &str) -> Result<String, String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("\\u") {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(braced) = rest[2..].strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or("Invalid Unicode escape: missing '}'")?;
+            let c = u32::from_str_radix(&braced[..end], 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| format!("Invalid Unicode escape: \\u{{{}}}", &braced[..end]))?;
+            output.push(c);
+            rest = &braced[end + 1..];
+            continue;
+        }
+
+        let Some(unit) = hex_unit(&rest[2..]) else {
+            output.push_str("\\u");
+            rest = &rest[2..];
+            continue;
+        };
+        rest = &rest[6..];
+
+        let decoded = if (0xD800..0xDC00).contains(&unit) {
+            let low = rest.strip_prefix("\\u").and_then(hex_unit);
+            match low {
+                Some(low) if (0xDC00..0xE000).contains(&low) => {
+                    rest = &rest[6..];
+                    char::decode_utf16([unit, low]).next().and_then(|c| c.ok())
+                }
+                _ => None,
+            }
+        } else {
+            char::from_u32(unit as u32)
+        };
+        output.push(decoded.ok_or_else(|| {
+            format!("Invalid Unicode escape: unpaired surrogate \\u{:04x}", unit)
+        })?);
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn hex_unit(text: &str) -> Option<u16> {
+    let digits = text.get(..4)?;
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u16::from_str_radix(digits, 16).ok()
+}
+
+// Encode text with a codec
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncodeText {
+    pub codec: Codec,
+}
+
+impl Transform for EncodeText {
+    fn name(&self) -> &'static str {
+        "encode_text"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        Ok(self.codec.encode(text))
+    }
+}
+
+// Decode text encoded with a codec, failing on malformed input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecodeText {
+    pub codec: Codec,
+}
+
+impl Transform for DecodeText {
+    fn name(&self) -> &'static str {
+        "decode_text"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        self.codec.decode(text)
+    }
+}
+
+// Show a JWT's header and payload as JSON. The signature is not verified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecodeJwt;
+
+impl Transform for DecodeJwt {
+    fn name(&self) -> &'static str {
+        "decode_jwt"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let token = text.trim();
+        let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() != 3 {
+            return Err("Invalid JWT: expected three dot-separated parts".to_string());
+        }
+
+        let decode_part = |label: &str, part: &str| -> Result<Value, String> {
+            let bytes = decode_base64(part).map_err(|e| format!("Invalid JWT {}: {}", label, e))?;
+            let json = utf8(bytes).map_err(|e| format!("Invalid JWT {}: {}", label, e))?;
+            parse_json(&json).map_err(|e| format!("Invalid JWT {}: {}", label, e))
+        };
+
+        let decoded = Value::Object(Map::from_iter([
+            ("header".to_string(), decode_part("header", parts[0])?),
+            ("payload".to_string(), decode_part("payload", parts[1])?),
+        ]));
+        Ok(to_json(&decoded, Some(2)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseStyle {
+    Camel,
+    #[default]
+    Snake,
+    Kebab,
+    Title,
+}
+
+// Convert each line to camelCase, snake_case, kebab-case or Title Case
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConvertCase {
+    pub style: CaseStyle,
+}
+
+impl Transform for ConvertCase {
+    fn name(&self) -> &'static str {
+        "convert_case"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let lines: Vec<String> = text
+            .split('\n')
+            .map(|line| {
+                let body = line.trim();
+                if body.is_empty() {
+                    return line.to_string();
+                }
+                let indentation = &line[..line.len() - line.trim_start().len()];
+                let converted = match self.style {
+                    CaseStyle::Camel => body.to_lower_camel_case(),
+                    CaseStyle::Snake => body.to_snake_case(),
+                    CaseStyle::Kebab => body.to_kebab_case(),
+                    CaseStyle::Title => body.to_title_case(),
+                };
+                // Lines without any letters or digits have no words to convert
+                if converted.is_empty() {
+                    return line.to_string();
+                }
+                format!("{}{}", indentation, converted)
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+}
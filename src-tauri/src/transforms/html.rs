@@ -0,0 +1,834 @@
+use super::Transform;
+use serde::{Deserialize, Serialize};
+
+// Tags that mark the clipboard text as an HTML document or fragment
+const HTML_TAGS: &[&str] = &[
+    "html",
+    "body",
+    "meta",
+    "p",
+    "div",
+    "span",
+    "br",
+    "ul",
+    "ol",
+    "li",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "table",
+    "a",
+    "b",
+    "strong",
+    "em",
+    "i",
+    "pre",
+    "code",
+    "blockquote",
+];
+
+// Elements whose content never ends up in the Markdown
+const SKIPPED_TAGS: &[&str] = &["head", "script", "style", "title", "template", "noscript"];
+
+const BLOCK_TAGS: &[&str] = &[
+    "div",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "main",
+    "nav",
+    "aside",
+    "figure",
+    "figcaption",
+    "address",
+    "dl",
+    "dt",
+    "dd",
+    "details",
+    "summary",
+    "center",
+];
+
+// Convert HTML copied from browsers, docs and mail clients into Markdown.
+// Text that doesn't look like HTML passes through unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HtmlToMarkdown {
+    // Drop images instead of converting them to ![alt](src)
+    pub strip_images: bool,
+}
+
+impl Transform for HtmlToMarkdown {
+    fn name(&self) -> &'static str {
+        "html_to_markdown"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        if !looks_like_html(text) {
+            return Ok(text.to_string());
+        }
+        Ok(self.convert(text))
+    }
+}
+
+impl HtmlToMarkdown {
+    pub fn convert(&self, html: &str) -> String {
+        let mut converter = Converter {
+            strip_images: self.strip_images,
+            ..Converter::default()
+        };
+        for token in tokenize(strip_cf_html_header(html)) {
+            converter.handle(token);
+        }
+        converter.finish()
+    }
+}
+
+// Heuristic for HTML fragments: starts with a tag and contains a known element
+pub fn looks_like_html(text: &str) -> bool {
+    let trimmed = strip_cf_html_header(text).trim_start();
+    if !trimmed.starts_with('<') {
+        return false;
+    }
+    let has_closing = trimmed.contains("</") || trimmed.contains("<br");
+    has_closing
+        && tokenize(trimmed).iter().any(|token| match token {
+            Token::Start { name, .. } | Token::End(name) => HTML_TAGS.contains(&name.as_str()),
+            Token::Text(_) => false,
+        })
+}
+
+// Windows puts a "Version:0.9 StartHTML:..." header in front of the HTML clipboard flavor
+fn strip_cf_html_header(text: &str) -> &str {
+    if text.trim_start().starts_with("Version:") {
+        if let Some(start) = text.find('<') {
+            return &text[start..];
+        }
+    }
+    text
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    Start { name: String, attributes: &'a str },
+    End(String),
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut position = 0;
+
+    while let Some(offset) = html[position..].find('<') {
+        let tag_start = position + offset;
+        let rest = &html[tag_start..];
+
+        // Comments, doctypes and processing instructions are dropped
+        let skip_to = if rest.starts_with("<!--") {
+            Some(
+                rest.find("-->")
+                    .map_or(html.len(), |end| tag_start + end + 3),
+            )
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            Some(rest.find('>').map_or(html.len(), |end| tag_start + end + 1))
+        } else {
+            None
+        };
+        if let Some(end) = skip_to {
+            if text_start < tag_start {
+                tokens.push(Token::Text(&html[text_start..tag_start]));
+            }
+            position = end;
+            text_start = end;
+            continue;
+        }
+
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let name: String = rest[name_start..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        let Some(tag_end) = find_tag_end(rest).filter(|_| !name.is_empty()) else {
+            // A stray "<" is just text
+            position = tag_start + 1;
+            continue;
+        };
+
+        if text_start < tag_start {
+            tokens.push(Token::Text(&html[text_start..tag_start]));
+        }
+        let name = name.to_ascii_lowercase();
+        if closing {
+            tokens.push(Token::End(name));
+        } else {
+            let attributes = rest[name_start + name.len()..tag_end].trim_end_matches('/');
+            tokens.push(Token::Start { name, attributes });
+        }
+        position = tag_start + tag_end + 1;
+        text_start = position;
+    }
+
+    if text_start < html.len() {
+        tokens.push(Token::Text(&html[text_start..]));
+    }
+    tokens
+}
+
+// Index of the ">" closing a tag, skipping over quoted attribute values
+fn find_tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+        let key_len = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_len];
+        rest = rest[key_len..].trim_start();
+
+        let value = if let Some(after_equals) = rest.strip_prefix('=') {
+            let after_equals = after_equals.trim_start();
+            let (value, remaining) = match after_equals.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = after_equals[1..]
+                        .find(q)
+                        .map_or(after_equals.len(), |i| i + 1);
+                    (
+                        &after_equals[1..end],
+                        after_equals.get(end + 1..).unwrap_or(""),
+                    )
+                }
+                _ => {
+                    let end = after_equals
+                        .find(char::is_whitespace)
+                        .unwrap_or(after_equals.len());
+                    (&after_equals[..end], &after_equals[end..])
+                }
+            };
+            rest = remaining;
+            value
+        } else {
+            ""
+        };
+
+        if key.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value, false));
+        }
+    }
+}
+
+pub(crate) fn decode_entities(text: &str, keep_angle_brackets: bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let decoded = entity.and_then(|name| match name {
+            "lt" | "gt" if keep_angle_brackets => None,
+            _ => decode_entity(name),
+        });
+
+        match (entity, decoded) {
+            (Some(name), Some(c)) => {
+                output.push(c);
+                rest = &rest[name.len() + 2..];
+            }
+            _ => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        "euro" => '€',
+        _ => return None,
+    };
+    Some(c)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !in_space {
+                output.push(' ');
+            }
+            in_space = true;
+        } else {
+            output.push(c);
+            in_space = false;
+        }
+    }
+    output
+}
+
+#[derive(Debug, Default)]
+struct List {
+    ordered: bool,
+    next: u64,
+    items: usize,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+}
+
+#[derive(Debug)]
+struct OpenInline {
+    tag: String,
+    closing: String,
+    // Length of the pending openers when the element started; if the opener is
+    // still pending when the element closes, it never got any text
+    pending_len: usize,
+}
+
+#[derive(Debug, Default)]
+struct Converter {
+    strip_images: bool,
+    out: String,
+    // Newlines to write before the next text, and the quote depth for the blank lines among them
+    pending_newlines: usize,
+    break_quote_depth: usize,
+    quote_depth: usize,
+    lists: Vec<List>,
+    // List marker waiting for the item's first text
+    pending_marker: Option<String>,
+    // Inline openers ("**", "[", ...) waiting for text, so empty elements vanish
+    pending_inline: String,
+    inline: Vec<OpenInline>,
+    skip_depth: usize,
+    pre: Option<String>,
+    pre_language: String,
+    inline_code: usize,
+    table: Option<Table>,
+    cell: Option<String>,
+}
+
+impl Converter {
+    fn handle(&mut self, token: Token<'_>) {
+        match token {
+            Token::Start { name, attributes } => self.start(&name, attributes),
+            Token::End(name) => self.end(&name),
+            Token::Text(text) => self.text(text),
+        }
+    }
+
+    fn start(&mut self, name: &str, attributes: &str) {
+        if SKIPPED_TAGS.contains(&name) {
+            self.skip_depth += 1;
+            return;
+        }
+        if self.skip_depth > 0 {
+            return;
+        }
+
+        if let Some(pre) = &mut self.pre {
+            match name {
+                "br" => pre.push('\n'),
+                "code" if self.pre_language.is_empty() => {
+                    self.pre_language = code_language(attributes);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match name {
+            // Paragraphs inside list items stay tight
+            "p" => self.break_line(if self.lists.is_empty() { 2 } else { 1 }),
+            "br" => {
+                if self.cell.is_some() {
+                    self.text(" ");
+                } else if self.pending_marker.is_none() {
+                    self.pending_newlines = (self.pending_newlines + 1).min(2);
+                    self.break_quote_depth = self.quote_depth;
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.break_line(2);
+                let level = name[1..].parse().unwrap_or(1);
+                self.open_inline(name, format!("{} ", "#".repeat(level)), String::new());
+            }
+            "hr" => {
+                self.break_line(2);
+                self.write_markup("---");
+                self.break_line(2);
+            }
+            "blockquote" => {
+                self.break_line(2);
+                self.quote_depth += 1;
+            }
+            "ul" | "ol" => {
+                self.break_line(if self.lists.is_empty() { 2 } else { 1 });
+                let start = attribute(attributes, "start")
+                    .and_then(|start| start.trim().parse().ok())
+                    .unwrap_or(1);
+                self.lists.push(List {
+                    ordered: name == "ol",
+                    next: start,
+                    items: 0,
+                });
+            }
+            "li" => {
+                let marker = match self.lists.last_mut() {
+                    Some(list) => {
+                        list.items += 1;
+                        if list.ordered {
+                            list.next += 1;
+                            format!("{}. ", list.next - 1)
+                        } else {
+                            "- ".to_string()
+                        }
+                    }
+                    None => "- ".to_string(),
+                };
+                let first_item = self.lists.last().is_none_or(|list| list.items == 1);
+                if !self.out.is_empty() && self.cell.is_none() {
+                    self.pending_newlines = if first_item {
+                        self.pending_newlines.max(1)
+                    } else {
+                        1
+                    };
+                    self.break_quote_depth = self.break_quote_depth.min(self.quote_depth);
+                }
+                self.pending_marker = Some(marker);
+            }
+            "pre" => {
+                self.break_line(2);
+                self.pre = Some(String::new());
+                self.pre_language = code_language(attributes);
+            }
+            "code" | "tt" | "kbd" | "samp" => {
+                self.inline_code += 1;
+                self.open_inline(name, "`".to_string(), "`".to_string());
+            }
+            "b" | "strong" => {
+                // Google Docs wraps whole documents in <b style="font-weight:normal">
+                if style_contains(attributes, "font-weight:normal") {
+                    self.open_inline(name, String::new(), String::new());
+                } else {
+                    self.open_inline(name, "**".to_string(), "**".to_string());
+                }
+            }
+            "i" | "em" | "cite" | "var" => self.open_inline(name, "*".to_string(), "*".to_string()),
+            "s" | "strike" | "del" => self.open_inline(name, "~~".to_string(), "~~".to_string()),
+            "span" => {
+                // Docs and Word mark formatting with inline styles rather than tags
+                let mut marker = String::new();
+                if style_contains(attributes, "font-weight:700")
+                    || style_contains(attributes, "font-weight:bold")
+                {
+                    marker.push_str("**");
+                }
+                if style_contains(attributes, "font-style:italic") {
+                    marker.push('*');
+                }
+                let closing: String = marker.chars().rev().collect();
+                self.open_inline(name, marker, closing);
+            }
+            "a" => match attribute(attributes, "href")
+                .filter(|href| !href.is_empty() && !href.starts_with("javascript:"))
+            {
+                Some(href) => self.open_inline(name, "[".to_string(), format!("]({})", href)),
+                None => self.open_inline(name, String::new(), String::new()),
+            },
+            "img" if !self.strip_images => {
+                if let Some(src) = attribute(attributes, "src") {
+                    let alt = attribute(attributes, "alt").unwrap_or_default();
+                    self.write_markup(&format!("![{}]({})", alt.trim(), src));
+                }
+            }
+            // Nested tables are flattened into the outer one
+            "table" if self.table.is_none() => {
+                self.break_line(2);
+                self.table = Some(Table::default());
+            }
+            "tr" => {
+                self.close_cell();
+                self.close_row();
+            }
+            "td" | "th" => {
+                self.close_cell();
+                if self.table.is_some() {
+                    self.cell = Some(String::new());
+                }
+            }
+            _ if BLOCK_TAGS.contains(&name) => self.break_line(1),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        if SKIPPED_TAGS.contains(&name) {
+            self.skip_depth = self.skip_depth.saturating_sub(1);
+            return;
+        }
+        if self.skip_depth > 0 {
+            return;
+        }
+
+        if self.pre.is_some() {
+            if name == "pre" {
+                self.finish_pre();
+            }
+            return;
+        }
+
+        match name {
+            "p" => self.break_line(if self.lists.is_empty() { 2 } else { 1 }),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.close_inline(name);
+                self.break_line(2);
+            }
+            "blockquote" => {
+                self.break_line(2);
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.pending_marker = None;
+                self.break_line(if self.lists.is_empty() { 2 } else { 1 });
+            }
+            "li" => self.break_line(1),
+            "code" | "tt" | "kbd" | "samp" => {
+                self.close_inline(name);
+                self.inline_code = self.inline_code.saturating_sub(1);
+            }
+            "b" | "strong" | "i" | "em" | "cite" | "var" | "s" | "strike" | "del" | "span"
+            | "a" => self.close_inline(name),
+            "td" | "th" => self.close_cell(),
+            "tr" => {
+                self.close_cell();
+                self.close_row();
+            }
+            "table" => self.finish_table(),
+            _ if BLOCK_TAGS.contains(&name) => self.break_line(1),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        if self.skip_depth > 0 {
+            return;
+        }
+        if let Some(pre) = &mut self.pre {
+            pre.push_str(&decode_entities(raw, false));
+            return;
+        }
+
+        let text = decode_entities(&collapse_whitespace(raw), self.inline_code == 0);
+        let at_line_start = self.current().is_empty()
+            || self.current().ends_with('\n')
+            || (self.pending_newlines > 0 && self.cell.is_none())
+            || self.pending_marker.is_some();
+        let mut text = if at_line_start {
+            text.trim_start()
+        } else {
+            text.as_str()
+        };
+        if text.is_empty() {
+            return;
+        }
+
+        // Keep a leading space outside of any pending openers: "a <b>b</b>" -> "a **b**"
+        if let Some(stripped) = text.strip_prefix(' ') {
+            if !self.current().ends_with(' ') {
+                self.push(" ");
+            }
+            text = stripped;
+            if text.is_empty() {
+                return;
+            }
+        }
+
+        self.flush_pending();
+        self.push(text);
+    }
+
+    fn write_markup(&mut self, markup: &str) {
+        if self.skip_depth == 0 {
+            self.flush_pending();
+            self.push(markup);
+        }
+    }
+
+    fn break_line(&mut self, newlines: usize) {
+        if self.cell.is_some() {
+            if !self.current().is_empty() && !self.current().ends_with(' ') {
+                self.push(" ");
+            }
+            return;
+        }
+        // The item's marker line hasn't been written yet
+        if self.pending_marker.is_some() {
+            return;
+        }
+        if self.pending_newlines == 0 {
+            self.break_quote_depth = self.quote_depth;
+        }
+        self.pending_newlines = self.pending_newlines.max(newlines);
+        self.break_quote_depth = self.break_quote_depth.min(self.quote_depth);
+    }
+
+    fn flush_pending(&mut self) {
+        if self.cell.is_none() && (self.pending_newlines > 0 || self.pending_marker.is_some()) {
+            if !self.out.is_empty() {
+                let trimmed_len = self.out.trim_end_matches(' ').len();
+                self.out.truncate(trimmed_len);
+                let newlines = self.pending_newlines.max(1);
+                for _ in 1..newlines {
+                    self.out.push('\n');
+                    self.out.push_str(&"> ".repeat(self.break_quote_depth));
+                    let trimmed_len = self.out.trim_end_matches(' ').len();
+                    self.out.truncate(trimmed_len);
+                }
+                self.out.push('\n');
+            }
+            self.out.push_str(&self.line_prefix());
+            if let Some(marker) = self.pending_marker.take() {
+                self.out.push_str(&marker);
+            }
+        }
+        self.pending_newlines = 0;
+
+        let openers = std::mem::take(&mut self.pending_inline);
+        self.push(&openers);
+    }
+
+    // Quote markers plus the indentation of enclosing list items
+    fn line_prefix(&self) -> String {
+        let levels = if self.pending_marker.is_some() {
+            self.lists.len().saturating_sub(1)
+        } else {
+            self.lists.len()
+        };
+        let indent: usize = self.lists[..levels]
+            .iter()
+            .map(|list| if list.ordered { 3 } else { 2 })
+            .sum();
+        format!("{}{}", "> ".repeat(self.quote_depth), " ".repeat(indent))
+    }
+
+    fn open_inline(&mut self, tag: &str, opening: String, closing: String) {
+        self.inline.push(OpenInline {
+            tag: tag.to_string(),
+            closing,
+            pending_len: self.pending_inline.len(),
+        });
+        self.pending_inline.push_str(&opening);
+    }
+
+    fn close_inline(&mut self, tag: &str) {
+        let Some(position) = self.inline.iter().rposition(|open| open.tag == tag) else {
+            return;
+        };
+        // Close anything left open inside this element as well
+        while self.inline.len() > position {
+            let Some(open) = self.inline.pop() else {
+                break;
+            };
+            if self.pending_inline.len() > open.pending_len || open.closing.is_empty() {
+                self.pending_inline.truncate(open.pending_len);
+                continue;
+            }
+
+            // Emphasis markers can't be preceded by a space: "**bold **" -> "**bold** "
+            let had_space = self.current().ends_with(' ');
+            let trimmed_len = self.current().trim_end_matches(' ').len();
+            self.current_mut().truncate(trimmed_len);
+            self.push(&open.closing);
+            if had_space {
+                self.push(" ");
+            }
+        }
+    }
+
+    fn close_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            let cell = cell.trim().replace('|', "\\|");
+            if let Some(table) = &mut self.table {
+                table.row.push(cell);
+            }
+        }
+    }
+
+    fn close_row(&mut self) {
+        if let Some(table) = &mut self.table {
+            if !table.row.is_empty() {
+                let row = std::mem::take(&mut table.row);
+                table.rows.push(row);
+            }
+        }
+    }
+
+    fn finish_table(&mut self) {
+        self.close_cell();
+        self.close_row();
+        let Some(table) = self.table.take() else {
+            return;
+        };
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        // Markdown tables always have a header; the first row becomes it
+        let format_row = |row: &[String]| {
+            let cells: Vec<&str> = (0..columns)
+                .map(|i| row.get(i).map_or("", String::as_str))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        self.break_line(2);
+        for (index, row) in table.rows.iter().enumerate() {
+            self.write_markup(&format_row(row));
+            self.break_line(1);
+            if index == 0 {
+                self.write_markup(&format_row(&vec!["---".to_string(); columns]));
+                self.break_line(1);
+            }
+        }
+        self.break_line(2);
+    }
+
+    fn finish_pre(&mut self) {
+        let Some(code) = self.pre.take() else {
+            return;
+        };
+        let code = code.strip_prefix('\n').unwrap_or(&code).trim_end();
+
+        // Use a longer fence if the code itself contains one
+        let mut fence = "```".to_string();
+        while code.contains(fence.as_str()) {
+            fence.push('`');
+        }
+
+        self.break_line(2);
+        let language = std::mem::take(&mut self.pre_language);
+        self.write_markup(&format!("{}{}", fence, language));
+        for line in code.lines() {
+            self.break_line(1);
+            self.write_markup(line);
+        }
+        self.break_line(1);
+        self.write_markup(&fence);
+        self.break_line(2);
+    }
+
+    fn finish(mut self) -> String {
+        while let Some(open) = self.inline.last() {
+            let tag = open.tag.clone();
+            self.close_inline(&tag);
+        }
+        if self.pre.is_some() {
+            self.finish_pre();
+        }
+        if self.table.is_some() {
+            self.finish_table();
+        }
+        self.out.trim_end().to_string()
+    }
+
+    fn current(&self) -> &String {
+        self.cell.as_ref().unwrap_or(&self.out)
+    }
+
+    fn current_mut(&mut self) -> &mut String {
+        self.cell.as_mut().unwrap_or(&mut self.out)
+    }
+
+    fn push(&mut self, text: &str) {
+        self.current_mut().push_str(text);
+    }
+}
+
+fn code_language(attributes: &str) -> String {
+    attribute(attributes, "class")
+        .unwrap_or_default()
+        .split_whitespace()
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn style_contains(attributes: &str, declaration: &str) -> bool {
+    attribute(attributes, "style").is_some_and(|style| {
+        style
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase()
+            .contains(declaration)
+    })
+}
@@ -0,0 +1,500 @@
+use super::code::split_line_number;
+use super::Transform;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Split text into lines, remembering whether it ended with a newline. A "\r" before
+// the newline stays on its line, so CRLF text comes back out as CRLF.
+fn split_lines(text: &str) -> (Vec<&str>, bool) {
+    let trailing_newline = text.ends_with('\n');
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    if body.is_empty() && !trailing_newline {
+        return (Vec::new(), false);
+    }
+    (body.split('\n').collect(), trailing_newline)
+}
+
+fn join_lines<S: AsRef<str>>(lines: &[S], trailing_newline: bool) -> String {
+    let mut output = lines
+        .iter()
+        .map(|line| line.as_ref())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if trailing_newline && !lines.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    // "file2" before "file10", ignoring case
+    #[default]
+    Natural,
+    // By the number at the start of each line; lines without one go last
+    Numeric,
+    CaseInsensitive,
+    // Plain Unicode code point order
+    Lexical,
+}
+
+// Sort lines, dropping blank ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SortLines {
+    pub mode: SortMode,
+    pub descending: bool,
+}
+
+impl Transform for SortLines {
+    fn name(&self) -> &'static str {
+        "sort_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let (lines, trailing_newline) = split_lines(text);
+        let mut lines: Vec<&str> = lines.into_iter().filter(|l| !l.trim().is_empty()).collect();
+
+        // Ties fall back to plain order so the result doesn't depend on the input order
+        lines.sort_by(|a, b| {
+            let ordering = match self.mode {
+                SortMode::Natural => natural_cmp(a, b),
+                SortMode::Numeric => numeric_cmp(a, b),
+                SortMode::CaseInsensitive => a.to_lowercase().cmp(&b.to_lowercase()),
+                SortMode::Lexical => Ordering::Equal,
+            };
+            ordering.then_with(|| a.cmp(b))
+        });
+        if self.descending {
+            lines.reverse();
+        }
+
+        Ok(join_lines(&lines, trailing_newline))
+    }
+}
+
+// Compare runs of digits by value and everything else case-insensitively
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (a_chunks, b_chunks) = (chunks(a.trim()), chunks(b.trim()));
+    for (x, y) in a_chunks.iter().zip(&b_chunks) {
+        let x_digits = x.starts_with(|c: char| c.is_ascii_digit());
+        let y_digits = y.starts_with(|c: char| c.is_ascii_digit());
+        let ordering = match (x_digits, y_digits) {
+            (true, true) => {
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            _ => x.to_lowercase().cmp(&y.to_lowercase()),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a_chunks.len().cmp(&b_chunks.len())
+}
+
+// Split into alternating runs of ASCII digits and other characters
+fn chunks(text: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut in_digits = None;
+    for (index, c) in text.char_indices() {
+        let is_digit = c.is_ascii_digit();
+        if in_digits.is_some_and(|d| d != is_digit) {
+            chunks.push(&text[start..index]);
+            start = index;
+        }
+        in_digits = Some(is_digit);
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
+
+fn numeric_cmp(a: &str, b: &str) -> Ordering {
+    match (leading_number(a), leading_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+// The number a line starts with, allowing a sign, thousands separators and decimals
+fn leading_number(line: &str) -> Option<f64> {
+    let trimmed = line.trim_start();
+    let end = trimmed
+        .char_indices()
+        .find(|&(i, c)| {
+            !(c.is_ascii_digit() || c == '.' || c == ',' || (i == 0 && (c == '-' || c == '+')))
+        })
+        .map_or(trimmed.len(), |(i, _)| i);
+    let number: String = trimmed[..end].chars().filter(|&c| c != ',').collect();
+    number.trim_end_matches('.').parse().ok()
+}
+
+// Remove repeated lines, keeping the first occurrence. Blank lines are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DedupeLines {
+    pub case_sensitive: bool,
+    // Treat lines that differ only in leading or trailing whitespace as duplicates
+    pub ignore_surrounding_whitespace: bool,
+}
+
+impl Default for DedupeLines {
+    fn default() -> Self {
+        DedupeLines {
+            case_sensitive: true,
+            ignore_surrounding_whitespace: true,
+        }
+    }
+}
+
+impl Transform for DedupeLines {
+    fn name(&self) -> &'static str {
+        "dedupe_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let (lines, trailing_newline) = split_lines(text);
+        let mut seen = HashSet::new();
+        let kept: Vec<&str> = lines
+            .into_iter()
+            .filter(|line| {
+                if line.trim().is_empty() {
+                    return true;
+                }
+                let key = if self.ignore_surrounding_whitespace {
+                    line.trim()
+                } else {
+                    line
+                };
+                if self.case_sensitive {
+                    seen.insert(key.to_string())
+                } else {
+                    seen.insert(key.to_lowercase())
+                }
+            })
+            .collect();
+        Ok(join_lines(&kept, trailing_newline))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReverseLines;
+
+impl Transform for ReverseLines {
+    fn name(&self) -> &'static str {
+        "reverse_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let (mut lines, trailing_newline) = split_lines(text);
+        lines.reverse();
+        Ok(join_lines(&lines, trailing_newline))
+    }
+}
+
+// Shuffle lines into a random order; a fixed seed gives a repeatable order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShuffleLines {
+    pub seed: Option<u64>,
+}
+
+impl Transform for ShuffleLines {
+    fn name(&self) -> &'static str {
+        "shuffle_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let (mut lines, trailing_newline) = split_lines(text);
+        let mut state = self.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+
+        // Fisher-Yates with a splitmix64 generator
+        for i in (1..lines.len()).rev() {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            lines.swap(i, (z % (i as u64 + 1)) as usize);
+        }
+
+        Ok(join_lines(&lines, trailing_newline))
+    }
+}
+
+// Prefix lines with right-aligned numbers: " 9. first", "10. second"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumberLines {
+    pub start: u64,
+    pub separator: String,
+    // Leave blank lines unnumbered and don't count them
+    pub skip_blank: bool,
+}
+
+impl Default for NumberLines {
+    fn default() -> Self {
+        NumberLines {
+            start: 1,
+            separator: ". ".to_string(),
+            skip_blank: true,
+        }
+    }
+}
+
+impl Transform for NumberLines {
+    fn name(&self) -> &'static str {
+        "number_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let (lines, trailing_newline) = split_lines(text);
+        let numbered_count = lines
+            .iter()
+            .filter(|line| !self.skip_blank || !line.trim().is_empty())
+            .count() as u64;
+        let last = self.start + numbered_count.saturating_sub(1);
+        let width = last.to_string().len();
+
+        let mut next = self.start;
+        let numbered: Vec<String> = lines
+            .iter()
+            .map(|line| {
+                if self.skip_blank && line.trim().is_empty() {
+                    return line.to_string();
+                }
+                let prefixed = format!("{:>width$}{}{}", next, self.separator, line);
+                next += 1;
+                prefixed
+            })
+            .collect();
+
+        Ok(join_lines(&numbered, trailing_newline))
+    }
+}
+
+// Strip "1. ", "1) ", "1: ", "1 | " or "1<tab>" prefixes, but only when every
+// non-blank line has one so ordinary text starting with a number survives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoveLineNumbers;
+
+impl Transform for RemoveLineNumbers {
+    fn name(&self) -> &'static str {
+        "remove_line_numbers"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let (lines, trailing_newline) = split_lines(text);
+        let stripped: Option<Vec<&str>> = lines
+            .iter()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    Some(*line)
+                } else {
+                    strip_number_prefix(line)
+                }
+            })
+            .collect();
+
+        match stripped {
+            Some(stripped) if lines.iter().any(|l| !l.trim().is_empty()) => {
+                Ok(join_lines(&stripped, trailing_newline))
+            }
+            _ => Ok(text.to_string()),
+        }
+    }
+}
+
+fn strip_number_prefix(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(body) = trimmed[digits..].strip_prefix(['.', ')']) {
+            return body.strip_prefix([' ', '\t']);
+        }
+    }
+    split_line_number(line).map(|(_, body)| body)
+}
+
+// Join lines into one, separated by a delimiter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JoinLines {
+    pub delimiter: String,
+    pub skip_blank: bool,
+    pub trim: bool,
+}
+
+impl Default for JoinLines {
+    fn default() -> Self {
+        JoinLines {
+            delimiter: ", ".to_string(),
+            skip_blank: true,
+            trim: true,
+        }
+    }
+}
+
+impl Transform for JoinLines {
+    fn name(&self) -> &'static str {
+        "join_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let (lines, _) = split_lines(text);
+        let parts: Vec<&str> = lines
+            .into_iter()
+            .map(|line| if self.trim { line.trim() } else { line })
+            .filter(|line| !self.skip_blank || !line.trim().is_empty())
+            .collect();
+        Ok(parts.join(&self.delimiter))
+    }
+}
+
+// Split text on a delimiter, one part per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitOnDelimiter {
+    pub delimiter: String,
+    pub trim: bool,
+    pub skip_empty: bool,
+}
+
+impl Default for SplitOnDelimiter {
+    fn default() -> Self {
+        SplitOnDelimiter {
+            delimiter: ",".to_string(),
+            trim: true,
+            skip_empty: true,
+        }
+    }
+}
+
+impl Transform for SplitOnDelimiter {
+    fn name(&self) -> &'static str {
+        "split_on_delimiter"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        if self.delimiter.is_empty() {
+            return Err("Delimiter must not be empty".to_string());
+        }
+        let parts: Vec<&str> = text
+            .split(self.delimiter.as_str())
+            .map(|part| if self.trim { part.trim() } else { part })
+            .filter(|part| !self.skip_empty || !part.trim().is_empty())
+            .collect();
+        Ok(parts.join("\n"))
+    }
+}
+
+// Pad cells so a delimiter lines up on every line:
+//   "a = 1" / "long_name = 2"  ->  "a         = 1" / "long_name = 2"
+// Lines without the delimiter are left alone. Whitespace delimiters align with spaces.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlignColumns {
+    pub delimiter: String,
+}
+
+impl Default for AlignColumns {
+    fn default() -> Self {
+        AlignColumns {
+            delimiter: ",".to_string(),
+        }
+    }
+}
+
+impl Transform for AlignColumns {
+    fn name(&self) -> &'static str {
+        "align_columns"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let delimiter = self.delimiter.as_str();
+        if delimiter.is_empty() {
+            return Err("Delimiter must not be empty".to_string());
+        }
+        let whitespace = delimiter.trim().is_empty();
+        // "a,  b" keeps the delimiter on its cell; "a = b" centres it in the gap
+        let trailing = matches!(delimiter, "," | ";");
+
+        let (lines, trailing_newline) = split_lines(text);
+        let rows: Vec<Option<Vec<String>>> = lines
+            .iter()
+            .map(|line| {
+                let cells: Vec<&str> = if delimiter == " " {
+                    line.split_whitespace().collect()
+                } else {
+                    line.split(delimiter).map(str::trim).collect()
+                };
+                (cells.len() > 1).then(|| {
+                    let last = cells.len() - 1;
+                    cells
+                        .iter()
+                        .enumerate()
+                        .map(|(i, cell)| {
+                            if trailing && i < last {
+                                format!("{}{}", cell, delimiter)
+                            } else {
+                                cell.to_string()
+                            }
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+
+        let mut widths: Vec<usize> = Vec::new();
+        for cells in rows.iter().flatten() {
+            for (i, cell) in cells.iter().enumerate().take(cells.len() - 1) {
+                let width = cell.chars().count();
+                match widths.get_mut(i) {
+                    Some(max) => *max = (*max).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+
+        let gap = if whitespace || trailing {
+            if whitespace { "  " } else { " " }.to_string()
+        } else {
+            format!(" {} ", delimiter)
+        };
+        let aligned: Vec<String> = rows
+            .iter()
+            .zip(&lines)
+            .map(|(cells, line)| match cells {
+                Some(cells) => {
+                    let last = cells.len() - 1;
+                    let mut row = String::new();
+                    for (i, cell) in cells.iter().enumerate() {
+                        row.push_str(cell);
+                        if i < last {
+                            let padding = widths[i] - cell.chars().count();
+                            row.push_str(&" ".repeat(padding));
+                            row.push_str(&gap);
+                        }
+                    }
+                    row.trim_end().to_string()
+                }
+                None => line.to_string(),
+            })
+            .collect();
+
+        Ok(join_lines(&aligned, trailing_newline))
+    }
+}
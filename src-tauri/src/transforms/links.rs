@@ -0,0 +1,262 @@
+use super::Transform;
+use crate::storage::{load_json_file, save_json_file};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use url::Url;
+
+const URL_RULES_FILE: &str = "url_rules.json";
+
+// Query parameters added by analytics and ad platforms; a trailing "*" matches a prefix
+pub const DEFAULT_TRACKING_PARAMS: &[&str] = &[
+    "utm_*",
+    "fbclid",
+    "gclid",
+    "gclsrc",
+    "dclid",
+    "gbraid",
+    "wbraid",
+    "msclkid",
+    "yclid",
+    "twclid",
+    "ttclid",
+    "igshid",
+    "mc_cid",
+    "mc_eid",
+    "_ga",
+    "_gl",
+    "_hsenc",
+    "_hsmi",
+    "mkt_tok",
+    "oly_anon_id",
+    "oly_enc_id",
+    "vero_id",
+    "li_fat_id",
+    "ref_src",
+];
+
+// Redirect wrappers can be nested, e.g. Safe Links around a Google redirect
+const MAX_REDIRECT_DEPTH: usize = 5;
+
+// Remove tracking parameters from URLs and unwrap redirect wrappers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanUrls {
+    // Parameter names to remove, matched case-insensitively; "utm_*" matches by prefix
+    pub strip_params: Vec<String>,
+    // Replace Google, Outlook Safe Links, Slack and Facebook redirects with their target
+    pub unwrap_redirects: bool,
+}
+
+impl Default for CleanUrls {
+    fn default() -> Self {
+        CleanUrls {
+            strip_params: DEFAULT_TRACKING_PARAMS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            unwrap_redirects: true,
+        }
+    }
+}
+
+impl Transform for CleanUrls {
+    fn name(&self) -> &'static str {
+        "clean_urls"
+    }
+
+    // Clean every http(s) URL in the text, leaving everything around it untouched
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = find_url_start(rest) {
+            output.push_str(&rest[..start]);
+            let candidate = &rest[start..];
+            let end = candidate
+                .find(|c: char| c.is_whitespace() || c == '"' || c == '<' || c == '>')
+                .unwrap_or(candidate.len());
+            let (url, trailing) = split_trailing_punctuation(&candidate[..end]);
+
+            output.push_str(&self.clean_url(url).unwrap_or_else(|| url.to_string()));
+            output.push_str(trailing);
+            rest = &candidate[end..];
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+impl CleanUrls {
+    // Clean a single URL, or None if it can't be parsed. The URL is returned as written
+    // unless a redirect was unwrapped or a parameter removed.
+    pub fn clean_url(&self, url: &str) -> Option<String> {
+        let parsed = Url::parse(url).ok()?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return None;
+        }
+
+        let mut target = None;
+        if self.unwrap_redirects {
+            for _ in 0..MAX_REDIRECT_DEPTH {
+                match unwrap_redirect(target.as_ref().unwrap_or(&parsed)) {
+                    Some(unwrapped) => target = Some(unwrapped),
+                    None => break,
+                }
+            }
+        }
+
+        Some(match target {
+            Some(target) => self
+                .strip_tracking_params(target.as_str())
+                .unwrap_or_else(|| {
+                    target
+                        .as_str()
+                        .strip_suffix('#')
+                        .unwrap_or(target.as_str())
+                        .to_string()
+                }),
+            None => self
+                .strip_tracking_params(url)
+                .unwrap_or_else(|| url.to_string()),
+        })
+    }
+
+    // `url` without its tracking parameters, or None if it has none. The raw query is
+    // filtered so everything else keeps its original encoding.
+    fn strip_tracking_params(&self, url: &str) -> Option<String> {
+        let (before_fragment, fragment) = match url.find('#') {
+            Some(index) => url.split_at(index),
+            None => (url, ""),
+        };
+        let (base, query) = before_fragment.split_once('?')?;
+
+        let pairs: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+        let kept: Vec<&str> = pairs
+            .iter()
+            .copied()
+            .filter(|pair| {
+                let key = pair.split('=').next().unwrap_or("");
+                let key = url::form_urlencoded::parse(key.as_bytes())
+                    .next()
+                    .map(|(key, _)| key.into_owned())
+                    .unwrap_or_default();
+                !self.is_tracking_param(&key)
+            })
+            .collect();
+        if kept.len() == pairs.len() {
+            return None;
+        }
+
+        let mut cleaned = base.to_string();
+        if !kept.is_empty() {
+            cleaned.push('?');
+            cleaned.push_str(&kept.join("&"));
+        }
+        if fragment != "#" {
+            cleaned.push_str(fragment);
+        }
+        Some(cleaned)
+    }
+
+    fn is_tracking_param(&self, key: &str) -> bool {
+        let key = key.to_ascii_lowercase();
+        self.strip_params.iter().any(|rule| {
+            let rule = rule.trim().to_ascii_lowercase();
+            match rule.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == rule,
+            }
+        })
+    }
+}
+
+fn find_url_start(text: &str) -> Option<usize> {
+    let http = text.find("http://");
+    let https = text.find("https://");
+    match (http, https) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// Sentence punctuation after a URL isn't part of it; closing brackets only when unbalanced
+fn split_trailing_punctuation(url: &str) -> (&str, &str) {
+    let mut end = url.len();
+    while let Some(c) = url[..end].chars().last() {
+        let unbalanced_bracket = match c {
+            ')' => url[..end].matches('(').count() < url[..end].matches(')').count(),
+            ']' => url[..end].matches('[').count() < url[..end].matches(']').count(),
+            _ => false,
+        };
+        if matches!(c, '.' | ',' | ';' | ':' | '!' | '?' | '\'') || unbalanced_bracket {
+            end -= c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    (&url[..end], &url[end..])
+}
+
+// The wrapped URL if `url` is a known redirector
+fn unwrap_redirect(url: &Url) -> Option<Url> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let path = url.path();
+
+    // Query parameters that may hold the target, in order of preference
+    let params: &[&str] =
+        if (host.starts_with("www.google.") || host.starts_with("google.")) && path == "/url" {
+            &["q", "url"]
+        } else if host.ends_with("safelinks.protection.outlook.com")
+            || (host == "slack-redir.net" && path == "/link")
+        {
+            &["url"]
+        } else if (host == "l.facebook.com" || host == "lm.facebook.com") && path == "/l.php" {
+            &["u"]
+        } else {
+            return None;
+        };
+
+    params.iter().find_map(|param| {
+        let target = url
+            .query_pairs()
+            .find(|(key, value)| key == param && !value.is_empty())
+            .map(|(_, value)| value.into_owned())?;
+        let target = Url::parse(&target).ok()?;
+        matches!(target.scheme(), "http" | "https").then_some(target)
+    })
+}
+
+// Editable URL rules used by the clipboard monitor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UrlRulesStore {
+    // Clean URLs in the clipboard monitor as soon as they are copied (off by default)
+    pub auto_clean: bool,
+    pub rules: CleanUrls,
+}
+
+impl UrlRulesStore {
+    // The cleaned URL when auto-clean is on, `text` is a single URL and cleaning changes it
+    pub fn clean_copied_text(&self, text: &str) -> Option<String> {
+        let trimmed = text.trim();
+        if !self.auto_clean || trimmed.contains(char::is_whitespace) {
+            return None;
+        }
+        self.rules
+            .clean_url(trimmed)
+            .filter(|cleaned| cleaned != trimmed)
+    }
+}
+
+pub type UrlRulesState = Arc<RwLock<UrlRulesStore>>;
+
+pub fn load_url_rules_from_file() -> io::Result<UrlRulesStore> {
+    load_json_file(URL_RULES_FILE)
+}
+
+pub fn save_url_rules_to_file(store: &UrlRulesStore) -> io::Result<()> {
+    save_json_file(URL_RULES_FILE, store)
+}
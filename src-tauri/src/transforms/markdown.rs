@@ -0,0 +1,356 @@
+use super::Transform;
+use serde::{Deserialize, Serialize};
+
+// Bullet characters that rendered pages and word processors put on the clipboard
+const BULLET_MARKERS: &[char] = &['-', '*', '+', '•', '◦', '▪'];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulletMarker {
+    #[default]
+    Dash,
+    Asterisk,
+    Plus,
+    // Leave bullet markers as they are
+    Keep,
+}
+
+impl BulletMarker {
+    fn as_char(self) -> Option<char> {
+        match self {
+            BulletMarker::Dash => Some('-'),
+            BulletMarker::Asterisk => Some('*'),
+            BulletMarker::Plus => Some('+'),
+            BulletMarker::Keep => None,
+        }
+    }
+}
+
+// Tidy Markdown structure while leaving code blocks, tables and quotes intact
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkdownCleanup {
+    pub bullet_marker: BulletMarker,
+    // Number ordered list items consecutively, keeping each list's start number
+    pub renumber_lists: bool,
+    // Put a space after the hashes of ATX headings and a blank line around them
+    pub heading_spacing: bool,
+}
+
+impl Default for MarkdownCleanup {
+    fn default() -> Self {
+        MarkdownCleanup {
+            bullet_marker: BulletMarker::Dash,
+            renumber_lists: true,
+            heading_spacing: true,
+        }
+    }
+}
+
+impl Transform for MarkdownCleanup {
+    fn name(&self) -> &'static str {
+        "markdown_cleanup"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let mut output: Vec<String> = Vec::new();
+        let mut fence: Option<(char, usize)> = None;
+        let mut in_indented_code = false;
+        // (indent, delimiter, next number) for each open ordered list, innermost last
+        let mut counters: Vec<(usize, char, u64)> = Vec::new();
+        let mut in_list = false;
+        let mut previous_blank = true;
+        let mut after_heading = false;
+
+        for line in text.split('\n') {
+            let trimmed = line.trim();
+            let indent = indentation_width(line);
+
+            // Fenced code blocks are copied verbatim, including the fences
+            if let Some((fence_char, fence_len)) = fence {
+                if is_closing_fence(trimmed, fence_char, fence_len) {
+                    fence = None;
+                }
+                output.push(line.to_string());
+                previous_blank = false;
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                // Collapse runs of blank lines outside of code
+                if previous_blank && !in_indented_code {
+                    continue;
+                }
+                output.push(String::new());
+                previous_blank = true;
+                after_heading = false;
+                continue;
+            }
+
+            if after_heading && self.heading_spacing {
+                output.push(String::new());
+                previous_blank = true;
+            }
+            after_heading = false;
+
+            if let Some(opening) = opening_fence(trimmed) {
+                fence = Some(opening);
+                in_indented_code = false;
+                output.push(line.to_string());
+                previous_blank = false;
+                continue;
+            }
+
+            // Indented code blocks start after a blank line outside of lists
+            if indent >= 4 && (in_indented_code || (previous_blank && !in_list)) {
+                in_indented_code = true;
+                output.push(line.to_string());
+                previous_blank = false;
+                continue;
+            }
+            in_indented_code = false;
+
+            // Tables, block quotes and thematic breaks are left untouched
+            if trimmed.starts_with('|') || trimmed.starts_with('>') || is_thematic_break(trimmed) {
+                if indent == 0 && previous_blank {
+                    in_list = false;
+                    counters.clear();
+                }
+                output.push(line.to_string());
+                previous_blank = false;
+                continue;
+            }
+
+            if let Some(heading) = self.heading(line) {
+                counters.clear();
+                in_list = false;
+                if self.heading_spacing && !previous_blank {
+                    output.push(String::new());
+                }
+                output.push(heading);
+                previous_blank = false;
+                after_heading = true;
+                continue;
+            }
+
+            if let Some(item) = parse_list_item(line) {
+                // Close nested lists, and the list at this level if the marker type changed
+                let delimiter = match item.marker {
+                    ListMarker::Ordered(_, delimiter) => Some(delimiter),
+                    ListMarker::Bullet(_) => None,
+                };
+                counters.retain(|&(counter_indent, counter_delimiter, _)| {
+                    counter_indent < item.indent
+                        || (counter_indent == item.indent && Some(counter_delimiter) == delimiter)
+                });
+                let rewritten = match item.marker {
+                    ListMarker::Bullet(marker) => self.bullet_line(line, &item, marker),
+                    ListMarker::Ordered(number, delimiter) => {
+                        let number = if self.renumber_lists {
+                            match counters.last_mut() {
+                                Some((counter_indent, _, next))
+                                    if *counter_indent == item.indent =>
+                                {
+                                    let current = *next;
+                                    *next += 1;
+                                    current
+                                }
+                                _ => {
+                                    counters.push((item.indent, delimiter, number + 1));
+                                    number
+                                }
+                            }
+                        } else {
+                            number
+                        };
+                        format!(
+                            "{}{}{} {}",
+                            &line[..item.marker_start],
+                            number,
+                            delimiter,
+                            &line[item.content_start..]
+                        )
+                    }
+                };
+                output.push(rewritten.trim_end().to_string());
+                in_list = true;
+                previous_blank = false;
+                continue;
+            }
+
+            // A paragraph back at the margin after a blank line ends any open list
+            if indent == 0 && previous_blank {
+                in_list = false;
+                counters.clear();
+            }
+            output.push(line.to_string());
+            previous_blank = false;
+        }
+
+        Ok(output.join("\n"))
+    }
+}
+
+impl MarkdownCleanup {
+    fn heading(&self, line: &str) -> Option<String> {
+        let indent = indentation_width(line);
+        if indent > 3 {
+            return None;
+        }
+        let body = line.trim_start();
+        let level = body.chars().take_while(|&c| c == '#').count();
+        if level == 0 || level > 6 {
+            return None;
+        }
+
+        let rest = &body[level..];
+        let title = rest.trim();
+        if title.is_empty() {
+            return Some(line.trim_end().to_string());
+        }
+
+        if !rest.starts_with([' ', '\t']) {
+            // "#Heading" is only fixed when it can't be a hashtag or a directive like #include
+            let first = title.chars().next()?;
+            let fixable = if level == 1 {
+                first.is_uppercase()
+            } else {
+                first.is_alphanumeric()
+            };
+            if !self.heading_spacing || !fixable {
+                return None;
+            }
+        }
+
+        if !self.heading_spacing {
+            return Some(line.trim_end().to_string());
+        }
+        Some(format!(
+            "{}{} {}",
+            &line[..line.len() - body.len()],
+            &body[..level],
+            title
+        ))
+    }
+
+    fn bullet_line(&self, line: &str, item: &ListItem, marker: char) -> String {
+        let marker = match self.bullet_marker.as_char() {
+            // An empty item could turn into a setext underline, so leave it alone
+            Some(replacement) if item.content_start < line.len() => replacement,
+            _ => marker,
+        };
+        let rewritten = format!(
+            "{}{} {}",
+            &line[..item.marker_start],
+            marker,
+            &line[item.content_start..]
+        );
+        if is_thematic_break(rewritten.trim()) {
+            return line.to_string();
+        }
+        rewritten
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListMarker {
+    Bullet(char),
+    Ordered(u64, char),
+}
+
+#[derive(Debug)]
+struct ListItem {
+    indent: usize,
+    marker: ListMarker,
+    // Byte offset of the marker
+    marker_start: usize,
+    // Byte offset of the item text after the marker and its spacing
+    content_start: usize,
+}
+
+fn parse_list_item(line: &str) -> Option<ListItem> {
+    let body = line.trim_start();
+    let marker_start = line.len() - body.len();
+    let indent = indentation_width(line);
+
+    let first = body.chars().next()?;
+    let (marker, marker_end) = if BULLET_MARKERS.contains(&first) {
+        (ListMarker::Bullet(first), marker_start + first.len_utf8())
+    } else {
+        let digits = body.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 || digits > 9 {
+            return None;
+        }
+        let delimiter = body[digits..].chars().next()?;
+        if delimiter != '.' && delimiter != ')' {
+            return None;
+        }
+        let number = body[..digits].parse().ok()?;
+        (
+            ListMarker::Ordered(number, delimiter),
+            marker_start + digits + 1,
+        )
+    };
+
+    let rest = &line[marker_end..];
+    if rest.is_empty() {
+        return Some(ListItem {
+            indent,
+            marker,
+            marker_start,
+            content_start: line.len(),
+        });
+    }
+
+    let spacing = rest.chars().take_while(|&c| c == ' ').count();
+    if spacing == 0 {
+        return None;
+    }
+    // Five or more spaces start an indented code block inside the item
+    let content_start = if spacing <= 4 {
+        marker_end + spacing
+    } else {
+        marker_end + 1
+    };
+
+    Some(ListItem {
+        indent,
+        marker,
+        marker_start,
+        content_start,
+    })
+}
+
+fn indentation_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break,
+        }
+    }
+    width
+}
+
+fn opening_fence(trimmed: &str) -> Option<(char, usize)> {
+    let fence_char = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let fence_len = trimmed.chars().take_while(|&c| c == fence_char).count();
+    (fence_len >= 3).then_some((fence_char, fence_len))
+}
+
+fn is_closing_fence(trimmed: &str, fence_char: char, fence_len: usize) -> bool {
+    let run = trimmed.chars().take_while(|&c| c == fence_char).count();
+    run >= fence_len && trimmed.chars().skip(run).all(char::is_whitespace)
+}
+
+fn is_thematic_break(trimmed: &str) -> bool {
+    let Some(first) = trimmed.chars().next() else {
+        return false;
+    };
+    if !matches!(first, '-' | '*' | '_') {
+        return false;
+    }
+    trimmed.chars().all(|c| c == first || c == ' ' || c == '\t')
+        && trimmed.chars().filter(|&c| c == first).count() >= 3
+}
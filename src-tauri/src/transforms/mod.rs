@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+
+pub mod code;
+pub mod data;
+pub mod encoding;
+pub mod html;
+pub mod lines;
+pub mod links;
+pub mod markdown;
+pub mod presets;
+pub mod reflow;
+pub mod replace;
+pub mod scripts;
+pub mod sql;
+pub mod tables;
+pub mod unicode;
+pub mod whitespace;
+pub mod xml;
+pub mod yaml;
+
+use code::CodeCleanup;
+use data::FormatData;
+use encoding::{ConvertCase, DecodeJwt, DecodeText, EncodeText};
+use html::HtmlToMarkdown;
+use lines::{
+    AlignColumns, DedupeLines, JoinLines, NumberLines, RemoveLineNumbers, ReverseLines,
+    ShuffleLines, SortLines, SplitOnDelimiter,
+};
+use links::CleanUrls;
+use markdown::MarkdownCleanup;
+use reflow::Reflow;
+use scripts::RunScript;
+use tables::ConvertTable;
+use unicode::UnicodeScrub;
+use whitespace::{
+    CollapseSpaces, LimitBlankLines, NormalizeLineEndings, Tabs, TrimLines, TrimText,
+};
+
+// A single text transformation that can be chained into a cleanup pipeline.
+// tests/cleanup_properties.rs checks that every built-in preset is idempotent and
+// that the whitespace presets never drop or reorder non-whitespace characters.
+// Line, encoding and script transforms may reorder or rewrite content by design.
+pub trait Transform {
+    // Stable identifier used in presets, logs and error messages
+    fn name(&self) -> &'static str;
+
+    fn apply(&self, text: &str) -> Result<String, String>;
+}
+
+// Serializable description of a pipeline step and its parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TransformStep {
+    NormalizeLineEndings(NormalizeLineEndings),
+    Tabs(Tabs),
+    CollapseSpaces(CollapseSpaces),
+    TrimLines(TrimLines),
+    LimitBlankLines(LimitBlankLines),
+    TrimText(TrimText),
+    Reflow(Reflow),
+    UnicodeScrub(UnicodeScrub),
+    CodeCleanup(CodeCleanup),
+    HtmlToMarkdown(HtmlToMarkdown),
+    MarkdownCleanup(MarkdownCleanup),
+    CleanUrls(CleanUrls),
+    FormatData(FormatData),
+    EncodeText(EncodeText),
+    DecodeText(DecodeText),
+    DecodeJwt(DecodeJwt),
+    ConvertCase(ConvertCase),
+    SortLines(SortLines),
+    DedupeLines(DedupeLines),
+    ReverseLines(ReverseLines),
+    ShuffleLines(ShuffleLines),
+    NumberLines(NumberLines),
+    RemoveLineNumbers(RemoveLineNumbers),
+    JoinLines(JoinLines),
+    SplitOnDelimiter(SplitOnDelimiter),
+    AlignColumns(AlignColumns),
+    ConvertTable(ConvertTable),
+    RunScript(RunScript),
+}
+
+impl TransformStep {
+    pub fn as_transform(&self) -> &dyn Transform {
+        match self {
+            TransformStep::NormalizeLineEndings(t) => t,
+            TransformStep::Tabs(t) => t,
+            TransformStep::CollapseSpaces(t) => t,
+            TransformStep::TrimLines(t) => t,
+            TransformStep::LimitBlankLines(t) => t,
+            TransformStep::TrimText(t) => t,
+            TransformStep::Reflow(t) => t,
+            TransformStep::UnicodeScrub(t) => t,
+            TransformStep::CodeCleanup(t) => t,
+            TransformStep::HtmlToMarkdown(t) => t,
+            TransformStep::MarkdownCleanup(t) => t,
+            TransformStep::CleanUrls(t) => t,
+            TransformStep::FormatData(t) => t,
+            TransformStep::EncodeText(t) => t,
+            TransformStep::DecodeText(t) => t,
+            TransformStep::DecodeJwt(t) => t,
+            TransformStep::ConvertCase(t) => t,
+            TransformStep::SortLines(t) => t,
+            TransformStep::DedupeLines(t) => t,
+            TransformStep::ReverseLines(t) => t,
+            TransformStep::ShuffleLines(t) => t,
+            TransformStep::NumberLines(t) => t,
+            TransformStep::RemoveLineNumbers(t) => t,
+            TransformStep::JoinLines(t) => t,
+            TransformStep::SplitOnDelimiter(t) => t,
+            TransformStep::AlignColumns(t) => t,
+            TransformStep::ConvertTable(t) => t,
+            TransformStep::RunScript(t) => t,
+        }
+    }
+
+    // Every available step with its default parameters, used to populate the pipeline editor
+    pub fn catalog() -> Vec<TransformStep> {
+        vec![
+            TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+            TransformStep::Tabs(Tabs::default()),
+            TransformStep::CollapseSpaces(CollapseSpaces::default()),
+            TransformStep::TrimLines(TrimLines::default()),
+            TransformStep::LimitBlankLines(LimitBlankLines::default()),
+            TransformStep::TrimText(TrimText::default()),
+            TransformStep::Reflow(Reflow::default()),
+            TransformStep::UnicodeScrub(UnicodeScrub::default()),
+            TransformStep::CodeCleanup(CodeCleanup::default()),
+            TransformStep::HtmlToMarkdown(HtmlToMarkdown::default()),
+            TransformStep::MarkdownCleanup(MarkdownCleanup::default()),
+            TransformStep::CleanUrls(CleanUrls::default()),
+            TransformStep::FormatData(FormatData::default()),
+            TransformStep::EncodeText(EncodeText::default()),
+            TransformStep::DecodeText(DecodeText::default()),
+            TransformStep::DecodeJwt(DecodeJwt),
+            TransformStep::ConvertCase(ConvertCase::default()),
+            TransformStep::SortLines(SortLines::default()),
+            TransformStep::DedupeLines(DedupeLines::default()),
+            TransformStep::ReverseLines(ReverseLines),
+            TransformStep::ShuffleLines(ShuffleLines::default()),
+            TransformStep::NumberLines(NumberLines::default()),
+            TransformStep::RemoveLineNumbers(RemoveLineNumbers),
+            TransformStep::JoinLines(JoinLines::default()),
+            TransformStep::SplitOnDelimiter(SplitOnDelimiter::default()),
+            TransformStep::AlignColumns(AlignColumns::default()),
+            TransformStep::ConvertTable(ConvertTable::default()),
+            TransformStep::RunScript(RunScript::default()),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStep {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub step: TransformStep,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl PipelineStep {
+    pub fn new(step: TransformStep) -> Self {
+        PipelineStep {
+            enabled: true,
+            step,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub steps: Vec<PipelineStep>,
+}
+
+impl Pipeline {
+    pub fn new(steps: Vec<TransformStep>) -> Self {
+        Pipeline {
+            steps: steps.into_iter().map(PipelineStep::new).collect(),
+        }
+    }
+
+    // Whether the pipeline would convert HTML, so callers can feed it the HTML clipboard flavor
+    pub fn accepts_html(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|s| s.enabled && matches!(s.step, TransformStep::HtmlToMarkdown(_)))
+    }
+
+    // Clean URLs with the user's saved URL rules rather than the parameters stored with
+    // each step, so the rules are edited in one place
+    pub fn with_url_rules(mut self, rules: &CleanUrls) -> Self {
+        for pipeline_step in &mut self.steps {
+            if let TransformStep::CleanUrls(step_rules) = &mut pipeline_step.step {
+                *step_rules = rules.clone();
+            }
+        }
+        self
+    }
+
+    // Run every enabled step in order, stopping at the first failing step
+    pub fn run(&self, text: &str) -> Result<String, String> {
+        let mut current = text.to_string();
+
+        for pipeline_step in self.steps.iter().filter(|s| s.enabled) {
+            let transform = pipeline_step.step.as_transform();
+            current = transform
+                .apply(&current)
+                .map_err(|e| format!("Step '{}' failed: {}", transform.name(), e))?;
+        }
+
+        Ok(current)
+    }
+}
+
+// Clean text with the store's default preset, or its code preset when the text looks like code
+pub fn cleanup_text(store: &presets::CleanupPresetStore, text: &str) -> String {
+    store
+        .pipeline_for_text(None, text)
+        .and_then(|pipeline| pipeline.run(text))
+        .unwrap_or_else(|_| text.to_string())
+}
+
+// Clean text for pasting into Markdown tools, converting HTML to Markdown first
+pub fn cleanup_markdown(store: &presets::CleanupPresetStore, text: &str) -> String {
+    store
+        .pipeline_for(Some(presets::MARKDOWN_PRESET))
+        .and_then(|pipeline| pipeline.run(text))
+        .unwrap_or_else(|_| text.to_string())
+}
@@ -0,0 +1,391 @@
+use super::code::{looks_like_code, CodeCleanup};
+use super::data::{DataAction, FormatData};
+use super::html::HtmlToMarkdown;
+use super::links::CleanUrls;
+use super::markdown::MarkdownCleanup;
+use super::reflow::Reflow;
+use super::unicode::UnicodeScrub;
+use super::whitespace::{
+    CollapseSpaces, LimitBlankLines, NormalizeLineEndings, TabMode, Tabs, TrimLines, TrimText,
+};
+use super::{Pipeline, PipelineStep, TransformStep};
+use crate::storage::{load_json_file, save_json_file};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const PRESETS_FILE: &str = "cleanup_presets.json";
+
+pub const DEFAULT_PRESET: &str = "default";
+pub const PRESERVE_INDENTATION_PRESET: &str = "preserve_indentation";
+pub const REFLOW_PRESET: &str = "reflow";
+pub const UNICODE_SAFE_PRESET: &str = "unicode_safe";
+pub const CODE_PRESET: &str = "code";
+pub const MARKDOWN_PRESET: &str = "markdown";
+pub const URL_PRESET: &str = "url";
+pub const PRETTY_DATA_PRESET: &str = "pretty_data";
+pub const MINIFY_DATA_PRESET: &str = "minify_data";
+
+// A named, user-selectable cleanup pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupPreset {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub pipeline: Pipeline,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+// Presets shipped with the app; they cannot be overwritten or deleted
+pub fn builtin_presets() -> Vec<CleanupPreset> {
+    vec![
+        CleanupPreset {
+            name: DEFAULT_PRESET.to_string(),
+            description:
+                "Standard Clipify cleanup: collapse spaces, trim lines and limit blank lines"
+                    .to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::Tabs(Tabs::default()),
+                TransformStep::CollapseSpaces(CollapseSpaces::default()),
+                TransformStep::TrimLines(TrimLines::default()),
+                TransformStep::LimitBlankLines(LimitBlankLines::default()),
+                TransformStep::TrimText(TrimText::default()),
+            ]),
+            builtin: true,
+        },
+        CleanupPreset {
+            name: PRESERVE_INDENTATION_PRESET.to_string(),
+            description:
+                "Fix line endings and trailing whitespace while keeping tabs and indentation"
+                    .to_string(),
+            pipeline: Pipeline {
+                steps: vec![
+                    PipelineStep::new(TransformStep::NormalizeLineEndings(NormalizeLineEndings)),
+                    PipelineStep {
+                        enabled: false,
+                        step: TransformStep::Tabs(Tabs {
+                            mode: TabMode::Expand,
+                            tab_width: 4,
+                        }),
+                    },
+                    PipelineStep {
+                        enabled: false,
+                        step: TransformStep::CollapseSpaces(CollapseSpaces {
+                            preserve_indentation: true,
+                        }),
+                    },
+                    PipelineStep::new(TransformStep::TrimLines(TrimLines {
+                        leading: false,
+                        trailing: true,
+                    })),
+                    PipelineStep::new(TransformStep::LimitBlankLines(LimitBlankLines::default())),
+                    PipelineStep::new(TransformStep::TrimText(TrimText {
+                        preserve_indentation: true,
+                    })),
+                ],
+            },
+            builtin: true,
+        },
+        CleanupPreset {
+            name: REFLOW_PRESET.to_string(),
+            description:
+                "Join hard-wrapped PDF and e-mail text into paragraphs, keeping lists and headings"
+                    .to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::Tabs(Tabs::default()),
+                TransformStep::Reflow(Reflow::default()),
+                TransformStep::CollapseSpaces(CollapseSpaces {
+                    preserve_indentation: true,
+                }),
+                TransformStep::TrimLines(TrimLines {
+                    leading: false,
+                    trailing: true,
+                }),
+                TransformStep::LimitBlankLines(LimitBlankLines::default()),
+                TransformStep::TrimText(TrimText {
+                    preserve_indentation: true,
+                }),
+            ]),
+            builtin: true,
+        },
+        CleanupPreset {
+            name: UNICODE_SAFE_PRESET.to_string(),
+            description: "Standard cleanup plus NFC normalization and removal of invisible and bidi control characters"
+                .to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::UnicodeScrub(UnicodeScrub::default()),
+                TransformStep::Tabs(Tabs::default()),
+                TransformStep::CollapseSpaces(CollapseSpaces::default()),
+                TransformStep::TrimLines(TrimLines::default()),
+                TransformStep::LimitBlankLines(LimitBlankLines::default()),
+                TransformStep::TrimText(TrimText::default()),
+            ]),
+            builtin: true,
+        },
+        CleanupPreset {
+            name: CODE_PRESET.to_string(),
+            description: "Normalize indentation, dedent and strip line numbers and prompts from pasted code"
+                .to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::CodeCleanup(CodeCleanup::default()),
+                TransformStep::LimitBlankLines(LimitBlankLines { max_blank_lines: 2 }),
+                TransformStep::TrimText(TrimText {
+                    preserve_indentation: true,
+                }),
+            ]),
+            builtin: true,
+        },
+        CleanupPreset {
+            name: MARKDOWN_PRESET.to_string(),
+            description: "Convert HTML to Markdown and tidy bullets, numbering and headings, leaving code blocks and tables intact"
+                .to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::HtmlToMarkdown(HtmlToMarkdown::default()),
+                TransformStep::MarkdownCleanup(MarkdownCleanup::default()),
+                TransformStep::TrimText(TrimText {
+                    preserve_indentation: true,
+                }),
+            ]),
+            builtin: true,
+        },
+        CleanupPreset {
+            name: URL_PRESET.to_string(),
+            description: "Strip tracking parameters from links and unwrap Google, Outlook and Slack redirects"
+                .to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::CleanUrls(CleanUrls::default()),
+                TransformStep::TrimText(TrimText::default()),
+            ]),
+            builtin: true,
+        },
+        CleanupPreset {
+            name: PRETTY_DATA_PRESET.to_string(),
+            description: "Pretty-print JSON, XML and SQL; other text is left as it is".to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::FormatData(FormatData::default()),
+            ]),
+            builtin: true,
+        },
+        CleanupPreset {
+            name: MINIFY_DATA_PRESET.to_string(),
+            description: "Minify JSON, XML and SQL onto a single line; other text is left as it is"
+                .to_string(),
+            pipeline: Pipeline::new(vec![
+                TransformStep::NormalizeLineEndings(NormalizeLineEndings),
+                TransformStep::FormatData(FormatData {
+                    action: DataAction::Minify,
+                    ..FormatData::default()
+                }),
+            ]),
+            builtin: true,
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanupPresetStore {
+    // User-defined presets only; builtins are merged in at lookup time
+    pub presets: Vec<CleanupPreset>,
+    pub default_preset: String,
+    // Switch to the code preset when no preset is requested and the text looks like code
+    pub auto_detect_code: bool,
+}
+
+impl Default for CleanupPresetStore {
+    fn default() -> Self {
+        CleanupPresetStore {
+            presets: Vec::new(),
+            default_preset: DEFAULT_PRESET.to_string(),
+            auto_detect_code: true,
+        }
+    }
+}
+
+impl CleanupPresetStore {
+    pub fn all(&self) -> Vec<CleanupPreset> {
+        let mut presets = builtin_presets();
+        presets.extend(self.presets.iter().cloned());
+        presets
+    }
+
+    pub fn get(&self, name: &str) -> Option<CleanupPreset> {
+        self.all().into_iter().find(|p| p.name == name)
+    }
+
+    // Resolve the pipeline for an explicit preset, or the user's default when none is given
+    pub fn pipeline_for(&self, name: Option<&str>) -> Result<Pipeline, String> {
+        let name = name.unwrap_or(&self.default_preset);
+        self.get(name)
+            .map(|preset| preset.pipeline)
+            .ok_or_else(|| format!("Cleanup preset '{}' not found", name))
+    }
+
+    // Like `pipeline_for`, but picks the code preset for code when no preset is requested
+    pub fn pipeline_for_text(&self, name: Option<&str>, text: &str) -> Result<Pipeline, String> {
+        if name.is_none() && self.auto_detect_code && looks_like_code(text) {
+            return self.pipeline_for(Some(CODE_PRESET));
+        }
+        self.pipeline_for(name)
+    }
+
+    pub fn upsert(&mut self, mut preset: CleanupPreset) -> Result<(), String> {
+        if preset.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if builtin_presets().iter().any(|p| p.name == preset.name) {
+            return Err(format!(
+                "Cannot overwrite built-in preset '{}'",
+                preset.name
+            ));
+        }
+
+        preset.builtin = false;
+        match self.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<bool, String> {
+        if builtin_presets().iter().any(|p| p.name == name) {
+            return Err(format!("Cannot delete built-in preset '{}'", name));
+        }
+
+        let original_len = self.presets.len();
+        self.presets.retain(|p| p.name != name);
+
+        // Fall back to the standard preset if the default was removed
+        if self.default_preset == name {
+            self.default_preset = DEFAULT_PRESET.to_string();
+        }
+
+        Ok(self.presets.len() != original_len)
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<(), String> {
+        if self.get(name).is_none() {
+            return Err(format!("Cleanup preset '{}' not found", name));
+        }
+        self.default_preset = name.to_string();
+        Ok(())
+    }
+}
+
+pub type CleanupPresetsState = Arc<RwLock<CleanupPresetStore>>;
+
+pub fn load_presets_from_file() -> io::Result<CleanupPresetStore> {
+    load_json_file(PRESETS_FILE)
+}
+
+pub fn save_presets_to_file(store: &CleanupPresetStore) -> io::Result<()> {
+    save_json_file(PRESETS_FILE, store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_preset(name: &str, pipeline: Pipeline) -> CleanupPreset {
+        CleanupPreset {
+            name: name.to_string(),
+            description: String::new(),
+            pipeline,
+            builtin: false,
+        }
+    }
+
+    #[test]
+    fn run_skips_disabled_steps() {
+        let pipeline = Pipeline {
+            steps: vec![
+                PipelineStep::new(TransformStep::TrimText(TrimText::default())),
+                PipelineStep {
+                    enabled: false,
+                    step: TransformStep::CollapseSpaces(CollapseSpaces::default()),
+                },
+            ],
+        };
+
+        assert_eq!(pipeline.run("  a    b  ").unwrap(), "a    b");
+    }
+
+    #[test]
+    fn upsert_adds_and_replaces_user_presets() {
+        let mut store = CleanupPresetStore::default();
+        let mut preset = user_preset("trim", Pipeline::new(vec![]));
+        preset.builtin = true;
+        store.upsert(preset).unwrap();
+        store
+            .upsert(user_preset(
+                "trim",
+                Pipeline::new(vec![TransformStep::TrimText(TrimText::default())]),
+            ))
+            .unwrap();
+
+        assert_eq!(store.presets.len(), 1);
+        let saved = store.get("trim").unwrap();
+        assert!(!saved.builtin);
+        assert_eq!(saved.pipeline.run("  x  ").unwrap(), "x");
+    }
+
+    #[test]
+    fn upsert_refuses_empty_names_and_builtins() {
+        let mut store = CleanupPresetStore::default();
+
+        assert!(store
+            .upsert(user_preset("  ", Pipeline::default()))
+            .is_err());
+        assert!(store
+            .upsert(user_preset(DEFAULT_PRESET, Pipeline::default()))
+            .is_err());
+        assert!(store.presets.is_empty());
+        assert_eq!(
+            store.get(DEFAULT_PRESET),
+            builtin_presets()
+                .into_iter()
+                .find(|p| p.name == DEFAULT_PRESET)
+        );
+    }
+
+    #[test]
+    fn remove_refuses_builtins_and_resets_a_removed_default() {
+        let mut store = CleanupPresetStore::default();
+        store
+            .upsert(user_preset("mine", Pipeline::default()))
+            .unwrap();
+        store.set_default("mine").unwrap();
+
+        assert!(store.remove(CODE_PRESET).is_err());
+        assert!(store.get(CODE_PRESET).is_some());
+
+        assert_eq!(store.remove("mine"), Ok(true));
+        assert_eq!(store.remove("mine"), Ok(false));
+        assert_eq!(store.default_preset, DEFAULT_PRESET);
+    }
+
+    #[test]
+    fn set_default_accepts_builtins_and_rejects_unknown_presets() {
+        let mut store = CleanupPresetStore::default();
+
+        store.set_default(REFLOW_PRESET).unwrap();
+        assert_eq!(store.default_preset, REFLOW_PRESET);
+
+        assert!(store.set_default("missing").is_err());
+        assert_eq!(store.default_preset, REFLOW_PRESET);
+        assert_eq!(
+            store.pipeline_for(None),
+            store.pipeline_for(Some(REFLOW_PRESET))
+        );
+    }
+}
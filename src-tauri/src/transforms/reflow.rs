@@ -0,0 +1,250 @@
+use super::Transform;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+// Paragraphs whose longest line is shorter than this are treated as intentionally
+// short lines (addresses, signatures, chat messages) and never joined.
+const MIN_WRAP_WIDTH: usize = 30;
+
+// A line shorter than this fraction of the paragraph's wrap width ends the line on purpose
+const SHORT_LINE_RATIO: f32 = 0.6;
+
+// Left halves that form compounds ("self-contained", "well-known") and keep their hyphen
+const HYPHEN_PREFIXES: &[&str] = &[
+    "all", "cross", "ex", "full", "half", "high", "long", "low", "part", "self", "short", "well",
+];
+
+// Right halves that are word endings rather than words ("long-ing", "infor-mation")
+const SUFFIX_FRAGMENTS: &[&str] = &[
+    "able", "al", "ance", "ed", "ence", "er", "ers", "est", "ible", "ing", "ings", "ity", "ive",
+    "ly", "ment", "ments", "ness", "ous", "sion", "tion", "tions",
+];
+
+// Join hard-wrapped lines (PDF exports, quoted e-mails) back into paragraphs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Reflow {
+    // Remove leading `>` quote markers before reflowing
+    pub strip_quotes: bool,
+    // Re-join words hyphenated across a line break
+    pub join_hyphenated: bool,
+}
+
+impl Default for Reflow {
+    fn default() -> Self {
+        Reflow {
+            strip_quotes: true,
+            join_hyphenated: true,
+        }
+    }
+}
+
+impl Transform for Reflow {
+    fn name(&self) -> &'static str {
+        "reflow"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let mut output: Vec<String> = Vec::new();
+        let mut paragraph: Vec<String> = Vec::new();
+        let mut in_fence = false;
+        let mut paragraph_depth = 0;
+
+        for raw_line in text.split('\n') {
+            let (depth, line) = if self.strip_quotes && !in_fence {
+                strip_quote_prefix(raw_line)
+            } else {
+                (0, raw_line)
+            };
+
+            // A change in quote level (e.g. "On ... wrote:" followed by "> ...") starts a new
+            // paragraph; separate it with a blank line so the boundary survives quote stripping
+            if depth != paragraph_depth {
+                output.extend(self.reflow_paragraph(&paragraph));
+                paragraph.clear();
+                paragraph_depth = depth;
+                if output.last().is_some_and(|last| !last.trim().is_empty()) {
+                    output.push(String::new());
+                }
+            }
+
+            if line.trim_start().starts_with("```") {
+                output.extend(self.reflow_paragraph(&paragraph));
+                paragraph.clear();
+                in_fence = !in_fence;
+                output.push(line.to_string());
+            } else if in_fence {
+                output.push(line.to_string());
+            } else if line.trim().is_empty() {
+                output.extend(self.reflow_paragraph(&paragraph));
+                paragraph.clear();
+                output.push(String::new());
+            } else {
+                paragraph.push(line.to_string());
+            }
+        }
+        output.extend(self.reflow_paragraph(&paragraph));
+
+        Ok(output.join("\n"))
+    }
+}
+
+impl Reflow {
+    fn reflow_paragraph(&self, lines: &[String]) -> Vec<String> {
+        let wrap_width = lines
+            .iter()
+            .map(|line| line.trim().chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut result = Vec::new();
+        let mut current = String::new();
+
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+
+            if current.is_empty() {
+                // Keep the original indentation of the first line of each block
+                current = line.trim_end().to_string();
+            } else {
+                self.join_line(&mut current, trimmed);
+            }
+
+            // A short line inside a wrapped paragraph marks the end of a
+            // paragraph; make that explicit with a blank line so a second pass
+            // doesn't join it with what follows.
+            let (ends_block, ends_paragraph) = match lines.get(index + 1) {
+                None => (true, false),
+                Some(next) if is_block_start(next.trim()) || is_heading(trimmed) => (true, false),
+                // Short-line paragraphs keep their hyphenated breaks too; joining them
+                // could widen the paragraph enough to be joined on the next pass
+                Some(next) => {
+                    let wrapped = wrap_width >= MIN_WRAP_WIDTH;
+                    let short = is_short_line(trimmed, wrap_width)
+                        && !(wrapped && ends_with_split_word(trimmed, next.trim()));
+                    (short, short && wrapped)
+                }
+            };
+
+            if ends_block {
+                result.push(std::mem::take(&mut current));
+            }
+            if ends_paragraph {
+                result.push(String::new());
+            }
+        }
+
+        result
+    }
+
+    fn join_line(&self, current: &mut String, next: &str) {
+        if self.join_hyphenated && ends_with_split_word(current, next) {
+            let left_word = current
+                .trim_end_matches('-')
+                .rsplit(|c: char| !c.is_alphabetic())
+                .next()
+                .unwrap_or("")
+                .to_lowercase();
+            let right_word: String = next
+                .chars()
+                .take_while(|c| c.is_alphabetic())
+                .collect::<String>()
+                .to_lowercase();
+
+            if !keeps_hyphen(&left_word, &right_word) {
+                current.pop();
+            }
+            current.push_str(next);
+        } else {
+            current.push(' ');
+            current.push_str(next);
+        }
+    }
+}
+
+// Strip every level of `>` quoting, e.g. "> > text" or ">> text", returning the quote depth
+fn strip_quote_prefix(line: &str) -> (usize, &str) {
+    let mut rest = line.trim_start();
+    if !rest.starts_with('>') {
+        return (0, line);
+    }
+    let mut depth = 0;
+    while let Some(stripped) = rest.strip_prefix('>') {
+        depth += 1;
+        rest = stripped.trim_start();
+    }
+    (depth, rest)
+}
+
+pub(crate) fn is_list_item(line: &str) -> bool {
+    let mut chars = line.chars();
+    match chars.next() {
+        Some('-') | Some('*') | Some('+') | Some('•') | Some('–') | Some('·') => {
+            chars.next() == Some(' ')
+        }
+        Some(c) if c.is_ascii_alphanumeric() => {
+            // "1. ", "12) ", "a) " style markers
+            let marker_len = line
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .count();
+            let is_numbered = line.chars().take(marker_len).all(|c| c.is_ascii_digit());
+            if marker_len > 3 || (!is_numbered && marker_len > 1) {
+                return false;
+            }
+            let rest = &line[marker_len..];
+            (is_numbered && rest.starts_with(". ")) || rest.starts_with(") ")
+        }
+        _ => false,
+    }
+}
+
+fn is_heading(line: &str) -> bool {
+    if line.starts_with("# ") || line.starts_with("##") {
+        return true;
+    }
+
+    // ALL-CAPS lines such as "INTRODUCTION" or "TERMS AND CONDITIONS"
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    letters.len() >= 3 && line.chars().count() <= 60 && letters.iter().all(|c| c.is_uppercase())
+}
+
+fn is_block_start(line: &str) -> bool {
+    is_list_item(line) || is_heading(line)
+}
+
+fn is_short_line(line: &str, wrap_width: usize) -> bool {
+    if wrap_width < MIN_WRAP_WIDTH {
+        return true;
+    }
+    (line.chars().count() as f32) < wrap_width as f32 * SHORT_LINE_RATIO
+}
+
+fn ends_with_split_word(current: &str, next: &str) -> bool {
+    let mut tail = current.chars().rev();
+    tail.next() == Some('-')
+        && tail.next().is_some_and(|c| c.is_alphabetic())
+        && next.chars().next().is_some_and(|c| c.is_lowercase())
+}
+
+// Decide whether "left-" + "right" is a real compound ("follow-up", "self-contained")
+// or a word broken by line wrapping ("infor-" + "mation").
+fn keeps_hyphen(left: &str, right: &str) -> bool {
+    if HYPHEN_PREFIXES.contains(&left) && !SUFFIX_FRAGMENTS.contains(&right) {
+        return true;
+    }
+
+    let words = common_words();
+    let joined = format!("{}{}", left, right);
+    words.contains(left) && words.contains(right) && !words.contains(joined.as_str())
+}
+
+fn common_words() -> &'static HashSet<&'static str> {
+    static WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        include_str!("common_words.txt")
+            .split_whitespace()
+            .collect()
+    })
+}
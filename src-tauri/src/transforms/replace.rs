@@ -0,0 +1,166 @@
+use crate::storage::{load_json_file, save_json_file};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+const REPLACE_RULES_FILE: &str = "replace_rules.json";
+
+// A house rule such as fixing a product name's casing. `replacement` may refer to
+// capture groups as $1 or ${name}; "$$" is a literal dollar sign.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplaceRule {
+    pub id: String,
+    pub name: String,
+    pub pattern: String,
+    pub replacement: String,
+    pub enabled: bool,
+    pub case_insensitive: bool,
+    // Content types the rule applies to ("text", "code", "url", ...); empty means all
+    pub content_types: Vec<String>,
+}
+
+impl Default for ReplaceRule {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            pattern: String::new(),
+            replacement: String::new(),
+            enabled: true,
+            case_insensitive: false,
+            content_types: Vec::new(),
+        }
+    }
+}
+
+impl ReplaceRule {
+    // "^" and "$" match at line boundaries so rules can target the start of each line
+    pub fn compile(&self) -> Result<Regex, String> {
+        RegexBuilder::new(&self.pattern)
+            .case_insensitive(self.case_insensitive)
+            .multi_line(true)
+            .build()
+            .map_err(|e| format!("Invalid pattern in rule '{}': {}", self.label(), e))
+    }
+
+    pub fn applies_to(&self, content_type: &str) -> bool {
+        self.enabled
+            && (self.content_types.is_empty()
+                || self
+                    .content_types
+                    .iter()
+                    .any(|t| t.eq_ignore_ascii_case(content_type)))
+    }
+
+    fn label(&self) -> &str {
+        if self.name.is_empty() {
+            &self.pattern
+        } else {
+            &self.name
+        }
+    }
+}
+
+// How one rule would affect a text, as reported by a dry run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleMatchCount {
+    pub id: String,
+    pub name: String,
+    pub matches: usize,
+    // False when the rule is disabled or scoped to other content types
+    pub applies: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplaceDryRun {
+    pub rules: Vec<RuleMatchCount>,
+    pub result: String,
+}
+
+// Ordered find/replace rules run after the cleanup pipeline
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplaceRulesStore {
+    pub rules: Vec<ReplaceRule>,
+}
+
+impl ReplaceRulesStore {
+    // Check every pattern and give new rules an id
+    pub fn set_rules(&mut self, mut rules: Vec<ReplaceRule>) -> Result<(), String> {
+        for rule in &mut rules {
+            if rule.pattern.is_empty() {
+                return Err(format!("Rule '{}' has an empty pattern", rule.name));
+            }
+            rule.compile()?;
+            if rule.id.is_empty() {
+                rule.id = Uuid::new_v4().to_string();
+            }
+        }
+        self.rules = rules;
+        Ok(())
+    }
+
+    // Run each applicable rule in order, each on the previous rule's output.
+    // Rules that fail to compile are skipped so a bad rule never blocks a copy.
+    pub fn apply(&self, text: &str, content_type: &str) -> String {
+        self.run(text, content_type).result
+    }
+
+    // Per-rule match counts and the resulting text, without touching the clipboard
+    pub fn dry_run(&self, text: &str, content_type: &str) -> ReplaceDryRun {
+        self.run(text, content_type)
+    }
+
+    fn run(&self, text: &str, content_type: &str) -> ReplaceDryRun {
+        let mut result = text.to_string();
+        let mut counts = Vec::with_capacity(self.rules.len());
+
+        for rule in &self.rules {
+            let applies = rule.applies_to(content_type);
+            let mut count = RuleMatchCount {
+                id: rule.id.clone(),
+                name: rule.name.clone(),
+                matches: 0,
+                applies,
+                error: None,
+            };
+            match rule.compile() {
+                Ok(regex) => {
+                    count.matches = regex.find_iter(&result).count();
+                    if applies && count.matches > 0 {
+                        result = regex
+                            .replace_all(&result, rule.replacement.as_str())
+                            .into_owned();
+                    }
+                }
+                Err(e) => {
+                    if applies {
+                        eprintln!("Skipping replace rule: {}", e);
+                    }
+                    count.error = Some(e);
+                }
+            }
+            counts.push(count);
+        }
+
+        ReplaceDryRun {
+            rules: counts,
+            result,
+        }
+    }
+}
+
+pub type ReplaceRulesState = Arc<RwLock<ReplaceRulesStore>>;
+
+pub fn load_replace_rules_from_file() -> io::Result<ReplaceRulesStore> {
+    load_json_file(REPLACE_RULES_FILE)
+}
+
+pub fn save_replace_rules_to_file(store: &ReplaceRulesStore) -> io::Result<()> {
+    save_json_file(REPLACE_RULES_FILE, store)
+}
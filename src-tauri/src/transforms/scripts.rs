@@ -0,0 +1,300 @@
+// User transforms written in Rhai and loaded from the "scripts" folder in the app
+// data directory. A script sees the clipboard text as `text` and the entry's metadata
+// as `entry`, and its last expression is the transformed text:
+//
+//     // scripts/shout.rhai
+//     if entry.content_type == "text" { text.to_upper() } else { text }
+//
+// Scripts run sandboxed: no file or module access, no `eval`, and limits on running
+// time, operations and the size of strings, arrays and maps they build.
+
+use super::Transform;
+use crate::clipboard::{detect_content_type, ClipboardEntry};
+use crate::storage::get_data_file_path;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+const SCRIPTS_DIR: &str = "scripts";
+const SCRIPT_EXTENSION: &str = "rhai";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptLimits {
+    pub timeout_ms: u64,
+    pub max_operations: u64,
+    // Rhai has no heap limit, so memory is bounded by the size of each value
+    pub max_string_size: usize,
+    pub max_array_size: usize,
+    pub max_map_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 1000,
+            max_operations: 50_000_000,
+            max_string_size: 16 * 1024 * 1024,
+            max_array_size: 100_000,
+            max_map_size: 10_000,
+        }
+    }
+}
+
+// Entry metadata handed to scripts as the `entry` map
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScriptEntry {
+    // Empty when the text doesn't come from a history entry
+    pub id: String,
+    pub content_type: String,
+    pub original_content: String,
+    pub is_cleaned: bool,
+    pub timestamp: String,
+    pub char_count: usize,
+    pub line_count: usize,
+}
+
+impl ScriptEntry {
+    // Metadata for text that isn't in history yet, such as a cleanup pipeline's input
+    pub fn for_text(text: &str) -> Self {
+        Self {
+            id: String::new(),
+            content_type: detect_content_type(text),
+            original_content: text.to_string(),
+            is_cleaned: false,
+            timestamp: String::new(),
+            char_count: text.chars().count(),
+            line_count: text.lines().count(),
+        }
+    }
+
+    fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("id".into(), self.id.clone().into());
+        map.insert("content_type".into(), self.content_type.clone().into());
+        map.insert(
+            "original_content".into(),
+            self.original_content.clone().into(),
+        );
+        map.insert("is_cleaned".into(), self.is_cleaned.into());
+        map.insert("timestamp".into(), self.timestamp.clone().into());
+        map.insert("char_count".into(), (self.char_count as i64).into());
+        map.insert("line_count".into(), (self.line_count as i64).into());
+        map
+    }
+}
+
+impl From<&ClipboardEntry> for ScriptEntry {
+    fn from(entry: &ClipboardEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            content_type: entry.content_type.clone(),
+            original_content: entry.original_content.clone(),
+            is_cleaned: entry.is_cleaned,
+            timestamp: entry.timestamp.to_rfc3339(),
+            char_count: entry.char_count,
+            line_count: entry.line_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptInfo {
+    pub name: String,
+    pub path: String,
+    // Syntax error found when the script was compiled, if any
+    pub error: Option<String>,
+}
+
+// The scripts folder, created on first use
+pub fn scripts_dir() -> io::Result<PathBuf> {
+    let dir = get_data_file_path(SCRIPTS_DIR)?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Every *.rhai file in the scripts folder, by name
+pub fn list_scripts() -> io::Result<Vec<ScriptInfo>> {
+    let engine = sandboxed_engine(&ScriptLimits::default());
+    let mut scripts = Vec::new();
+    for entry in fs::read_dir(scripts_dir()?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(SCRIPT_EXTENSION) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let error = match fs::read_to_string(&path) {
+            Ok(source) => engine.compile(&source).err().map(|e| e.to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        scripts.push(ScriptInfo {
+            name: name.to_string(),
+            path: path.to_string_lossy().into_owned(),
+            error,
+        });
+    }
+    scripts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(scripts)
+}
+
+// Names are plain file stems so a script can never be read from outside the folder
+fn script_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '));
+    if !valid {
+        return Err(format!("Invalid script name '{}'", name));
+    }
+    let dir = scripts_dir().map_err(|e| format!("Failed to open scripts folder: {}", e))?;
+    Ok(dir.join(format!("{}.{}", name, SCRIPT_EXTENSION)))
+}
+
+pub fn load_script(name: &str) -> Result<String, String> {
+    let path = script_path(name)?;
+    fs::read_to_string(&path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => format!("Script '{}' not found", name),
+        _ => format!("Failed to read script '{}': {}", name, e),
+    })
+}
+
+fn sandboxed_engine(limits: &ScriptLimits) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(limits.max_operations);
+    engine.set_max_string_size(limits.max_string_size);
+    engine.set_max_array_size(limits.max_array_size);
+    engine.set_max_map_size(limits.max_map_size);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 64);
+    engine.on_print(|message| println!("[Script] {}", message));
+    engine.on_debug(|message, _, _| println!("[Script] {}", message));
+    engine
+}
+
+// A compiled script and the file version it was compiled from
+struct CompiledScript {
+    modified: Option<SystemTime>,
+    len: u64,
+    ast: Arc<AST>,
+}
+
+// Scripts by name, compiled once and again only when their file changes
+fn compiled_script(name: &str) -> Result<Arc<AST>, String> {
+    static COMPILED: OnceLock<Mutex<HashMap<String, CompiledScript>>> = OnceLock::new();
+
+    let path = script_path(name)?;
+    let (modified, len) = match fs::metadata(&path) {
+        Ok(metadata) => (metadata.modified().ok(), metadata.len()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(format!("Script '{}' not found", name))
+        }
+        Err(e) => return Err(format!("Failed to read script '{}': {}", name, e)),
+    };
+
+    let mut compiled = COMPILED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(script) = compiled.get(name) {
+        if script.modified.is_some() && script.modified == modified && script.len == len {
+            return Ok(script.ast.clone());
+        }
+    }
+
+    let ast = Arc::new(compile(&load_script(name)?, &ScriptLimits::default())?);
+    compiled.insert(
+        name.to_string(),
+        CompiledScript {
+            modified,
+            len,
+            ast: ast.clone(),
+        },
+    );
+    Ok(ast)
+}
+
+fn compile(source: &str, limits: &ScriptLimits) -> Result<AST, String> {
+    sandboxed_engine(limits)
+        .compile(source)
+        .map_err(|e| format!("Script error: {}", e))
+}
+
+// Run `source` on `text` and return the string it evaluates to
+pub fn eval_script(
+    source: &str,
+    text: &str,
+    entry: &ScriptEntry,
+    limits: &ScriptLimits,
+) -> Result<String, String> {
+    eval_ast(&compile(source, limits)?, text, entry, limits)
+}
+
+fn eval_ast(
+    ast: &AST,
+    text: &str,
+    entry: &ScriptEntry,
+    limits: &ScriptLimits,
+) -> Result<String, String> {
+    let mut engine = sandboxed_engine(limits);
+    let deadline = Instant::now() + Duration::from_millis(limits.timeout_ms);
+    engine.on_progress(move |_| (Instant::now() > deadline).then_some(Dynamic::UNIT));
+
+    let mut scope = Scope::new();
+    scope.push("text", text.to_string());
+    scope.push("entry", entry.to_map());
+
+    let result = engine
+        .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+        .map_err(|e| match *e {
+            EvalAltResult::ErrorTerminated(..) => {
+                format!("Script timed out after {} ms", limits.timeout_ms)
+            }
+            EvalAltResult::ErrorTooManyOperations(..) => {
+                "Script exceeded its operation limit".to_string()
+            }
+            EvalAltResult::ErrorDataTooLarge(ref what, ..) => {
+                format!("Script exceeded its memory limit: {} too large", what)
+            }
+            e => format!("Script error: {}", e),
+        })?;
+
+    let type_name = result.type_name();
+    result
+        .into_string()
+        .map_err(|_| format!("Script must return a string, not {}", type_name))
+}
+
+// Run a script from the scripts folder by name. Scripts can run for up to their time
+// limit, so async callers should run this on a blocking thread.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunScript {
+    pub script: String,
+}
+
+impl RunScript {
+    pub fn run(&self, text: &str, entry: &ScriptEntry) -> Result<String, String> {
+        let ast = compiled_script(&self.script)?;
+        eval_ast(&ast, text, entry, &ScriptLimits::default())
+    }
+}
+
+impl Transform for RunScript {
+    fn name(&self) -> &'static str {
+        "run_script"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        self.run(text, &ScriptEntry::for_text(text))
+    }
+}
@@ -0,0 +1,387 @@
+// A dialect-agnostic SQL layout pass. It never parses the grammar: statements are
+// tokenized, keywords upper-cased and line breaks placed before clauses, so any
+// dialect formats reasonably and the token stream itself is never altered.
+
+use super::data::DataError;
+
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "AS",
+    "ASC",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CHECK",
+    "COLUMN",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXCEPT",
+    "EXISTS",
+    "FALSE",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "ILIKE",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NATURAL",
+    "NOT",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "PRIMARY",
+    "REFERENCES",
+    "REPLACE",
+    "RETURNING",
+    "RIGHT",
+    "SELECT",
+    "SET",
+    "TABLE",
+    "THEN",
+    "TRUE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WITH",
+];
+
+// Keywords that start a new line at the statement's indentation
+const CLAUSES: &[&str] = &[
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "GROUP",
+    "ORDER",
+    "HAVING",
+    "LIMIT",
+    "OFFSET",
+    "UNION",
+    "INTERSECT",
+    "EXCEPT",
+    "VALUES",
+    "SET",
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "RETURNING",
+    "WITH",
+    "JOIN",
+];
+
+// Start a new line only as part of "LEFT JOIN", "FULL OUTER JOIN" and so on
+const JOIN_MODIFIERS: &[&str] = &["LEFT", "RIGHT", "INNER", "FULL", "CROSS", "NATURAL"];
+
+const STATEMENTS: &[&str] = &[
+    "SELECT", "INSERT", "UPDATE", "DELETE", "WITH", "CREATE", "ALTER", "DROP",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    // A quoted string or identifier, including its quotes
+    Quoted(&'a str),
+    LineComment(&'a str),
+    BlockComment(&'a str),
+    Punct(char),
+    Operator(&'a str),
+}
+
+// Tokens with their byte offsets, or the offset and message of the first lexing error
+fn tokenize(text: &str) -> Result<Vec<(Token<'_>, usize)>, (usize, &'static str)> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while let Some(c) = text[position..].chars().next() {
+        let rest = &text[position..];
+        let start = position;
+        let token = if c.is_whitespace() {
+            position += c.len_utf8();
+            continue;
+        } else if rest.starts_with("--") {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            position += end;
+            Token::LineComment(rest[..end].trim_end())
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let end = body
+                .find("*/")
+                .map(|i| i + 4)
+                .ok_or((start, "unterminated comment"))?;
+            position += end;
+            Token::BlockComment(&rest[..end])
+        } else if matches!(c, '\'' | '"' | '`') {
+            let end = quoted_end(rest, c).ok_or((start, "unterminated string"))?;
+            position += end;
+            Token::Quoted(&rest[..end])
+        } else if "(),;.[]{}".contains(c) {
+            position += 1;
+            Token::Punct(c)
+        } else if is_word_char(c) {
+            let end = rest.find(|c: char| !is_word_char(c)).unwrap_or(rest.len());
+            position += end;
+            Token::Word(&rest[..end])
+        } else {
+            // A run of operator characters, stopping before a comment
+            let mut end = c.len_utf8();
+            while let Some(next) = rest[end..].chars().next() {
+                let tail = &rest[end..];
+                if is_word_char(next)
+                    || next.is_whitespace()
+                    || "(),;.[]{}'\"`".contains(next)
+                    || tail.starts_with("--")
+                    || tail.starts_with("/*")
+                {
+                    break;
+                }
+                end += next.len_utf8();
+            }
+            position += end;
+            Token::Operator(&rest[..end])
+        };
+        tokens.push((token, start));
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && (!c.is_ascii_punctuation() || matches!(c, '_' | '$' | '@' | '#' | ':'))
+}
+
+// Byte index just past the closing quote; doubled quotes are escapes
+fn quoted_end(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((index, c)) = chars.next() {
+        if c == quote {
+            if chars.peek().is_some_and(|&(_, next)| next == quote) {
+                chars.next();
+                continue;
+            }
+            return Some(index + 1);
+        }
+    }
+    None
+}
+
+fn keyword(token: &Token) -> Option<String> {
+    match token {
+        Token::Word(word) => {
+            let upper = word.to_ascii_uppercase();
+            KEYWORDS.contains(&upper.as_str()).then_some(upper)
+        }
+        _ => None,
+    }
+}
+
+// Lay out `text` with `indent` spaces per level, or on a single line when None.
+// Text that can't be tokenized is returned unchanged.
+pub fn format(text: &str, indent: Option<usize>) -> String {
+    let Ok(tokens) = tokenize(text) else {
+        return text.to_string();
+    };
+    let keywords: Vec<Option<String>> = tokens.iter().map(|(t, _)| keyword(t)).collect();
+
+    let mut output = String::with_capacity(text.len());
+    // One entry per open parenthesis: true when it holds a subquery
+    let mut parens: Vec<bool> = Vec::new();
+    let mut in_between = false;
+    let mut after_line_comment = false;
+    let mut previous: Option<&Token> = None;
+    // A sign with nothing to its left binds to the next token: "= -1"
+    let mut after_unary = false;
+
+    for (index, (token, _)) in tokens.iter().enumerate() {
+        let word = keywords[index].as_deref();
+        let next_word = keywords.get(index + 1).and_then(|k| k.as_deref());
+        let previous_word = index.checked_sub(1).and_then(|i| keywords[i].as_deref());
+        // Subqueries sit one level deeper than the condition or list holding them
+        let level = 2 * parens.iter().filter(|&&subquery| subquery).count();
+        let at_clause_depth = parens.last().is_none_or(|&subquery| subquery);
+
+        let starts_clause = match word {
+            Some("JOIN") => {
+                !matches!(previous_word, Some(w) if JOIN_MODIFIERS.contains(&w) || w == "OUTER")
+            }
+            Some(w) if JOIN_MODIFIERS.contains(&w) => matches!(next_word, Some("JOIN" | "OUTER")),
+            Some(w) => CLAUSES.contains(&w),
+            None => false,
+        };
+        let starts_condition = at_clause_depth && matches!(word, Some("AND" | "OR")) && !in_between;
+        if word == Some("AND") && in_between {
+            in_between = false;
+        }
+        if word == Some("BETWEEN") {
+            in_between = true;
+        }
+
+        let closes_subquery = *token == Token::Punct(')') && parens.last() == Some(&true);
+        let line_break = if starts_clause {
+            Some(level)
+        } else if closes_subquery {
+            Some(level.saturating_sub(1))
+        } else if starts_condition || after_line_comment {
+            Some(level + 1)
+        } else {
+            None
+        };
+        after_line_comment = false;
+
+        match (indent, line_break) {
+            (Some(width), Some(depth)) if !output.is_empty() => {
+                output.truncate(output.trim_end().len());
+                output.push('\n');
+                output.push_str(&" ".repeat(width * depth));
+            }
+            _ if output.is_empty() || output.ends_with(' ') || output.ends_with('\n') => {}
+            _ => {
+                // Comments keep their distance, and operators are never glued into "--" or "/*"
+                let separate = match token {
+                    Token::LineComment(_) | Token::BlockComment(_) => true,
+                    Token::Operator(text) => {
+                        (output.ends_with('-') && text.starts_with('-'))
+                            || (output.ends_with('/') && text.starts_with('*'))
+                    }
+                    _ => false,
+                };
+                if separate || !after_unary && needs_space(previous, token, previous_word) {
+                    output.push(' ');
+                }
+            }
+        }
+
+        match token {
+            Token::Word(text) => output.push_str(word.unwrap_or(text)),
+            Token::LineComment(comment) if indent.is_none() && !comment.contains("*/") => {
+                output.push_str(&format!("/* {} */", comment[2..].trim()))
+            }
+            Token::LineComment(comment) => {
+                output.push_str(comment);
+                if indent.is_none() {
+                    output.push('\n');
+                }
+                after_line_comment = true;
+            }
+            Token::Quoted(text) | Token::BlockComment(text) | Token::Operator(text) => {
+                output.push_str(text)
+            }
+            Token::Punct(c) => output.push(*c),
+        }
+
+        match token {
+            Token::Punct('(') => {
+                parens.push(matches!(next_word, Some("SELECT" | "WITH")));
+            }
+            Token::Punct(')') => {
+                parens.pop();
+            }
+            Token::Punct(',') if at_clause_depth && indent.is_some() => {
+                output.push('\n');
+                output.push_str(&" ".repeat(indent.unwrap_or(0) * (level + 1)));
+            }
+            Token::Punct(';') => {
+                parens.clear();
+                in_between = false;
+                if indent.is_some() {
+                    output.push('\n');
+                }
+            }
+            _ => {}
+        }
+        after_unary = matches!(token, Token::Operator("-" | "+"))
+            && match previous {
+                None | Some(Token::Operator(_) | Token::Punct('(' | ',' | '[')) => true,
+                Some(Token::Word(_)) => previous_word.is_some_and(|w| w != "END"),
+                _ => false,
+            };
+        previous = Some(token);
+    }
+
+    output.trim_end().to_string()
+}
+
+fn needs_space(previous: Option<&Token>, token: &Token, previous_word: Option<&str>) -> bool {
+    match (previous, token) {
+        (Some(Token::Punct('(' | '.' | '[')), _) => false,
+        (_, Token::Punct(')' | ',' | ';' | '.' | ']')) => false,
+        // Function calls and array indexes hug their brackets: COUNT(*), items[0]
+        (Some(Token::Word(_) | Token::Quoted(_)), Token::Punct('(' | '[')) => {
+            previous_word.is_some()
+        }
+        _ => true,
+    }
+}
+
+// Check that strings and comments are terminated and parentheses balance
+pub fn validate(text: &str) -> Result<(), DataError> {
+    let tokens =
+        tokenize(text).map_err(|(offset, message)| DataError::at_offset(text, offset, message))?;
+
+    let mut open: Vec<usize> = Vec::new();
+    for (token, offset) in &tokens {
+        match token {
+            Token::Punct('(') => open.push(*offset),
+            Token::Punct(')') if open.pop().is_none() => {
+                return Err(DataError::at_offset(text, *offset, "unmatched ')'"));
+            }
+            _ => {}
+        }
+    }
+    match open.last() {
+        Some(offset) => Err(DataError::at_offset(text, *offset, "unclosed '('")),
+        None => Ok(()),
+    }
+}
+
+// Text starting with a statement keyword followed by more of the statement,
+// after any leading comments
+pub fn looks_like_sql(text: &str) -> bool {
+    let mut text = text.trim_start();
+    loop {
+        if text.starts_with("--") {
+            text = text.find('\n').map_or("", |end| &text[end..]).trim_start();
+        } else if let Some(body) = text.strip_prefix("/*") {
+            text = body
+                .find("*/")
+                .map_or("", |end| &body[end + 2..])
+                .trim_start();
+        } else {
+            break;
+        }
+    }
+    let Some(end) = text.find(char::is_whitespace) else {
+        return false;
+    };
+    STATEMENTS.contains(&text[..end].to_ascii_uppercase().as_str())
+        && !text[end..].trim().is_empty()
+}
@@ -0,0 +1,637 @@
+// Conversion between the table shapes that end up on the clipboard: CSV, TSV copied
+// from spreadsheets, Markdown and ASCII tables, and JSON arrays of objects. Every
+// format is read into a `Table` of plain strings and written back out from it.
+
+use super::data::{parse_json, to_json};
+use super::Transform;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    // Detect the source format; as a target, keep the source format
+    #[default]
+    Auto,
+    Csv,
+    Tsv,
+    Markdown,
+    Ascii,
+    Json,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub header: Option<Vec<String>>,
+    pub rows: Vec<Vec<String>>,
+}
+
+// What detection found, for showing conversion options before converting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub format: TableFormat,
+    pub has_header: bool,
+    pub rows: usize,
+    pub columns: usize,
+}
+
+// The format of a table in `text`, or None when it doesn't look like one
+pub fn detect_format(text: &str) -> Option<TableFormat> {
+    let trimmed = text.trim();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return parse_json_table(trimmed, None)
+            .is_ok()
+            .then_some(TableFormat::Json);
+    }
+
+    let lines: Vec<&str> = content_lines(trimmed).collect();
+    if lines.iter().any(|line| is_rule(line) && line.contains('+')) {
+        return Some(TableFormat::Ascii);
+    }
+    if lines.len() >= 2 && lines.iter().all(|line| line.contains('|')) && is_separator(lines[1]) {
+        return Some(TableFormat::Markdown);
+    }
+    if lines.first().is_some_and(|line| line.contains('\t')) {
+        return parse_delimited(trimmed, '\t')
+            .is_ok_and(|rows| rows.iter().any(|row| row.len() >= 2))
+            .then_some(TableFormat::Tsv);
+    }
+    // Commas alone are common in prose, so CSV needs two rows with a consistent shape
+    match parse_delimited(trimmed, ',') {
+        Ok(rows) if rows.len() >= 2 && rows[0].len() >= 2 => rows
+            .iter()
+            .all(|row| row.len() == rows[0].len())
+            .then_some(TableFormat::Csv),
+        _ => None,
+    }
+}
+
+pub fn detect_table(text: &str) -> Option<TableInfo> {
+    let format = detect_format(text)?;
+    let table = Table::parse(text, format, None).ok()?;
+    Some(TableInfo {
+        format,
+        has_header: table.header.is_some(),
+        rows: table.rows.len(),
+        columns: table.columns(),
+    })
+}
+
+impl Table {
+    // Parse `text` as `format`. `has_header` overrides header detection.
+    pub fn parse(
+        text: &str,
+        format: TableFormat,
+        has_header: Option<bool>,
+    ) -> Result<Table, String> {
+        let format = match format {
+            TableFormat::Auto => detect_format(text).ok_or("No table detected")?,
+            format => format,
+        };
+        let table = match format {
+            TableFormat::Csv | TableFormat::Tsv => {
+                let delimiter = if format == TableFormat::Csv {
+                    ','
+                } else {
+                    '\t'
+                };
+                let rows = parse_delimited(text.trim_end_matches(['\r', '\n']), delimiter)?;
+                split_header(rows, has_header)
+            }
+            TableFormat::Markdown => parse_markdown(text, has_header)?,
+            TableFormat::Ascii => parse_ascii(text, has_header),
+            TableFormat::Json => parse_json_table(text.trim(), has_header)?,
+            TableFormat::Auto => unreachable!(),
+        };
+        if table.header.is_none() && table.rows.is_empty() {
+            return Err("No table detected".to_string());
+        }
+        Ok(table.padded())
+    }
+
+    pub fn columns(&self) -> usize {
+        let widest_row = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        self.header.as_ref().map_or(0, Vec::len).max(widest_row)
+    }
+
+    // Give every row the same number of cells
+    fn padded(mut self) -> Table {
+        let columns = self.columns();
+        for row in self.header.iter_mut().chain(self.rows.iter_mut()) {
+            row.resize(columns, String::new());
+        }
+        self
+    }
+
+    fn all_rows(&self) -> impl Iterator<Item = &Vec<String>> {
+        self.header.iter().chain(self.rows.iter())
+    }
+
+    pub fn to_format(&self, format: TableFormat) -> Result<String, String> {
+        match format {
+            TableFormat::Csv => Ok(self.to_delimited(',')),
+            TableFormat::Tsv => Ok(self.to_delimited('\t')),
+            TableFormat::Markdown => Ok(self.to_markdown()),
+            TableFormat::Ascii => Ok(self.to_ascii()),
+            TableFormat::Json => Ok(self.to_json()),
+            TableFormat::Auto => Err("A target table format is required".to_string()),
+        }
+    }
+
+    // Fields are quoted only when they contain the delimiter, a quote or a line break,
+    // which is also what spreadsheets expect when pasting TSV
+    fn to_delimited(&self, delimiter: char) -> String {
+        self.all_rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| {
+                        if cell.contains([delimiter, '"', '\n', '\r']) {
+                            format!("\"{}\"", cell.replace('"', "\"\""))
+                        } else {
+                            cell.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&delimiter.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn to_markdown(&self) -> String {
+        // Markdown tables always have a header row
+        let header = self.header.clone().unwrap_or_else(|| {
+            (1..=self.columns())
+                .map(|n| format!("Column {}", n))
+                .collect()
+        });
+        let escape = |cell: &String| {
+            cell.replace('|', "\\|")
+                .replace("\r\n", "<br>")
+                .replace('\n', "<br>")
+        };
+        let header: Vec<String> = header.iter().map(escape).collect();
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(escape).collect())
+            .collect();
+        let widths = column_widths(std::iter::once(&header).chain(rows.iter()), 3);
+
+        let mut lines = vec![bordered_row(&header, &widths)];
+        let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+        lines.push(bordered_row(&separator, &widths));
+        lines.extend(rows.iter().map(|row| bordered_row(row, &widths)));
+        lines.join("\n")
+    }
+
+    // A box-drawn table for plain-text contexts. Line breaks inside cells become spaces.
+    fn to_ascii(&self) -> String {
+        let flatten = |row: &Vec<String>| -> Vec<String> {
+            row.iter()
+                .map(|cell| {
+                    cell.split(['\r', '\n'])
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect()
+        };
+        let header = self.header.as_ref().map(flatten);
+        let rows: Vec<Vec<String>> = self.rows.iter().map(flatten).collect();
+        let widths = column_widths(header.iter().chain(rows.iter()), 1);
+
+        let rule = format!(
+            "+{}+",
+            widths
+                .iter()
+                .map(|&width| "-".repeat(width + 2))
+                .collect::<Vec<_>>()
+                .join("+")
+        );
+        let mut lines = vec![rule.clone()];
+        if let Some(header) = &header {
+            lines.push(bordered_row(header, &widths));
+            lines.push(rule.clone());
+        }
+        lines.extend(rows.iter().map(|row| bordered_row(row, &widths)));
+        if !rows.is_empty() {
+            lines.push(rule);
+        }
+        lines.join("\n")
+    }
+
+    // An array of objects keyed by the header, or an array of arrays without one.
+    // Cells that read as JSON numbers or booleans are written as such.
+    fn to_json(&self) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| match &self.header {
+                Some(header) => Value::Object(
+                    unique_keys(header)
+                        .into_iter()
+                        .zip(row.iter().map(|cell| json_value(cell)))
+                        .collect(),
+                ),
+                None => Value::Array(row.iter().map(|cell| json_value(cell)).collect()),
+            })
+            .collect();
+        to_json(&Value::Array(rows), Some(2))
+    }
+}
+
+// Non-blank lines with surrounding whitespace removed
+fn content_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+fn split_header(mut rows: Vec<Vec<String>>, has_header: Option<bool>) -> Table {
+    let has_header = has_header.unwrap_or_else(|| looks_like_header(&rows));
+    let header = (has_header && !rows.is_empty()).then(|| rows.remove(0));
+    Table { header, rows }
+}
+
+// A first row of distinct, non-empty, non-numeric labels above at least one data row
+fn looks_like_header(rows: &[Vec<String>]) -> bool {
+    let Some(first) = rows.first() else {
+        return false;
+    };
+    let mut seen = HashSet::new();
+    rows.len() >= 2
+        && first.iter().all(|cell| {
+            let cell = cell.trim();
+            !cell.is_empty() && !is_numeric(cell) && seen.insert(cell.to_lowercase())
+        })
+}
+
+fn is_numeric(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || ".,-+eE%$".contains(c))
+}
+
+// RFC 4180 parsing: quoted fields may hold delimiters, doubled quotes and line breaks.
+// Blank lines between records are skipped.
+fn parse_delimited(text: &str, delimiter: char) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut quote_line = 1;
+    let mut chars = text.chars().peekable();
+
+    let mut end_row = |row: &mut Vec<String>, field: &mut String, quoted: bool| {
+        if row.is_empty() && field.is_empty() && !quoted {
+            return;
+        }
+        row.push(std::mem::take(field));
+        rows.push(std::mem::take(row));
+    };
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                in_quotes = true;
+                quoted = true;
+                quote_line = line;
+            }
+            c if c == delimiter => {
+                row.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                end_row(&mut row, &mut field, quoted);
+                quoted = false;
+                line += 1;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(format!(
+            "Unterminated quoted field starting on line {}",
+            quote_line
+        ));
+    }
+    end_row(&mut row, &mut field, quoted);
+    Ok(rows)
+}
+
+// Cells between unescaped pipes, with the optional outer pipes removed
+fn split_pipes(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => line,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                chars.next();
+                cell.push('|');
+            }
+            '|' => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+        .into_iter()
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+// A Markdown delimiter row such as "| --- | :---: |"
+fn is_separator(line: &str) -> bool {
+    let cells = split_pipes(line);
+    cells.iter().all(|cell| {
+        let dashes = cell.strip_prefix(':').unwrap_or(cell);
+        let dashes = dashes.strip_suffix(':').unwrap_or(dashes);
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    })
+}
+
+// A horizontal border in an ASCII table: "+----+----+", "-----+-----" or "|====|"
+fn is_rule(line: &str) -> bool {
+    line.contains(['-', '='])
+        && line
+            .chars()
+            .all(|c| matches!(c, '+' | '-' | '=' | '|' | ':' | ' '))
+}
+
+fn parse_markdown(text: &str, has_header: Option<bool>) -> Result<Table, String> {
+    let lines: Vec<&str> = content_lines(text).collect();
+    if lines.len() < 2 || !is_separator(lines[1]) {
+        return Err("Markdown table is missing its delimiter row".to_string());
+    }
+    let unescape = |line: &str| -> Vec<String> {
+        split_pipes(line)
+            .into_iter()
+            .map(|cell| {
+                cell.replace("<br/>", "\n")
+                    .replace("<br />", "\n")
+                    .replace("<br>", "\n")
+            })
+            .collect()
+    };
+
+    let header = unescape(lines[0]);
+    let mut rows: Vec<Vec<String>> = lines[2..].iter().map(|line| unescape(line)).collect();
+    if has_header == Some(false) {
+        rows.insert(0, header);
+        return Ok(Table { header: None, rows });
+    }
+    Ok(Table {
+        header: Some(header),
+        rows,
+    })
+}
+
+// Box-drawn tables from database shells and plain-text reports. A rule under the
+// first row marks it as the header.
+fn parse_ascii(text: &str, has_header: Option<bool>) -> Table {
+    let mut rows = Vec::new();
+    let mut first_rule = None;
+    for line in content_lines(text) {
+        if is_rule(line) {
+            if !rows.is_empty() && first_rule.is_none() {
+                first_rule = Some(rows.len());
+            }
+            continue;
+        }
+        rows.push(split_pipes(line));
+    }
+    let detected = first_rule == Some(1) && rows.len() > 1;
+    split_header(rows, Some(has_header.unwrap_or(detected)))
+}
+
+fn parse_json_table(text: &str, has_header: Option<bool>) -> Result<Table, String> {
+    let node = parse_json(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let items = match node {
+        Value::Array(items) => items,
+        object @ Value::Object(_) => vec![object],
+        _ => return Err("Expected a JSON array of objects or arrays".to_string()),
+    };
+
+    if items.iter().all(|item| matches!(item, Value::Object(_))) {
+        // The header is every key in order of first appearance
+        let mut keys: Vec<String> = Vec::new();
+        for item in &items {
+            if let Value::Object(entries) = item {
+                for (key, _) in entries {
+                    if !keys.contains(key) {
+                        keys.push(key.clone());
+                    }
+                }
+            }
+        }
+        let rows = items
+            .iter()
+            .map(|item| {
+                keys.iter()
+                    .map(|key| match item {
+                        Value::Object(entries) => {
+                            entries.get(key).map(cell_text).unwrap_or_default()
+                        }
+                        _ => String::new(),
+                    })
+                    .collect()
+            })
+            .collect();
+        return Ok(Table {
+            header: Some(keys),
+            rows,
+        });
+    }
+
+    if items.iter().all(|item| matches!(item, Value::Array(_))) {
+        let rows = items
+            .iter()
+            .map(|item| match item {
+                Value::Array(values) => values.iter().map(cell_text).collect(),
+                _ => Vec::new(),
+            })
+            .collect();
+        return Ok(split_header(rows, has_header));
+    }
+
+    Err("Expected a JSON array of objects or arrays".to_string())
+}
+
+// Nested arrays and objects are kept as compact JSON
+fn cell_text(node: &Value) -> String {
+    match node {
+        Value::Null => String::new(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::String(value) => value.clone(),
+        nested => nested.to_string(),
+    }
+}
+
+fn json_value(cell: &str) -> Value {
+    match cell {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        // A number serde_json would write differently, e.g. "1.50", stays as written
+        cell if is_json_number(cell) => cell
+            .parse::<Number>()
+            .ok()
+            .filter(|number| number.to_string() == cell)
+            .map_or_else(|| Value::String(cell.to_string()), Value::Number),
+        cell => Value::String(cell.to_string()),
+    }
+}
+
+// Numbers exactly as JSON writes them, so "007" and "1." stay strings
+fn is_json_number(text: &str) -> bool {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let integer_end = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    let (integer, mut rest) = unsigned.split_at(integer_end);
+    if integer.is_empty() || (integer.len() > 1 && integer.starts_with('0')) {
+        return false;
+    }
+
+    let digits = |text: &str| {
+        text.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len())
+    };
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let end = digits(fraction);
+        if end == 0 {
+            return false;
+        }
+        rest = &fraction[end..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let end = digits(exponent);
+        if end == 0 {
+            return false;
+        }
+        rest = &exponent[end..];
+    }
+    rest.is_empty()
+}
+
+// Header names made unique and non-empty so no column is lost as an object key
+fn unique_keys(header: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    header
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let base = if name.is_empty() {
+                format!("column_{}", index + 1)
+            } else {
+                name.clone()
+            };
+            let mut key = base.clone();
+            let mut suffix = 2;
+            while !seen.insert(key.clone()) {
+                key = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            key
+        })
+        .collect()
+}
+
+fn column_widths<'a>(rows: impl Iterator<Item = &'a Vec<String>>, minimum: usize) -> Vec<usize> {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            let width = cell.chars().count().max(minimum);
+            match widths.get_mut(index) {
+                Some(existing) => *existing = (*existing).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    widths
+}
+
+fn bordered_row(cells: &[String], widths: &[usize]) -> String {
+    let padded: Vec<String> = widths
+        .iter()
+        .enumerate()
+        .map(|(index, &width)| {
+            let cell = cells.get(index).map(String::as_str).unwrap_or("");
+            format!(
+                "{}{}",
+                cell,
+                " ".repeat(width - cell.chars().count().min(width))
+            )
+        })
+        .collect();
+    format!("| {} |", padded.join(" | "))
+}
+
+// Convert a table between CSV, TSV, Markdown, ASCII and JSON
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConvertTable {
+    pub from: TableFormat,
+    pub to: TableFormat,
+    // None detects whether the first row is a header
+    pub has_header: Option<bool>,
+}
+
+impl Default for ConvertTable {
+    fn default() -> Self {
+        Self {
+            from: TableFormat::Auto,
+            to: TableFormat::Markdown,
+            has_header: None,
+        }
+    }
+}
+
+impl Transform for ConvertTable {
+    fn name(&self) -> &'static str {
+        "convert_table"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let from = match self.from {
+            TableFormat::Auto => detect_format(text).ok_or("No table detected")?,
+            format => format,
+        };
+        let to = match self.to {
+            TableFormat::Auto => from,
+            format => format,
+        };
+        Table::parse(text, from, self.has_header)?.to_format(to)
+    }
+}
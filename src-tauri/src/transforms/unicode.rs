@@ -0,0 +1,262 @@
+use super::Transform;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NormalizationForm {
+    None,
+    // Canonical composition; safe for any text
+    #[default]
+    Nfc,
+    // Compatibility composition; also folds full-width forms, ligatures, superscripts, ...
+    Nfkc,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharacterAction {
+    // Delete the character and report it
+    #[default]
+    Remove,
+    // Leave the character in place but report it
+    Report,
+    // Ignore the character entirely
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnicodeCategory {
+    ZeroWidth,
+    ByteOrderMark,
+    SoftHyphen,
+    // U+2028/U+2029, which most editors and compilers don't treat as line breaks
+    LineSeparator,
+    BidiControl,
+    SpecialSpace,
+    Punctuation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingAction {
+    Removed,
+    Replaced,
+    Reported,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnicodeFinding {
+    pub category: UnicodeCategory,
+    pub code_point: String,
+    pub name: String,
+    // 1-based position in the original text, column counted in characters
+    pub line: usize,
+    pub column: usize,
+    pub action: FindingAction,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnicodeReport {
+    pub findings: Vec<UnicodeFinding>,
+    // True when normalization changed the text after scrubbing
+    pub normalization_changed: bool,
+}
+
+impl UnicodeReport {
+    pub fn has_bidi_controls(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|f| f.category == UnicodeCategory::BidiControl)
+    }
+}
+
+// Normalize Unicode and scrub invisible, bidi-control and look-alike characters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnicodeScrub {
+    pub normalization: NormalizationForm,
+    // Zero-width characters, invisible operators, BOMs, soft hyphens and Unicode line
+    // separators; removed line separators become newlines
+    pub invisible: CharacterAction,
+    // Bidirectional overrides and isolates, which can make code read differently than it runs
+    pub bidi_controls: CharacterAction,
+    // Turn non-breaking and other fixed-width spaces into plain spaces
+    pub replace_special_spaces: bool,
+    // Turn smart quotes, dashes and ellipses into ASCII
+    pub ascii_punctuation: bool,
+}
+
+impl Default for UnicodeScrub {
+    fn default() -> Self {
+        UnicodeScrub {
+            normalization: NormalizationForm::Nfc,
+            invisible: CharacterAction::Remove,
+            bidi_controls: CharacterAction::Remove,
+            replace_special_spaces: true,
+            ascii_punctuation: false,
+        }
+    }
+}
+
+impl Transform for UnicodeScrub {
+    fn name(&self) -> &'static str {
+        "unicode_scrub"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        Ok(self.scrub(text).0)
+    }
+}
+
+impl UnicodeScrub {
+    // Scrub the text and describe every change that was made or flagged
+    pub fn scrub(&self, text: &str) -> (String, UnicodeReport) {
+        let mut report = UnicodeReport::default();
+        let mut scrubbed = String::with_capacity(text.len());
+        let chars: Vec<char> = text.chars().collect();
+        let mut line = 1;
+        let mut column = 0;
+
+        for (index, &c) in chars.iter().enumerate() {
+            if c == '\n' {
+                line += 1;
+                column = 0;
+                scrubbed.push(c);
+                continue;
+            }
+            column += 1;
+
+            let outcome = match classify(c) {
+                // Zero width joiners inside emoji sequences (e.g. 👩‍💻) are meaningful
+                Some(_) if c == '\u{200D}' && is_emoji_joiner(&chars, index) => None,
+                Some((category, name)) => self.handle(category, c).inspect(|(action, _)| {
+                    report.findings.push(UnicodeFinding {
+                        category,
+                        code_point: format!("U+{:04X}", c as u32),
+                        name: name.to_string(),
+                        line,
+                        column,
+                        action: *action,
+                    });
+                }),
+                None => None,
+            };
+
+            match outcome {
+                Some((_, Some(replacement))) => scrubbed.push_str(replacement),
+                Some((_, None)) | None => scrubbed.push(c),
+            }
+        }
+
+        let normalized: String = match self.normalization {
+            NormalizationForm::None => scrubbed.clone(),
+            NormalizationForm::Nfc => scrubbed.nfc().collect(),
+            NormalizationForm::Nfkc => scrubbed.nfkc().collect(),
+        };
+        report.normalization_changed = normalized != scrubbed;
+
+        (normalized, report)
+    }
+
+    // Decide what happens to a classified character: None leaves it untouched and
+    // unreported, otherwise the replacement (None keeps the original character)
+    fn handle(
+        &self,
+        category: UnicodeCategory,
+        c: char,
+    ) -> Option<(FindingAction, Option<&'static str>)> {
+        match category {
+            UnicodeCategory::ZeroWidth
+            | UnicodeCategory::ByteOrderMark
+            | UnicodeCategory::SoftHyphen => action_for(self.invisible),
+            UnicodeCategory::LineSeparator => match self.invisible {
+                CharacterAction::Remove => Some((FindingAction::Replaced, Some("\n"))),
+                action => action_for(action),
+            },
+            UnicodeCategory::BidiControl => action_for(self.bidi_controls),
+            UnicodeCategory::SpecialSpace if self.replace_special_spaces => {
+                Some((FindingAction::Replaced, Some(" ")))
+            }
+            UnicodeCategory::Punctuation if self.ascii_punctuation => {
+                ascii_equivalent(c).map(|ascii| (FindingAction::Replaced, Some(ascii)))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn action_for(action: CharacterAction) -> Option<(FindingAction, Option<&'static str>)> {
+    match action {
+        CharacterAction::Remove => Some((FindingAction::Removed, Some(""))),
+        CharacterAction::Report => Some((FindingAction::Reported, None)),
+        CharacterAction::Keep => None,
+    }
+}
+
+fn classify(c: char) -> Option<(UnicodeCategory, &'static str)> {
+    use UnicodeCategory::*;
+
+    let classified = match c {
+        '\u{200B}' => (ZeroWidth, "ZERO WIDTH SPACE"),
+        '\u{200C}' => (ZeroWidth, "ZERO WIDTH NON-JOINER"),
+        '\u{200D}' => (ZeroWidth, "ZERO WIDTH JOINER"),
+        '\u{2060}' => (ZeroWidth, "WORD JOINER"),
+        '\u{180E}' => (ZeroWidth, "MONGOLIAN VOWEL SEPARATOR"),
+        '\u{2061}' => (ZeroWidth, "FUNCTION APPLICATION"),
+        '\u{2062}' => (ZeroWidth, "INVISIBLE TIMES"),
+        '\u{2063}' => (ZeroWidth, "INVISIBLE SEPARATOR"),
+        '\u{2064}' => (ZeroWidth, "INVISIBLE PLUS"),
+        '\u{FEFF}' => (ByteOrderMark, "ZERO WIDTH NO-BREAK SPACE (BOM)"),
+        '\u{00AD}' => (SoftHyphen, "SOFT HYPHEN"),
+        '\u{2028}' => (LineSeparator, "LINE SEPARATOR"),
+        '\u{2029}' => (LineSeparator, "PARAGRAPH SEPARATOR"),
+        '\u{202A}' => (BidiControl, "LEFT-TO-RIGHT EMBEDDING"),
+        '\u{202B}' => (BidiControl, "RIGHT-TO-LEFT EMBEDDING"),
+        '\u{202C}' => (BidiControl, "POP DIRECTIONAL FORMATTING"),
+        '\u{202D}' => (BidiControl, "LEFT-TO-RIGHT OVERRIDE"),
+        '\u{202E}' => (BidiControl, "RIGHT-TO-LEFT OVERRIDE"),
+        '\u{2066}' => (BidiControl, "LEFT-TO-RIGHT ISOLATE"),
+        '\u{2067}' => (BidiControl, "RIGHT-TO-LEFT ISOLATE"),
+        '\u{2068}' => (BidiControl, "FIRST STRONG ISOLATE"),
+        '\u{2069}' => (BidiControl, "POP DIRECTIONAL ISOLATE"),
+        '\u{200E}' => (BidiControl, "LEFT-TO-RIGHT MARK"),
+        '\u{200F}' => (BidiControl, "RIGHT-TO-LEFT MARK"),
+        '\u{061C}' => (BidiControl, "ARABIC LETTER MARK"),
+        '\u{00A0}' => (SpecialSpace, "NO-BREAK SPACE"),
+        '\u{2000}'..='\u{200A}' => (SpecialSpace, "FIXED-WIDTH SPACE"),
+        '\u{202F}' => (SpecialSpace, "NARROW NO-BREAK SPACE"),
+        '\u{205F}' => (SpecialSpace, "MEDIUM MATHEMATICAL SPACE"),
+        '\u{2018}'..='\u{201B}' => (Punctuation, "SINGLE QUOTATION MARK"),
+        '\u{201C}'..='\u{201F}' => (Punctuation, "DOUBLE QUOTATION MARK"),
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => (Punctuation, "DASH"),
+        '\u{2026}' => (Punctuation, "HORIZONTAL ELLIPSIS"),
+        _ => return None,
+    };
+
+    Some(classified)
+}
+
+fn ascii_equivalent(c: char) -> Option<&'static str> {
+    match c {
+        '\u{2018}'..='\u{201B}' => Some("'"),
+        '\u{201C}'..='\u{201F}' => Some("\""),
+        '\u{2010}'..='\u{2013}' | '\u{2212}' => Some("-"),
+        '\u{2014}' | '\u{2015}' => Some("--"),
+        '\u{2026}' => Some("..."),
+        _ => None,
+    }
+}
+
+fn is_pictographic(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF) || c == '\u{FE0F}'
+}
+
+fn is_emoji_joiner(chars: &[char], index: usize) -> bool {
+    index > 0
+        && chars
+            .get(index + 1)
+            .is_some_and(|next| is_pictographic(*next))
+        && is_pictographic(chars[index - 1])
+}
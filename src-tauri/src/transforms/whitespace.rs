@@ -0,0 +1,238 @@
+use super::Transform;
+use serde::{Deserialize, Serialize};
+
+// Convert Windows (\r\n) and classic Mac (\r) line endings to Unix (\n)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NormalizeLineEndings;
+
+impl Transform for NormalizeLineEndings {
+    fn name(&self) -> &'static str {
+        "normalize_line_endings"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        Ok(text.replace("\r\n", "\n").replace('\r', "\n"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TabMode {
+    // Replace every tab with a single space
+    #[default]
+    Space,
+    // Expand tabs to spaces up to the next tab stop
+    Expand,
+    // Leave tabs untouched
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tabs {
+    pub mode: TabMode,
+    pub tab_width: usize,
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Tabs {
+            mode: TabMode::Space,
+            tab_width: 4,
+        }
+    }
+}
+
+impl Transform for Tabs {
+    fn name(&self) -> &'static str {
+        "tabs"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        match self.mode {
+            TabMode::Keep => Ok(text.to_string()),
+            TabMode::Space => Ok(text.replace('\t', " ")),
+            TabMode::Expand => Ok(expand_tabs(text, self.tab_width.max(1))),
+        }
+    }
+}
+
+fn expand_tabs(text: &str, tab_width: usize) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut column = 0;
+
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let padding = tab_width - (column % tab_width);
+                result.extend(std::iter::repeat_n(' ', padding));
+                column += padding;
+            }
+            '\n' => {
+                result.push(c);
+                column = 0;
+            }
+            _ => {
+                result.push(c);
+                column += 1;
+            }
+        }
+    }
+
+    result
+}
+
+// Collapse runs of spaces and tabs into a single space
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollapseSpaces {
+    // Leave the leading whitespace of each line untouched
+    pub preserve_indentation: bool,
+}
+
+impl Transform for CollapseSpaces {
+    fn name(&self) -> &'static str {
+        "collapse_spaces"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let lines: Vec<String> = text
+            .split('\n')
+            .map(|line| {
+                let (indent, body) = if self.preserve_indentation {
+                    line.split_at(line.len() - line.trim_start_matches([' ', '\t']).len())
+                } else {
+                    ("", line)
+                };
+                format!("{}{}", indent, collapse_horizontal_whitespace(body))
+            })
+            .collect();
+
+        Ok(lines.join("\n"))
+    }
+}
+
+fn collapse_horizontal_whitespace(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == ' ' || c == '\t' {
+            let mut run_length = 1;
+            while matches!(chars.peek(), Some(' ') | Some('\t')) {
+                chars.next();
+                run_length += 1;
+            }
+            // A lone separator keeps its original character
+            result.push(if run_length == 1 { c } else { ' ' });
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrimLines {
+    pub leading: bool,
+    pub trailing: bool,
+}
+
+impl Default for TrimLines {
+    fn default() -> Self {
+        TrimLines {
+            leading: true,
+            trailing: true,
+        }
+    }
+}
+
+impl Transform for TrimLines {
+    fn name(&self) -> &'static str {
+        "trim_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let lines: Vec<&str> = text
+            .split('\n')
+            .map(|line| match (self.leading, self.trailing) {
+                (true, true) => line.trim(),
+                (true, false) => line.trim_start(),
+                (false, true) => line.trim_end(),
+                (false, false) => line,
+            })
+            .collect();
+
+        Ok(lines.join("\n"))
+    }
+}
+
+// Cap the number of consecutive blank lines
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitBlankLines {
+    pub max_blank_lines: usize,
+}
+
+impl Default for LimitBlankLines {
+    fn default() -> Self {
+        LimitBlankLines { max_blank_lines: 1 }
+    }
+}
+
+impl Transform for LimitBlankLines {
+    fn name(&self) -> &'static str {
+        "limit_blank_lines"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        let mut lines = Vec::new();
+        let mut blank_run = 0;
+
+        for line in text.split('\n') {
+            if line.trim().is_empty() {
+                blank_run += 1;
+                if blank_run > self.max_blank_lines {
+                    continue;
+                }
+            } else {
+                blank_run = 0;
+            }
+            lines.push(line);
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+// Strip whitespace from the start and end of the whole text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrimText {
+    // Only drop leading blank lines so the first line keeps its indentation
+    pub preserve_indentation: bool,
+}
+
+impl Transform for TrimText {
+    fn name(&self) -> &'static str {
+        "trim_text"
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        if !self.preserve_indentation {
+            return Ok(text.trim().to_string());
+        }
+
+        let first_content_line = text.split('\n').position(|line| !line.trim().is_empty());
+
+        match first_content_line {
+            Some(index) => {
+                let body: Vec<&str> = text.split('\n').skip(index).collect();
+                Ok(body.join("\n").trim_end().to_string())
+            }
+            None => Ok(String::new()),
+        }
+    }
+}
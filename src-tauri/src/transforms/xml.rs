@@ -0,0 +1,219 @@
+// XML pretty-printing and minifying on top of quick-xml's pull parser. Markup is
+// re-emitted from the raw source so attributes, entities and namespaces survive
+// untouched; only the whitespace between nodes changes.
+
+use super::data::DataError;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+enum XmlNode {
+    Element {
+        // Everything between "<" and ">" of the start tag
+        tag: String,
+        name: String,
+        children: Vec<XmlNode>,
+        self_closing: bool,
+    },
+    Text(String),
+    // Comments, CDATA, processing instructions and declarations, emitted verbatim
+    Markup {
+        text: String,
+        inline: bool,
+    },
+}
+
+// Re-indent `text` with `indent` spaces per level, or put it on one line when None
+pub fn format(text: &str, indent: Option<usize>) -> Result<String, DataError> {
+    let nodes = parse(text)?;
+    let mut output = String::with_capacity(text.len());
+    for node in &nodes {
+        write_node(&mut output, node, indent, 0);
+    }
+    Ok(output.trim_end_matches('\n').to_string())
+}
+
+fn parse(text: &str) -> Result<Vec<XmlNode>, DataError> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().check_end_names = true;
+
+    // Open elements with the byte offset of their start tag
+    let mut stack: Vec<(XmlNode, usize)> = Vec::new();
+    let mut roots: Vec<XmlNode> = Vec::new();
+    let mut seen_root = false;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = reader.read_event().map_err(|e| {
+            DataError::at_offset(text, reader.error_position() as usize, e.to_string())
+        })?;
+
+        let node = match event {
+            Event::Eof => break,
+            Event::Start(tag) => {
+                let raw = decode(&tag);
+                let name = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+                stack.push((
+                    XmlNode::Element {
+                        tag: collapse_whitespace(&raw),
+                        name,
+                        children: Vec::new(),
+                        self_closing: false,
+                    },
+                    start,
+                ));
+                continue;
+            }
+            Event::End(_) => match stack.pop() {
+                Some((element, _)) => element,
+                None => return Err(DataError::at_offset(text, start, "unexpected closing tag")),
+            },
+            Event::Empty(tag) => XmlNode::Element {
+                tag: collapse_whitespace(&decode(&tag)),
+                name: String::from_utf8_lossy(tag.name().as_ref()).into_owned(),
+                children: Vec::new(),
+                self_closing: true,
+            },
+            Event::Text(content) => {
+                let raw = decode(&content);
+                let trimmed = raw.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if stack.is_empty() {
+                    return Err(DataError::at_offset(
+                        text,
+                        start + (raw.len() - raw.trim_start().len()),
+                        "text outside the root element",
+                    ));
+                }
+                XmlNode::Text(trimmed.to_string())
+            }
+            Event::CData(content) => XmlNode::Markup {
+                text: format!("<![CDATA[{}]]>", decode(&content)),
+                inline: true,
+            },
+            Event::Comment(content) => XmlNode::Markup {
+                text: format!("<!--{}-->", decode(&content)),
+                inline: false,
+            },
+            Event::Decl(content) => XmlNode::Markup {
+                text: format!("<?{}?>", decode(&content)),
+                inline: false,
+            },
+            Event::PI(content) => XmlNode::Markup {
+                text: format!("<?{}?>", decode(&content)),
+                inline: false,
+            },
+            Event::DocType(content) => XmlNode::Markup {
+                text: format!("<!DOCTYPE {}>", decode(&content).trim()),
+                inline: false,
+            },
+        };
+
+        match stack.last_mut() {
+            Some((XmlNode::Element { children, .. }, _)) => children.push(node),
+            _ => {
+                if matches!(node, XmlNode::Element { .. }) {
+                    if seen_root {
+                        return Err(DataError::at_offset(text, start, "multiple root elements"));
+                    }
+                    seen_root = true;
+                }
+                roots.push(node);
+            }
+        }
+    }
+
+    if let Some((XmlNode::Element { name, .. }, start)) = stack.last() {
+        return Err(DataError::at_offset(
+            text,
+            *start,
+            format!("element <{}> is never closed", name),
+        ));
+    }
+    if !seen_root {
+        return Err(DataError::new(1, 1, "no root element"));
+    }
+    Ok(roots)
+}
+
+fn decode(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// Collapse line breaks and runs of spaces between attributes, leaving quoted values alone
+fn collapse_whitespace(tag: &str) -> String {
+    let mut output = String::with_capacity(tag.len());
+    let mut quote = None;
+    let mut pending_space = false;
+    for c in tag.trim_end().chars() {
+        match quote {
+            Some(q) => {
+                output.push(c);
+                if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => pending_space = true,
+            None => {
+                if pending_space {
+                    output.push(' ');
+                    pending_space = false;
+                }
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+                output.push(c);
+            }
+        }
+    }
+    output
+}
+
+fn write_node(output: &mut String, node: &XmlNode, indent: Option<usize>, depth: usize) {
+    let pad = indent.map(|i| " ".repeat(i * depth)).unwrap_or_default();
+    let newline = if indent.is_some() { "\n" } else { "" };
+
+    match node {
+        XmlNode::Element {
+            tag,
+            name,
+            children,
+            self_closing,
+        } => {
+            output.push_str(&pad);
+            if *self_closing {
+                output.push_str(&format!("<{}/>{}", tag, newline));
+                return;
+            }
+
+            // Text-only elements stay on one line: <name>value</name>
+            let inline = children.iter().all(|child| match child {
+                XmlNode::Text(_) => true,
+                XmlNode::Markup { inline, .. } => *inline,
+                XmlNode::Element { .. } => false,
+            });
+            output.push_str(&format!("<{}>", tag));
+            if inline {
+                for child in children {
+                    match child {
+                        XmlNode::Text(text) | XmlNode::Markup { text, .. } => output.push_str(text),
+                        XmlNode::Element { .. } => {}
+                    }
+                }
+            } else {
+                output.push_str(newline);
+                for child in children {
+                    write_node(output, child, indent, depth + 1);
+                }
+                output.push_str(&pad);
+            }
+            output.push_str(&format!("</{}>{}", name, newline));
+        }
+        XmlNode::Text(text) | XmlNode::Markup { text, .. } => {
+            output.push_str(&pad);
+            output.push_str(text);
+            output.push_str(newline);
+        }
+    }
+}
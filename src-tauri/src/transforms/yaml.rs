@@ -0,0 +1,153 @@
+// YAML goes through serde_yaml_ng and is converted to the JSON tree the other data
+// transforms share. Anchors, aliases and merge keys are resolved, tags are dropped
+// and every document of a multi-document stream is kept. A `<<` key holding a mapping
+// is always read as a merge key, so such a key doesn't survive a round trip.
+
+use super::data::DataError;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use serde_yaml_ng::Value as YamlValue;
+
+// The documents of a YAML stream; empty input is a single null document
+pub fn parse(text: &str) -> Result<Vec<Value>, DataError> {
+    let mut documents = Vec::new();
+    for document in serde_yaml_ng::Deserializer::from_str(text) {
+        let mut value = YamlValue::deserialize(document).map_err(yaml_error)?;
+        merge_keys(&mut value);
+        documents.push(to_json_value(value));
+    }
+    if documents.is_empty() {
+        documents.push(Value::Null);
+    }
+    Ok(documents)
+}
+
+// A single document as it is, several as a list of documents
+pub fn parse_value(text: &str) -> Result<Value, DataError> {
+    let mut documents = parse(text)?;
+    Ok(if documents.len() == 1 {
+        documents.remove(0)
+    } else {
+        Value::Array(documents)
+    })
+}
+
+pub fn to_yaml(documents: &[Value]) -> String {
+    documents
+        .iter()
+        .map(|document| serde_yaml_ng::to_string(&to_yaml_value(document)).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("---\n")
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+// Resolve `<<` merge keys. serde_yaml_ng's own merge can't tell a quoted '<<' from a
+// merge key and rejects one holding anything but mappings, so `<<` is only merged when
+// it holds a mapping or a list of them and is an ordinary key otherwise. Keys the
+// mapping already has win over merged ones, and earlier mappings over later ones.
+fn merge_keys(value: &mut YamlValue) {
+    match value {
+        YamlValue::Sequence(items) => items.iter_mut().for_each(merge_keys),
+        YamlValue::Tagged(tagged) => merge_keys(&mut tagged.value),
+        YamlValue::Mapping(entries) => {
+            entries.values_mut().for_each(merge_keys);
+            let sources: Vec<_> = match entries.get("<<") {
+                Some(YamlValue::Mapping(source)) => vec![source.clone()],
+                Some(YamlValue::Sequence(items)) if items.iter().all(YamlValue::is_mapping) => {
+                    items
+                        .iter()
+                        .filter_map(YamlValue::as_mapping)
+                        .cloned()
+                        .collect()
+                }
+                _ => return,
+            };
+            entries.shift_remove("<<");
+            for (key, value) in sources.into_iter().flatten() {
+                if !entries.contains_key(&key) {
+                    entries.insert(key, value);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn yaml_error(e: serde_yaml_ng::Error) -> DataError {
+    let message = e.to_string();
+    let message = match message.find(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    match e.location() {
+        Some(location) => DataError::new(location.line(), location.column(), message),
+        None => DataError::new(1, 1, message),
+    }
+}
+
+fn to_json_value(value: YamlValue) -> Value {
+    match value {
+        YamlValue::Null => Value::Null,
+        YamlValue::Bool(value) => Value::Bool(value),
+        YamlValue::Number(number) => {
+            let converted = if let Some(value) = number.as_i64() {
+                Some(Number::from(value))
+            } else if let Some(value) = number.as_u64() {
+                Some(Number::from(value))
+            } else {
+                number.as_f64().and_then(Number::from_f64)
+            };
+            // JSON has no infinity or NaN
+            converted.map_or_else(|| Value::String(number.to_string()), Value::Number)
+        }
+        YamlValue::String(value) => Value::String(value),
+        YamlValue::Sequence(items) => Value::Array(items.into_iter().map(to_json_value).collect()),
+        YamlValue::Mapping(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key_text(key), to_json_value(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        YamlValue::Tagged(tagged) => to_json_value(tagged.value),
+    }
+}
+
+// JSON keys are strings, so other keys are written the way YAML would write them
+fn key_text(key: YamlValue) -> String {
+    match key {
+        YamlValue::Null => String::new(),
+        YamlValue::String(key) => key,
+        YamlValue::Tagged(tagged) => key_text(tagged.value),
+        key => serde_yaml_ng::to_string(&key)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    }
+}
+
+fn to_yaml_value(value: &Value) -> YamlValue {
+    match value {
+        Value::Null => YamlValue::Null,
+        Value::Bool(value) => YamlValue::Bool(*value),
+        Value::Number(number) => {
+            if let Some(value) = number.as_i64() {
+                YamlValue::from(value)
+            } else if let Some(value) = number.as_u64() {
+                YamlValue::from(value)
+            } else {
+                number
+                    .as_f64()
+                    .map_or_else(|| YamlValue::String(number.to_string()), YamlValue::from)
+            }
+        }
+        Value::String(value) => YamlValue::String(value.clone()),
+        Value::Array(items) => YamlValue::Sequence(items.iter().map(to_yaml_value).collect()),
+        Value::Object(entries) => YamlValue::Mapping(
+            entries
+                .iter()
+                .map(|(key, value)| (YamlValue::String(key.clone()), to_yaml_value(value)))
+                .collect(),
+        ),
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Clipboard writes kept for undo; older ones are dropped
+pub const MAX_UNDO_STEPS: usize = 50;
+
+// One clipboard write made by the app
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardChange {
+    // What wrote the clipboard: "cleanup", "transform", "paste", ...
+    pub label: String,
+    pub before: String,
+    pub after: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ClipboardChange {
+    pub fn new(label: &str, before: String, after: String) -> Self {
+        Self {
+            label: label.to_string(),
+            before,
+            after,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoStatus {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ClipboardUndoStack {
+    undo: VecDeque<ClipboardChange>,
+    redo: Vec<ClipboardChange>,
+    // Text last put back by undo or redo, so the clipboard monitor doesn't clean it again
+    restored: Option<String>,
+}
+
+impl ClipboardUndoStack {
+    // A new write starts a new branch of history, so anything undone can't be redone
+    pub fn record(&mut self, change: ClipboardChange) {
+        if change.before == change.after {
+            return;
+        }
+        self.undo.push_back(change);
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+        self.restored = None;
+    }
+
+    // The change an undo would reverse; call `finish_undo` once its `before` is written
+    pub fn next_undo(&self) -> Option<&ClipboardChange> {
+        self.undo.back()
+    }
+
+    pub fn finish_undo(&mut self) -> Option<ClipboardChange> {
+        let change = self.undo.pop_back()?;
+        self.restored = Some(change.before.clone());
+        self.redo.push(change.clone());
+        Some(change)
+    }
+
+    // The change a redo would reapply; call `finish_redo` once its `after` is written
+    pub fn next_redo(&self) -> Option<&ClipboardChange> {
+        self.redo.last()
+    }
+
+    pub fn finish_redo(&mut self) -> Option<ClipboardChange> {
+        let change = self.redo.pop()?;
+        self.restored = Some(change.after.clone());
+        self.undo.push_back(change.clone());
+        Some(change)
+    }
+
+    // Forget every change, e.g. once the clipboard no longer matches the recorded writes
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.restored = None;
+    }
+
+    pub fn is_restored(&self, text: &str) -> bool {
+        self.restored.as_deref() == Some(text)
+    }
+
+    pub fn status(&self) -> UndoStatus {
+        UndoStatus {
+            can_undo: !self.undo.is_empty(),
+            can_redo: !self.redo.is_empty(),
+            undo_label: self.undo.back().map(|change| change.label.clone()),
+            redo_label: self.redo.last().map(|change| change.label.clone()),
+        }
+    }
+}
+
+pub type ClipboardUndoState = Arc<RwLock<ClipboardUndoStack>>;
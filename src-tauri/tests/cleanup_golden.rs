@@ -0,0 +1,88 @@
+// Golden corpus for the built-in cleanup presets.
+//
+// Each directory under `tests/golden/` is named after a preset. Every
+// `<sample>.txt` in it is cleaned with that preset and compared against
+// `<sample>.expected.txt`. Run with `UPDATE_GOLDEN=1` to regenerate the
+// expected files after an intentional behaviour change.
+
+use refine_lib::CleanupPresetStore;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+}
+
+fn samples(preset_dir: &Path) -> Vec<PathBuf> {
+    let mut samples: Vec<PathBuf> = fs::read_dir(preset_dir)
+        .expect("preset directory is readable")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.ends_with(".txt") && !name.ends_with(".expected.txt")
+        })
+        .collect();
+    samples.sort();
+    samples
+}
+
+#[test]
+fn golden_corpus_matches_expected_output() {
+    let store = CleanupPresetStore::default();
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+    let mut checked = 0;
+
+    for entry in fs::read_dir(golden_dir()).expect("golden directory exists") {
+        let preset_dir = entry.expect("readable entry").path();
+        if !preset_dir.is_dir() {
+            continue;
+        }
+
+        let preset_name = preset_dir
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let pipeline = store
+            .pipeline_for(Some(&preset_name))
+            .unwrap_or_else(|e| panic!("golden directory has no matching preset: {}", e));
+
+        for sample in samples(&preset_dir) {
+            let input = fs::read_to_string(&sample).expect("sample is readable");
+            let actual = pipeline.run(&input).expect("cleanup succeeds");
+            let expected_path = sample.with_extension("expected.txt");
+
+            if update {
+                fs::write(&expected_path, &actual).expect("expected file is writable");
+                continue;
+            }
+
+            let expected = fs::read_to_string(&expected_path).unwrap_or_else(|_| {
+                panic!(
+                    "missing {}, run with UPDATE_GOLDEN=1",
+                    expected_path.display()
+                )
+            });
+            if actual != expected {
+                failures.push(format!(
+                    "{}/{}\n--- expected ---\n{}\n--- actual ---\n{}",
+                    preset_name,
+                    sample.file_name().unwrap().to_string_lossy(),
+                    expected,
+                    actual
+                ));
+            }
+            checked += 1;
+        }
+    }
+
+    assert!(update || checked > 0, "golden corpus is empty");
+    assert!(
+        failures.is_empty(),
+        "golden mismatches:\n\n{}",
+        failures.join("\n\n")
+    );
+}
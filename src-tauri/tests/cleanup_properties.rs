@@ -0,0 +1,138 @@
+// Property-based invariants for the cleanup engine.
+//
+// Every built-in preset must be idempotent and handle arbitrary Unicode without
+// panicking. Whitespace-only presets must also never drop or reorder
+// non-whitespace characters.
+
+mod common;
+
+use common::messy_text;
+use proptest::prelude::*;
+use refine_lib::{cleanup_text, CleanupPresetStore, ClipboardEntry, Pipeline};
+
+fn preset_pipeline(name: &str) -> Pipeline {
+    CleanupPresetStore::default()
+        .pipeline_for(Some(name))
+        .expect("built-in preset exists")
+}
+
+fn builtin_pipelines() -> Vec<(String, Pipeline)> {
+    CleanupPresetStore::default()
+        .all()
+        .into_iter()
+        .map(|preset| (preset.name, preset.pipeline))
+        .collect()
+}
+
+// Presets that only touch whitespace
+const WHITESPACE_PRESETS: &[&str] = &["default", "preserve_indentation"];
+
+fn non_whitespace(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+proptest! {
+    #[test]
+    fn cleanup_is_idempotent(text in messy_text()) {
+        for (name, pipeline) in builtin_pipelines() {
+            let once = pipeline.run(&text).unwrap();
+            let twice = pipeline.run(&once).unwrap();
+            prop_assert_eq!(&once, &twice, "preset '{}' is not idempotent", name);
+        }
+    }
+
+    #[test]
+    fn cleanup_never_loses_content(text in messy_text()) {
+        for name in WHITESPACE_PRESETS {
+            let cleaned = preset_pipeline(name).run(&text).unwrap();
+            prop_assert_eq!(
+                non_whitespace(&cleaned),
+                non_whitespace(&text),
+                "preset '{}' changed non-whitespace content",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn reflow_only_removes_quote_markers_and_hyphens(text in messy_text()) {
+        let reflowed = preset_pipeline("reflow").run(&text).unwrap();
+        let strip = |s: &str| -> String {
+            non_whitespace(s).chars().filter(|c| *c != '>' && *c != '-').collect()
+        };
+        prop_assert_eq!(strip(&reflowed), strip(&text));
+    }
+
+    #[test]
+    fn default_preset_output_is_normalized(text in messy_text()) {
+        let cleaned = preset_pipeline("default").run(&text).unwrap();
+
+        prop_assert!(!cleaned.contains('\r'));
+        prop_assert!(!cleaned.contains("\n\n\n"));
+        prop_assert!(!cleaned.contains("  "));
+        prop_assert!(!cleaned.contains('\t'));
+        prop_assert_eq!(cleaned.trim(), cleaned.as_str());
+        for line in cleaned.lines() {
+            prop_assert_eq!(line.trim(), line);
+        }
+    }
+
+    #[test]
+    fn preserve_indentation_keeps_leading_whitespace(
+        indent in "[ \t]{0,8}",
+        body in "[a-z]{1,10}( [a-z]{1,10}){0,3}",
+    ) {
+        let text = format!("first\n{}{}", indent, body);
+        let cleaned = preset_pipeline("preserve_indentation").run(&text).unwrap();
+        prop_assert_eq!(cleaned, text);
+    }
+
+    #[test]
+    fn single_character_survives_cleanup(c in any::<char>()) {
+        let text = c.to_string();
+        let expected = if c.is_whitespace() { String::new() } else { text.clone() };
+        prop_assert_eq!(cleanup_text(&CleanupPresetStore::default(), &text), expected);
+    }
+
+    #[test]
+    fn clipboard_entry_handles_any_unicode(text in messy_text()) {
+        let entry = ClipboardEntry::new(text.clone(), false, None);
+
+        prop_assert_eq!(entry.char_count, text.chars().count());
+        prop_assert!(entry.preview.chars().count() <= 100);
+        let kept = entry.preview.trim_end_matches("...");
+        prop_assert!(text.starts_with(kept));
+    }
+}
+
+#[test]
+fn crlf_is_converted_without_duplicating_last_character() {
+    let store = CleanupPresetStore::default();
+    assert_eq!(
+        cleanup_text(&store, "line one\r\nline two\r\n"),
+        "line one\nline two"
+    );
+    assert_eq!(cleanup_text(&store, "a\r\n"), "a");
+    assert_eq!(cleanup_text(&store, "ab"), "ab");
+    assert_eq!(cleanup_text(&store, "x  "), "x");
+}
+
+#[test]
+fn blank_line_runs_are_capped() {
+    let store = CleanupPresetStore::default();
+    assert_eq!(cleanup_text(&store, "a\n\n\n\n\n\nb"), "a\n\nb");
+    assert_eq!(cleanup_text(&store, "a\n \n\t\n \nb"), "a\n\nb");
+}
+
+#[test]
+fn cleanup_uses_the_stores_default_preset() {
+    let list = "Shopping list\n    milk  \n    eggs";
+    let mut store = CleanupPresetStore::default();
+    assert_eq!(cleanup_text(&store, list), "Shopping list\nmilk\neggs");
+
+    store.set_default("preserve_indentation").unwrap();
+    assert_eq!(
+        cleanup_text(&store, list),
+        "Shopping list\n    milk\n    eggs"
+    );
+}
@@ -0,0 +1,83 @@
+// Tests for code cleanup and for telling code from prose.
+
+use refine_lib::{cleanup_text, detect_content_type, CleanupPresetStore, ClipboardEntry, Pipeline};
+
+fn preset_pipeline(name: &str) -> Pipeline {
+    CleanupPresetStore::default()
+        .pipeline_for(Some(name))
+        .expect("built-in preset exists")
+}
+
+#[test]
+fn code_is_cleaned_without_losing_indentation() {
+    let pasted = "    1 | def greet(name):\r\n    2 | \tif name:   \r\n    3 |         return f\"hi {name}\"\r\n";
+    assert_eq!(
+        cleanup_text(&CleanupPresetStore::default(), pasted),
+        "def greet(name):\n    if name:\n        return f\"hi {name}\""
+    );
+}
+
+#[test]
+fn shell_prompts_are_stripped() {
+    let pasted = "$ cargo build --release\n$ ./target/release/app --verbose";
+    let cleaned = preset_pipeline("code").run(pasted).unwrap();
+    assert_eq!(
+        cleaned,
+        "cargo build --release\n./target/release/app --verbose"
+    );
+}
+
+#[test]
+fn prompts_are_only_stripped_from_transcripts() {
+    // "% " comments in LaTeX or MATLAB are not prompts
+    let latex = "% preamble\n\\documentclass{article}\n\\begin{document}\n% greeting\nHello\n\\end{document}";
+    assert_eq!(preset_pipeline("code").run(latex).unwrap(), latex);
+
+    // Indentation in front of a prompt is kept
+    let pasted = "$ cd app\n  $ make build\n$ make test";
+    assert_eq!(
+        preset_pipeline("code").run(pasted).unwrap(),
+        "cd app\n  make build\nmake test"
+    );
+}
+
+#[test]
+fn numbered_prose_lists_are_not_treated_as_code() {
+    let list = "1. Buy milk\n2. Call the bank\n3. Book flights";
+    assert_eq!(cleanup_text(&CleanupPresetStore::default(), list), list);
+    assert_eq!(
+        ClipboardEntry::new(list.to_string(), false, None).content_type,
+        "text"
+    );
+}
+
+#[test]
+fn prose_starting_with_keywords_is_not_code() {
+    let prose =
+        "@sam can you check this?\nimport the list before friday\nfrom now on we use the new form";
+    assert_eq!(detect_content_type(prose), "text");
+    let prices = "$ 20 for the ticket\n$ 5 for parking";
+    assert_eq!(detect_content_type(prices), "text");
+
+    let python =
+        "from os import path\n\n@dataclass\nclass Point:\n    x = 0\n    # unset\n    y = 0";
+    assert_eq!(detect_content_type(python), "code");
+    assert_eq!(detect_content_type("$ cargo build\n$ ls -la"), "code");
+
+    // Cleanup without a preset only switches to the code preset while detection is on
+    let code = "fn main() {\n\tprintln!(\"hi\");   \n}";
+    let mut presets = CleanupPresetStore::default();
+    let detected = presets
+        .pipeline_for_text(None, code)
+        .unwrap()
+        .run(code)
+        .unwrap();
+    assert_eq!(detected, "fn main() {\n    println!(\"hi\");\n}");
+    presets.auto_detect_code = false;
+    let standard = presets
+        .pipeline_for_text(None, code)
+        .unwrap()
+        .run(code)
+        .unwrap();
+    assert_ne!(standard, detected);
+}
@@ -0,0 +1,49 @@
+// Strategies shared by the property tests.
+
+use proptest::prelude::*;
+
+// Text biased towards the characters cleanup cares about: spaces, tabs, every
+// line-ending flavour, Unicode whitespace, multi-byte characters and Markdown
+// and HTML markup.
+pub fn messy_text() -> impl Strategy<Value = String> {
+    let piece = prop_oneof![
+        Just(" ".to_string()),
+        Just("  ".to_string()),
+        Just("\t".to_string()),
+        Just("\n".to_string()),
+        Just("\r\n".to_string()),
+        Just("\r".to_string()),
+        Just("\n\n\n".to_string()),
+        Just("\u{a0}".to_string()),
+        Just("\u{3000}".to_string()),
+        Just("é".to_string()),
+        Just("👩‍💻".to_string()),
+        Just("日本".to_string()),
+        Just("\u{200B}".to_string()),
+        Just("\u{202E}".to_string()),
+        Just("\u{FEFF}".to_string()),
+        Just("e\u{301}".to_string()),
+        Just("> ".to_string()),
+        Just("-\n".to_string()),
+        Just("\n- ".to_string()),
+        Just("\n1. ".to_string()),
+        Just("\n# ".to_string()),
+        Just("```".to_string()),
+        Just("\n* ".to_string()),
+        Just("\n    ".to_string()),
+        Just("| ".to_string()),
+        Just("<p>".to_string()),
+        Just("</p>".to_string()),
+        Just("<li>".to_string()),
+        Just("<b>".to_string()),
+        Just("&amp;".to_string()),
+        Just("SELECT ".to_string()),
+        Just("{\"a\": ".to_string()),
+        Just("}".to_string()),
+        Just("'".to_string()),
+        Just("--".to_string()),
+        "[a-zA-Z0-9.,;:!?()-]{1,8}",
+        any::<char>().prop_map(|c| c.to_string()),
+    ];
+    prop::collection::vec(piece, 0..40).prop_map(|pieces| pieces.concat())
+}
@@ -0,0 +1,189 @@
+// Tests for the JSON, YAML, XML and SQL formatters.
+
+mod common;
+
+use common::messy_text;
+use proptest::prelude::*;
+use refine_lib::{CleanupPresetStore, DataAction, DataFormat, FormatData, Pipeline, Transform};
+
+fn preset_pipeline(name: &str) -> Pipeline {
+    CleanupPresetStore::default()
+        .pipeline_for(Some(name))
+        .expect("built-in preset exists")
+}
+
+fn format_data(format: DataFormat, action: DataAction, text: &str) -> Result<String, String> {
+    FormatData {
+        format,
+        action,
+        indent: 2,
+    }
+    .apply(text)
+}
+
+// Arbitrary JSON documents, including awkward keys and strings
+fn json_value() -> impl Strategy<Value = serde_json::Value> {
+    let leaf = prop_oneof![
+        Just(serde_json::Value::Null),
+        any::<bool>().prop_map(serde_json::Value::from),
+        any::<i64>().prop_map(serde_json::Value::from),
+        (-1e9f64..1e9).prop_map(serde_json::Value::from),
+        any::<String>().prop_map(serde_json::Value::from),
+        prop_oneof![
+            Just("yes"),
+            Just("null"),
+            Just("1.0"),
+            Just("- item"),
+            Just("a: b"),
+            Just(" #x"),
+            Just("[x]"),
+            Just("'q'"),
+            Just("line\nbreak"),
+            Just("..."),
+            Just("")
+        ]
+        .prop_map(serde_json::Value::from),
+    ];
+    leaf.prop_recursive(4, 48, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(serde_json::Value::Array),
+            // A `<<` key holding a mapping is a YAML merge key, so it can't round trip
+            prop::collection::btree_map("[a-z :#-]{0,6}|\\PC{0,4}", inner, 0..6).prop_map(|map| {
+                serde_json::Value::Object(map.into_iter().filter(|(key, _)| key != "<<").collect())
+            }),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn json_survives_pretty_printing_and_yaml_round_trips(value in json_value()) {
+        let minified = value.to_string();
+
+        let pretty = format_data(DataFormat::Json, DataAction::Pretty, &minified).unwrap();
+        prop_assert_eq!(format_data(DataFormat::Json, DataAction::Minify, &pretty).unwrap(), minified.clone());
+
+        let yaml = format_data(DataFormat::Json, DataAction::ToYaml, &minified).unwrap();
+        let back = format_data(DataFormat::Yaml, DataAction::Minify, &yaml)
+            .unwrap_or_else(|e| panic!("{}\n{}", e, yaml));
+        prop_assert_eq!(back, minified);
+    }
+
+    #[test]
+    fn sql_formatting_is_idempotent(text in messy_text()) {
+        let sql = format!("SELECT {}", text);
+        for action in [DataAction::Pretty, DataAction::Minify] {
+            let once = format_data(DataFormat::Sql, action, &sql).unwrap();
+            let twice = format_data(DataFormat::Sql, action, &once).unwrap();
+            prop_assert_eq!(once, twice);
+        }
+    }
+}
+
+#[test]
+fn json_errors_point_at_the_offending_line() {
+    let error = format_data(
+        DataFormat::Json,
+        DataAction::Pretty,
+        "{\n  \"a\": 1,\n  \"b\": }",
+    )
+    .unwrap_err();
+    assert_eq!(error, "Invalid JSON at line 3, column 8: expected value");
+}
+
+#[test]
+fn json_key_order_is_kept_unless_sorting() {
+    let json = r#"{"b": 1, "a": {"d": 2, "c": 3}}"#;
+    assert_eq!(
+        format_data(DataFormat::Auto, DataAction::Minify, json).unwrap(),
+        r#"{"b":1,"a":{"d":2,"c":3}}"#
+    );
+    assert_eq!(
+        format_data(DataFormat::Json, DataAction::SortKeys, json).unwrap(),
+        "{\n  \"a\": {\n    \"c\": 3,\n    \"d\": 2\n  },\n  \"b\": 1\n}"
+    );
+}
+
+#[test]
+fn json_numbers_are_formatted_as_written() {
+    let json =
+        r#"{"id": 12345678901234567890123, "price": 1.50, "big": 1e400, "b": 1, "a": [-0.0]}"#;
+    assert_eq!(
+        format_data(DataFormat::Json, DataAction::Minify, json).unwrap(),
+        r#"{"id":12345678901234567890123,"price":1.50,"big":1e400,"b":1,"a":[-0.0]}"#
+    );
+    assert_eq!(
+        format_data(DataFormat::Json, DataAction::SortKeys, json).unwrap(),
+        "{\n  \"a\": [\n    -0.0\n  ],\n  \"b\": 1,\n  \"big\": 1e400,\n  \"id\": 12345678901234567890123,\n  \"price\": 1.50\n}"
+    );
+}
+
+#[test]
+fn yaml_converts_to_json() {
+    let yaml = "# service\nname: refine\nports:\n  - 80\n  - \"443\"\nowner:\n  team: core # inline\nnotes: |\n  one\n  two\n";
+    assert_eq!(
+        format_data(DataFormat::Yaml, DataAction::Minify, yaml).unwrap(),
+        r#"{"name":"refine","ports":[80,"443"],"owner":{"team":"core"},"notes":"one\ntwo\n"}"#
+    );
+
+    // Aliases and merge keys are resolved, tags dropped and documents kept apart
+    let yaml =
+        "base: &base {retries: 3}\nprod:\n  <<: *base\n  host: !host example.com\n---\nsecond";
+    assert_eq!(
+        format_data(DataFormat::Yaml, DataAction::Minify, yaml).unwrap(),
+        r#"[{"base":{"retries":3},"prod":{"host":"example.com","retries":3}},"second"]"#
+    );
+    assert_eq!(
+        format_data(DataFormat::Yaml, DataAction::Pretty, yaml).unwrap(),
+        "base:\n  retries: 3\nprod:\n  host: example.com\n  retries: 3\n---\nsecond"
+    );
+    // Only a mapping or a list of mappings is merged
+    assert_eq!(
+        format_data(
+            DataFormat::Yaml,
+            DataAction::Minify,
+            "'<<': 1\nb: {<<: null}"
+        )
+        .unwrap(),
+        r#"{"<<":1,"b":{"<<":null}}"#
+    );
+    assert_eq!(
+        format_data(DataFormat::Yaml, DataAction::Pretty, "a: *ref").unwrap_err(),
+        "Invalid YAML at line 1, column 4: unknown anchor"
+    );
+}
+
+#[test]
+fn xml_is_reindented_and_unclosed_elements_are_reported() {
+    let xml = "<root a=\"1\"><item>one</item><item><sub/></item></root>";
+    assert_eq!(
+        format_data(DataFormat::Auto, DataAction::Pretty, xml).unwrap(),
+        "<root a=\"1\">\n  <item>one</item>\n  <item>\n    <sub/>\n  </item>\n</root>"
+    );
+    assert_eq!(
+        format_data(DataFormat::Xml, DataAction::Pretty, "<root>\n  <item>one\n</root>").unwrap_err(),
+        "Invalid XML at line 3, column 1: ill-formed document: expected `</item>`, but `</root>` was found"
+    );
+}
+
+#[test]
+fn sql_clauses_start_new_lines() {
+    let sql =
+        "select id, name from users where active = true and age between 18 and 65 order by name";
+    assert_eq!(
+        format_data(DataFormat::Auto, DataAction::Pretty, sql).unwrap(),
+        "SELECT id,\n  name\nFROM users\nWHERE active = TRUE\n  AND age BETWEEN 18 AND 65\nORDER BY name"
+    );
+    assert_eq!(
+        format_data(DataFormat::Sql, DataAction::Validate, "SELECT (1").unwrap_err(),
+        "Invalid SQL at line 1, column 8: unclosed '('"
+    );
+}
+
+#[test]
+fn undetected_text_passes_through_the_data_presets() {
+    let text = "just some notes: not data";
+    for preset in ["pretty_data", "minify_data"] {
+        assert_eq!(preset_pipeline(preset).run(text).unwrap(), text);
+    }
+}
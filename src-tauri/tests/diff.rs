@@ -0,0 +1,62 @@
+// Tests for diff hunks between original and cleaned content and reverting them.
+
+mod common;
+
+use common::messy_text;
+use proptest::prelude::*;
+use refine_lib::{cleanup_text, diff_texts, revert_hunks, ChangeKind, CleanupPresetStore};
+
+proptest! {
+    #[test]
+    fn reverting_hunks_restores_the_original(original in messy_text()) {
+        let cleaned = cleanup_text(&CleanupPresetStore::default(), &original);
+        let hunks = diff_texts(&original, &cleaned).hunks.len();
+        let all: Vec<usize> = (0..hunks).collect();
+        prop_assert_eq!(revert_hunks(&original, &cleaned, &all).unwrap(), original.clone());
+        prop_assert_eq!(revert_hunks(&original, &cleaned, &[]).unwrap(), cleaned);
+    }
+}
+
+#[test]
+fn diff_hunks_show_line_and_character_changes() {
+    let original = "Title  here\nkeep\n\n\n\nend   \n";
+    let cleaned = "Title here\nkeep\n\nend\n";
+    let diff = diff_texts(original, cleaned);
+    assert_eq!(diff.hunks.len(), 2);
+
+    let first = &diff.hunks[0];
+    assert_eq!((first.old_start, first.old_lines), (0, 1));
+    assert_eq!(first.lines[0].text, "Title  here");
+    assert_eq!(first.lines[1].kind, ChangeKind::Insert);
+    let deleted: Vec<&str> = first
+        .spans
+        .iter()
+        .filter(|span| span.kind == ChangeKind::Delete)
+        .map(|span| span.text.as_str())
+        .collect();
+    assert_eq!(deleted, vec![" "]);
+    assert_eq!(diff.deleted_chars, 6);
+    assert_eq!(diff.inserted_chars, 0);
+
+    assert_eq!(
+        revert_hunks(original, cleaned, &[1]).unwrap(),
+        "Title here\nkeep\n\n\n\nend   \n"
+    );
+    assert!(revert_hunks(original, cleaned, &[2]).is_err());
+}
+
+#[test]
+fn large_diffs_fall_back_to_one_hunk_between_the_unchanged_ends() {
+    let original: String = (0..12_000).map(|i| format!("line {}\n", i)).collect();
+    let cleaned: String = (0..12_000).map(|i| format!("{} line\n", i)).collect();
+    let original = format!("head\n{}tail\n", original);
+    let cleaned = format!("head\n{}tail\n", cleaned);
+
+    let diff = diff_texts(&original, &cleaned);
+    assert_eq!(diff.hunks.len(), 1);
+    let hunk = &diff.hunks[0];
+    assert_eq!((hunk.old_start, hunk.old_lines), (1, 12_000));
+    assert_eq!((hunk.new_start, hunk.new_lines), (1, 12_000));
+    assert_eq!(diff_texts(&original, &cleaned), diff);
+    assert_eq!(revert_hunks(&original, &cleaned, &[0]).unwrap(), original);
+}
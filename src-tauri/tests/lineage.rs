@@ -0,0 +1,119 @@
+// Tests for entry lineage: parent links, lineage trees and relinking on duplicates.
+
+use chrono::Duration;
+use refine_lib::{
+    entry_lineage, ClipboardEntry, ClipboardHistory, EntryLink, LineageNode, LineageOperation,
+};
+
+fn derived(
+    content: &str,
+    parent: &ClipboardEntry,
+    operation: LineageOperation,
+    minutes: i64,
+) -> ClipboardEntry {
+    let mut entry = ClipboardEntry::new(content.to_string(), true, Some(parent.content.clone()));
+    entry.timestamp = parent.timestamp + Duration::minutes(minutes);
+    entry.parent = Some(EntryLink::new(&parent.id, operation));
+    entry
+}
+
+fn child_contents(node: &LineageNode) -> Vec<String> {
+    node.children
+        .iter()
+        .map(|child| child.entry.content.clone())
+        .collect()
+}
+
+#[test]
+fn builds_the_tree_from_the_oldest_ancestor() {
+    let original = ClipboardEntry::new("  hello   world ".to_string(), false, None);
+    let cleaned = derived("hello world", &original, LineageOperation::Cleanup, 1);
+    let rephrased = derived("Hello, world.", &cleaned, LineageOperation::Rephrase, 2);
+    let edited = derived("Hello, world!", &rephrased, LineageOperation::ManualEdit, 3);
+    let upper = derived("HELLO WORLD", &cleaned, LineageOperation::Transform, 4);
+    let unrelated = ClipboardEntry::new("something else".to_string(), false, None);
+
+    let mut history = ClipboardHistory::new(100);
+    for entry in [&original, &cleaned, &rephrased, &edited, &upper, &unrelated] {
+        history.add_entry(entry.clone());
+    }
+
+    let lineage = entry_lineage(&history.entries, &edited.id).unwrap();
+    assert_eq!(lineage.root.entry.id, original.id);
+    assert_eq!(
+        lineage.path,
+        vec![
+            original.id.clone(),
+            cleaned.id.clone(),
+            rephrased.id.clone(),
+            edited.id.clone()
+        ]
+    );
+    assert_eq!(lineage.missing_parent_id, None);
+
+    let cleaned_node = &lineage.root.children[0];
+    assert_eq!(child_contents(&lineage.root), vec!["hello world"]);
+    assert_eq!(
+        child_contents(cleaned_node),
+        vec!["Hello, world.", "HELLO WORLD"]
+    );
+    assert_eq!(
+        child_contents(&cleaned_node.children[0]),
+        vec!["Hello, world!"]
+    );
+
+    // Any entry of the tree gives the same tree
+    let from_upper = entry_lineage(&history.entries, &upper.id).unwrap();
+    assert_eq!(from_upper.root.entry.id, original.id);
+    assert_eq!(from_upper.path.len(), 3);
+
+    let alone = entry_lineage(&history.entries, &unrelated.id).unwrap();
+    assert!(alone.root.children.is_empty());
+    assert_eq!(alone.path, vec![unrelated.id.clone()]);
+
+    assert!(entry_lineage(&history.entries, "missing").is_none());
+}
+
+#[test]
+fn reports_a_parent_no_longer_in_history() {
+    let original = ClipboardEntry::new("draft".to_string(), false, None);
+    let rephrased = derived("A draft.", &original, LineageOperation::Rephrase, 1);
+    let mut history = ClipboardHistory::new(100);
+    history.add_entry(original.clone());
+    history.add_entry(rephrased.clone());
+    assert!(history.remove_entry(&original.id));
+
+    let lineage = entry_lineage(&history.entries, &rephrased.id).unwrap();
+    assert_eq!(lineage.root.entry.id, rephrased.id);
+    assert_eq!(lineage.missing_parent_id, Some(original.id.clone()));
+}
+
+#[test]
+fn duplicates_hand_their_links_to_the_new_entry() {
+    let original = ClipboardEntry::new("raw text".to_string(), false, None);
+    let rephrased = derived("Raw text.", &original, LineageOperation::Rephrase, 1);
+    let mut history = ClipboardHistory::new(100);
+    history.add_entry(original.clone());
+    history.add_entry(rephrased.clone());
+
+    // Copying the original again keeps its rephrasing attached
+    let copied_again = ClipboardEntry::new("raw text".to_string(), false, None);
+    history.add_entry(copied_again.clone());
+    let rephrased_link = history
+        .get_entry_by_id(&rephrased.id)
+        .unwrap()
+        .parent
+        .clone();
+    assert_eq!(rephrased_link.unwrap().parent_id, copied_again.id);
+
+    // Copying the rephrasing again keeps its own parent link
+    let rephrased_again = ClipboardEntry::new("Raw text.".to_string(), false, None);
+    history.add_entry(rephrased_again.clone());
+    let link = history.entries[0].parent.clone().unwrap();
+    assert_eq!(link.parent_id, copied_again.id);
+    assert_eq!(link.operation, LineageOperation::Rephrase);
+
+    let lineage = entry_lineage(&history.entries, &rephrased_again.id).unwrap();
+    assert_eq!(lineage.root.entry.id, copied_again.id);
+    assert_eq!(history.entries.len(), 2);
+}
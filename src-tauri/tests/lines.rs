@@ -0,0 +1,121 @@
+// Tests for the line transforms: sorting, dedupe, numbering, join/split and column alignment.
+
+mod common;
+
+use common::messy_text;
+use proptest::prelude::*;
+use refine_lib::{
+    AlignColumns, DedupeLines, JoinLines, NumberLines, RemoveLineNumbers, ShuffleLines, SortLines,
+    SortMode, SplitOnDelimiter, Transform,
+};
+
+proptest! {
+    #[test]
+    fn numbering_lines_can_be_undone(text in messy_text()) {
+        let numbered = NumberLines::default().apply(&text).unwrap();
+        prop_assert_eq!(RemoveLineNumbers.apply(&numbered).unwrap(), text);
+    }
+
+    #[test]
+    fn line_operations_keep_every_line(text in messy_text(), seed in any::<u64>()) {
+        let sorted_lines = |t: &str| {
+            let mut lines: Vec<String> = t.split('\n').filter(|l| !l.trim().is_empty()).map(String::from).collect();
+            lines.sort();
+            lines
+        };
+        let shuffled = ShuffleLines { seed: Some(seed) }.apply(&text).unwrap();
+        prop_assert_eq!(sorted_lines(&shuffled), sorted_lines(&text));
+        for mode in [SortMode::Natural, SortMode::Numeric, SortMode::CaseInsensitive, SortMode::Lexical] {
+            let sorted = SortLines { mode, descending: false }.apply(&text).unwrap();
+            prop_assert_eq!(sorted_lines(&sorted), sorted_lines(&text));
+            prop_assert_eq!(SortLines { mode, descending: false }.apply(&sorted).unwrap(), sorted);
+        }
+    }
+
+    #[test]
+    fn aligning_columns_is_idempotent(text in messy_text()) {
+        for delimiter in [",", "=", " ", "|"] {
+            let align = AlignColumns { delimiter: delimiter.to_string() };
+            let once = align.apply(&text).unwrap();
+            prop_assert_eq!(align.apply(&once).unwrap(), once);
+        }
+    }
+}
+
+#[test]
+fn natural_sort_orders_numbers_by_value() {
+    let files = "file10.txt\nFile2.txt\n\nfile1.txt\nfile02.txt";
+    let sort = |mode, descending| SortLines { mode, descending }.apply(files).unwrap();
+    assert_eq!(
+        sort(SortMode::Natural, false),
+        "file1.txt\nFile2.txt\nfile02.txt\nfile10.txt"
+    );
+    assert_eq!(
+        sort(SortMode::Lexical, true),
+        "file10.txt\nfile1.txt\nfile02.txt\nFile2.txt"
+    );
+    assert_eq!(
+        SortLines {
+            mode: SortMode::Numeric,
+            descending: false
+        }
+        .apply("10 apples\n-2.5 debt\nnone\n1,200 total\n3")
+        .unwrap(),
+        "-2.5 debt\n3\n10 apples\n1,200 total\nnone"
+    );
+}
+
+#[test]
+fn dedupe_keeps_first_occurrence_and_blank_lines() {
+    let text = "Apple\n apple\nbanana\n\nApple \n\nbanana";
+    assert_eq!(
+        DedupeLines::default().apply(text).unwrap(),
+        "Apple\n apple\nbanana\n\n"
+    );
+    let case_insensitive = DedupeLines {
+        case_sensitive: false,
+        ..DedupeLines::default()
+    };
+    assert_eq!(case_insensitive.apply(text).unwrap(), "Apple\nbanana\n\n");
+}
+
+#[test]
+fn line_numbers_are_aligned_and_only_stripped_when_every_line_has_one() {
+    let text = (1..=10)
+        .map(|i| format!("item {}", i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let numbered = NumberLines::default().apply(&text).unwrap();
+    assert!(numbered.starts_with(" 1. item 1\n"));
+    assert!(numbered.ends_with("\n10. item 10"));
+
+    let prose = "2024 was a good year\nwe shipped a lot";
+    assert_eq!(RemoveLineNumbers.apply(prose).unwrap(), prose);
+}
+
+#[test]
+fn join_split_and_align_use_the_delimiter() {
+    assert_eq!(
+        JoinLines::default().apply(" a \n\nb\nc\n").unwrap(),
+        "a, b, c"
+    );
+    assert_eq!(
+        SplitOnDelimiter::default().apply("a, b,,c\n").unwrap(),
+        "a\nb\nc"
+    );
+    let align = |delimiter: &str, text: &str| {
+        AlignColumns {
+            delimiter: delimiter.to_string(),
+        }
+        .apply(text)
+        .unwrap()
+    };
+    assert_eq!(
+        align("=", "x = 1\nlong_name=2\n# note"),
+        "x         = 1\nlong_name = 2\n# note"
+    );
+    assert_eq!(
+        align(",", "id,name,role\n7,Ada Lovelace,admin"),
+        "id, name,         role\n7,  Ada Lovelace, admin"
+    );
+}
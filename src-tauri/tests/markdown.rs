@@ -0,0 +1,27 @@
+// Tests for Markdown cleanup and HTML-to-Markdown conversion.
+
+use refine_lib::{cleanup_markdown, CleanupPresetStore};
+
+#[test]
+fn markdown_cleanup_leaves_code_fences_alone() {
+    let notes = "* one\n* two\n\n```\n* literal\n#literal\n```";
+    assert_eq!(
+        cleanup_markdown(&CleanupPresetStore::default(), notes),
+        "- one\n- two\n\n```\n* literal\n#literal\n```"
+    );
+}
+
+#[test]
+fn html_table_cells_with_pipes_are_escaped() {
+    let html = "<table><tr><th>a</th><th>b</th></tr><tr><td>x|y</td><td></td></tr></table>";
+    assert_eq!(
+        cleanup_markdown(&CleanupPresetStore::default(), html),
+        "| a | b |\n| --- | --- |\n| x\\|y |  |"
+    );
+}
+
+#[test]
+fn plain_text_with_angle_brackets_is_not_treated_as_html() {
+    let text = "<https://example.com> is an autolink and a < b > c";
+    assert_eq!(cleanup_markdown(&CleanupPresetStore::default(), text), text);
+}
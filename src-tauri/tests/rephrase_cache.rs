@@ -0,0 +1,93 @@
+// Tests for the rephrase result cache's keys, expiry and size limits.
+
+use chrono::{Duration, Utc};
+use refine_lib::{
+    cache_key, ProviderConfig, RephraseCache, RephraseCacheConfig, RephraseRequest,
+    RephraseResponse,
+};
+
+fn response(text: &str) -> RephraseResponse {
+    RephraseResponse {
+        rephrased_text: text.to_string(),
+        processing_time_ms: Some(10),
+    }
+}
+
+fn ollama(model: &str) -> ProviderConfig {
+    ProviderConfig::Ollama {
+        base_url: "http://localhost:11434".to_string(),
+        model: model.to_string(),
+    }
+}
+
+#[test]
+fn keys_cover_the_text_options_and_provider() {
+    let request = RephraseRequest::new("hello there");
+    let key = cache_key(&ollama("llama3.2"), &request);
+    assert_eq!(key.len(), 64);
+    assert_eq!(key, cache_key(&ollama("llama3.2"), &request.clone()));
+
+    let mut casual = request.clone();
+    casual.style = "casual".to_string();
+    let mut audience = request.clone();
+    audience.target_audience = "Customers".to_string();
+    for other in [
+        cache_key(&ollama("llama3.2"), &RephraseRequest::new("hello there!")),
+        cache_key(&ollama("llama3.2"), &casual),
+        cache_key(&ollama("llama3.2"), &audience),
+        cache_key(&ollama("mistral"), &request),
+    ] {
+        assert_ne!(key, other);
+    }
+}
+
+#[test]
+fn hits_and_misses_are_counted_and_entries_expire() {
+    let now = Utc::now();
+    let mut cache = RephraseCache::default();
+    assert_eq!(cache.get("a", now), None);
+    cache.insert("a".to_string(), response("Hello."), now);
+    assert_eq!(cache.get("a", now), Some(response("Hello.")));
+
+    let summary = cache.summary();
+    assert_eq!((summary.stats.hits, summary.stats.misses), (1, 1));
+    assert_eq!(summary.hit_rate, 0.5);
+    assert_eq!(summary.entries, 1);
+
+    let later = now + Duration::hours(cache.config.ttl_hours as i64);
+    assert_eq!(cache.get("a", later), None);
+    assert_eq!(cache.summary().stats.expired, 1);
+
+    // A disabled cache neither serves nor stores results
+    cache.config.enabled = false;
+    cache.insert("b".to_string(), response("Hi."), now);
+    assert_eq!(cache.summary().entries, 0);
+}
+
+#[test]
+fn least_recently_used_entries_are_evicted_first() {
+    let now = Utc::now();
+    let mut cache = RephraseCache {
+        config: RephraseCacheConfig {
+            max_entries: 2,
+            ..RephraseCacheConfig::default()
+        },
+        ..RephraseCache::default()
+    };
+    cache.insert("a".to_string(), response("A"), now);
+    cache.insert("b".to_string(), response("B"), now + Duration::seconds(1));
+    cache.get("a", now + Duration::seconds(2));
+    cache.insert("c".to_string(), response("C"), now + Duration::seconds(3));
+
+    assert!(cache.get("b", now + Duration::seconds(4)).is_none());
+    assert!(cache.get("a", now + Duration::seconds(4)).is_some());
+    assert_eq!(cache.summary().stats.evictions, 1);
+
+    // Shrinking the byte limit evicts until the rest fits
+    cache.config.max_bytes = cache.bytes() - 1;
+    cache.enforce_limits(now + Duration::seconds(5));
+    assert_eq!(cache.summary().entries, 1);
+
+    assert_eq!(cache.purge(), 1);
+    assert_eq!(cache.bytes(), 0);
+}
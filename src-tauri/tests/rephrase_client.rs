@@ -0,0 +1,820 @@
+// Integration tests for the native rephrase client and providers against local mock APIs.
+
+use refine_lib::{
+    ApiKeyUpdate, OllamaProvider, OpenAiProvider, ProviderConfig, RephraseClient,
+    RephraseClientConfig, RephraseError, RephraseProfile, RephraseProfileStore, RephraseProvider,
+    RephraseRequest, SseDecoder, REPHRASE_ENDPOINT,
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const TOKEN: &str = "test.jwt.token";
+
+// Short timeouts and backoff so failing paths finish quickly
+fn client(server: &MockServer, max_retries: u32) -> RephraseClient {
+    let config = RephraseClientConfig {
+        timeout_ms: 500,
+        stream_timeout_ms: 2000,
+        connect_timeout_ms: 500,
+        max_retries,
+        initial_backoff_ms: 10,
+        max_backoff_ms: 50,
+    };
+    RephraseClient::new(&server.uri(), config).unwrap()
+}
+
+fn rephrased(text: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "rephrased_text": text,
+        "processing_time_ms": 42,
+    }))
+}
+
+#[tokio::test]
+async fn sends_the_request_with_a_bearer_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(REPHRASE_ENDPOINT))
+        .and(header(
+            "authorization",
+            format!("Bearer {}", TOKEN).as_str(),
+        ))
+        .and(body_partial_json(json!({
+            "text": "hey can u send it",
+            "style": "formal",
+            "preserve_length": false,
+        })))
+        .respond_with(rephrased("Could you please send it?"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server, 0)
+        .rephrase(TOKEN, &RephraseRequest::new("hey can u send it"))
+        .await
+        .unwrap();
+    assert_eq!(response.rephrased_text, "Could you please send it?");
+    assert_eq!(response.processing_time_ms, Some(42));
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503).set_body_json(json!({"error": "overloaded"})))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(rephrased("done"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server, 3)
+        .rephrase(TOKEN, &RephraseRequest::new("text"))
+        .await
+        .unwrap();
+    assert_eq!(response.rephrased_text, "done");
+}
+
+#[tokio::test]
+async fn gives_up_after_the_retry_limit() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(json!({"message": "boom"})))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let error = client(&server, 2)
+        .rephrase(TOKEN, &RephraseRequest::new("text"))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        RephraseError::Server {
+            status: 500,
+            message: "boom".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn client_errors_are_typed_and_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("authorization", "Bearer expired"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({"error": "Token expired"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(header(
+            "authorization",
+            format!("Bearer {}", TOKEN).as_str(),
+        ))
+        .respond_with(ResponseTemplate::new(400).set_body_string("Text exceeds 150-word limit"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server, 3);
+    let request = RephraseRequest::new("text");
+    assert_eq!(
+        client.rephrase("expired", &request).await.unwrap_err(),
+        RephraseError::Unauthorized {
+            message: "Token expired".to_string()
+        }
+    );
+    assert_eq!(
+        client.rephrase(TOKEN, &request).await.unwrap_err(),
+        RephraseError::Rejected {
+            status: 400,
+            message: "Text exceeds 150-word limit".to_string()
+        }
+    );
+
+    // Without a token nothing is sent
+    assert!(matches!(
+        client.rephrase("", &request).await,
+        Err(RephraseError::Unauthorized { .. })
+    ));
+}
+
+#[tokio::test]
+async fn rate_limits_timeouts_and_bad_bodies() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({"text": "limited"})))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "7"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({"text": "slow"})))
+        .respond_with(rephrased("late").set_delay(Duration::from_secs(2)))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({"text": "garbled"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"result": "?"})))
+        .mount(&server)
+        .await;
+
+    let client = client(&server, 0);
+    assert_eq!(
+        client
+            .rephrase(TOKEN, &RephraseRequest::new("limited"))
+            .await
+            .unwrap_err(),
+        RephraseError::RateLimited {
+            retry_after_ms: Some(7000)
+        }
+    );
+    assert_eq!(
+        client
+            .rephrase(TOKEN, &RephraseRequest::new("slow"))
+            .await
+            .unwrap_err(),
+        RephraseError::Timeout
+    );
+    assert!(matches!(
+        client
+            .rephrase(TOKEN, &RephraseRequest::new("garbled"))
+            .await,
+        Err(RephraseError::InvalidResponse { .. })
+    ));
+}
+
+#[tokio::test]
+async fn unreachable_servers_are_network_errors() {
+    // A port nothing listens on; mock servers are pooled, so dropping one doesn't close it
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let config = RephraseClientConfig {
+        max_retries: 1,
+        initial_backoff_ms: 10,
+        ..RephraseClientConfig::default()
+    };
+    let client = RephraseClient::new(&format!("http://{}", address), config).unwrap();
+
+    let error = client
+        .rephrase(TOKEN, &RephraseRequest::new("text"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, RephraseError::Network { .. }));
+    assert!(error.is_retryable());
+}
+
+#[test]
+fn backoff_doubles_and_respects_retry_after() {
+    let config = RephraseClientConfig::default();
+    let server_error = RephraseError::Server {
+        status: 502,
+        message: String::new(),
+    };
+    let delays: Vec<u128> = (0..6)
+        .map(|retry| config.backoff(retry, &server_error).as_millis())
+        .collect();
+    assert_eq!(delays, vec![500, 1000, 2000, 4000, 8000, 8000]);
+
+    let limited = RephraseError::RateLimited {
+        retry_after_ms: Some(3000),
+    };
+    assert_eq!(config.backoff(0, &limited), Duration::from_millis(3000));
+    assert_eq!(config.backoff(3, &limited), Duration::from_millis(4000));
+}
+
+fn fast_settings() -> RephraseClientConfig {
+    RephraseClientConfig {
+        timeout_ms: 500,
+        max_retries: 1,
+        initial_backoff_ms: 10,
+        ..RephraseClientConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn openai_compatible_provider_uses_chat_completions() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer sk-local"))
+        .and(body_partial_json(json!({
+            "model": "gpt-4o-mini",
+            "temperature": 0.5,
+            "messages": [{"role": "system"}, {"role": "user", "content": "pls fix"}],
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": "  Please fix it.\n"}}],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // A trailing /v1 in the configured URL is accepted too
+    let provider = OpenAiProvider::new(
+        &format!("{}/v1/", server.uri()),
+        "gpt-4o-mini",
+        Some("sk-local".to_string()),
+        Some(0.5),
+        fast_settings(),
+    )
+    .unwrap();
+    let response = provider
+        .rephrase(&RephraseRequest::new("pls fix"))
+        .await
+        .unwrap();
+    assert_eq!(response.rephrased_text, "Please fix it.");
+    assert!(response.processing_time_ms.is_some());
+}
+
+#[tokio::test]
+async fn openai_errors_are_read_from_the_error_object() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "error": {"message": "The model `nope` does not exist", "type": "invalid_request_error"},
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = OpenAiProvider::new(&server.uri(), "nope", None, None, fast_settings()).unwrap();
+    assert_eq!(
+        provider
+            .rephrase(&RephraseRequest::new("text"))
+            .await
+            .unwrap_err(),
+        RephraseError::Rejected {
+            status: 404,
+            message: "The model `nope` does not exist".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn ollama_provider_uses_the_chat_api_without_streaming() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .and(body_partial_json(
+            json!({"model": "llama3.2", "stream": false}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "model": "llama3.2",
+            "message": {"role": "assistant", "content": "Hello there."},
+            "done": true,
+            "total_duration": 1_250_000_000u64,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = OllamaProvider::new(&server.uri(), "llama3.2", fast_settings()).unwrap();
+    let response = provider
+        .rephrase(&RephraseRequest::new("hello"))
+        .await
+        .unwrap();
+    assert_eq!(response.rephrased_text, "Hello there.");
+    assert_eq!(response.processing_time_ms, Some(1250));
+}
+
+// Run a streaming rephrase, returning the response and the deltas in arrival order
+async fn stream(
+    provider: &dyn RephraseProvider,
+    text: &str,
+) -> (
+    Result<refine_lib::RephraseResponse, RephraseError>,
+    Vec<String>,
+) {
+    let deltas = Mutex::new(Vec::new());
+    let response = provider
+        .rephrase_streaming(&RephraseRequest::new(text), &|delta| {
+            deltas.lock().unwrap().push(delta.to_string())
+        })
+        .await;
+    (response, deltas.into_inner().unwrap())
+}
+
+#[test]
+fn sse_decoder_joins_data_lines_until_a_blank_line() {
+    let mut decoder = SseDecoder::default();
+    assert_eq!(decoder.push_line(": keep-alive"), None);
+    assert_eq!(decoder.push_line("event: delta"), None);
+    assert_eq!(decoder.push_line("data: first"), None);
+    assert_eq!(decoder.push_line("data:second"), None);
+    assert_eq!(decoder.push_line(""), Some("first\nsecond".to_string()));
+    assert_eq!(decoder.push_line(""), None);
+    assert_eq!(decoder.push_line("data: unterminated"), None);
+    assert_eq!(decoder.finish(), Some("unterminated".to_string()));
+}
+
+#[tokio::test]
+async fn streams_server_sent_events_from_every_provider() {
+    let server = MockServer::start().await;
+    let openai_events = concat!(
+        "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"content\":\"Please \"}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"content\":\"fix it.\"}}]}\n\n",
+        "data: [DONE]\n\n",
+    );
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(json!({"stream": true})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(openai_events, "text/event-stream"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let ollama_lines = concat!(
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"Hello \"},\"done\":false}\n",
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"there.\"},\"done\":false}\n",
+        "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"total_duration\":2000000000}\n",
+    );
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .and(body_partial_json(json!({"stream": true})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(ollama_lines, "application/x-ndjson"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let clipify_events = concat!(
+        "data: {\"delta\":\"Could you \"}\n\n",
+        "data: {\"delta\":\"send it?\"}\n\n",
+        "data: {\"rephrased_text\":\"Could you send it?\",\"processing_time_ms\":7}\n",
+    );
+    Mock::given(method("POST"))
+        .and(path(REPHRASE_ENDPOINT))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(clipify_events, "text/event-stream"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let openai =
+        OpenAiProvider::new(&server.uri(), "gpt-4o-mini", None, None, fast_settings()).unwrap();
+    let (response, deltas) = stream(&openai, "pls fix").await;
+    assert_eq!(response.unwrap().rephrased_text, "Please fix it.");
+    assert_eq!(deltas, vec!["Please ", "fix it."]);
+
+    let ollama = OllamaProvider::new(&server.uri(), "llama3.2", fast_settings()).unwrap();
+    let (response, deltas) = stream(&ollama, "hello").await;
+    let response = response.unwrap();
+    assert_eq!(response.rephrased_text, "Hello there.");
+    assert_eq!(response.processing_time_ms, Some(2000));
+    assert_eq!(deltas, vec!["Hello ", "there."]);
+
+    // The last event is left unterminated; it still counts
+    let clipify = refine_lib::ClipifyProvider {
+        client: Arc::new(client(&server, 0)),
+        jwt_token: TOKEN.to_string(),
+    };
+    let (response, deltas) = stream(&clipify, "send it").await;
+    let response = response.unwrap();
+    assert_eq!(response.rephrased_text, "Could you send it?");
+    assert_eq!(response.processing_time_ms, Some(7));
+    assert_eq!(deltas, vec!["Could you ", "send it?"]);
+}
+
+#[tokio::test]
+async fn streaming_falls_back_to_whole_responses_and_reports_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(REPHRASE_ENDPOINT))
+        .respond_with(rephrased("Could you please send it?"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw("data: {not json}\n\n", "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+
+    // A server that doesn't stream answers with plain JSON, delivered as one delta
+    let clipify = refine_lib::ClipifyProvider {
+        client: Arc::new(client(&server, 0)),
+        jwt_token: TOKEN.to_string(),
+    };
+    let (response, deltas) = stream(&clipify, "send it").await;
+    assert_eq!(response.unwrap().processing_time_ms, Some(42));
+    assert_eq!(deltas, vec!["Could you please send it?"]);
+
+    let openai =
+        OpenAiProvider::new(&server.uri(), "gpt-4o-mini", None, None, fast_settings()).unwrap();
+    let (response, deltas) = stream(&openai, "pls fix").await;
+    assert!(matches!(
+        response.unwrap_err(),
+        RephraseError::InvalidResponse { .. }
+    ));
+    assert!(deltas.is_empty());
+}
+
+#[tokio::test]
+async fn openai_candidates_come_from_one_completion() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(json!({"n": 3, "stream": false})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "choices": [
+                {"index": 0, "message": {"content": "Please fix it."}},
+                {"index": 1, "message": {"content": " Please fix it. "}},
+                {"index": 2, "message": {"content": "Could you fix it?"}},
+            ],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider =
+        OpenAiProvider::new(&server.uri(), "gpt-4o-mini", None, None, fast_settings()).unwrap();
+    let candidates = provider
+        .rephrase_candidates(&RephraseRequest::new("pls fix"), 3)
+        .await
+        .unwrap();
+    let texts: Vec<&str> = candidates
+        .iter()
+        .map(|c| c.rephrased_text.as_str())
+        .collect();
+    assert_eq!(texts, vec!["Please fix it.", "Could you fix it?"]);
+}
+
+// Answers requests in the order they arrive, recording the body of each
+fn answer_in_turn(
+    responses: Vec<ResponseTemplate>,
+    bodies: Arc<Mutex<Vec<serde_json::Value>>>,
+) -> impl Fn(&Request) -> ResponseTemplate + Send + Sync {
+    move |request: &Request| {
+        let mut bodies = bodies.lock().unwrap();
+        bodies.push(serde_json::from_slice(&request.body).unwrap());
+        responses[(bodies.len() - 1) % responses.len()].clone()
+    }
+}
+
+#[tokio::test]
+async fn openai_candidates_are_made_up_when_n_is_ignored() {
+    let server = MockServer::start().await;
+    let answer = |text: &str| {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "choices": [{"index": 0, "message": {"content": text}}],
+        }))
+    };
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(answer_in_turn(
+            vec![
+                answer("Please fix it."),
+                answer("Could you fix it?"),
+                answer("Please fix it."),
+            ],
+            bodies.clone(),
+        ))
+        .mount(&server)
+        .await;
+
+    let provider =
+        OpenAiProvider::new(&server.uri(), "gpt-4o-mini", None, None, fast_settings()).unwrap();
+    let candidates = provider
+        .rephrase_candidates(&RephraseRequest::new("pls fix"), 3)
+        .await
+        .unwrap();
+    let mut texts: Vec<&str> = candidates
+        .iter()
+        .map(|c| c.rephrased_text.as_str())
+        .collect();
+    texts.sort();
+    assert_eq!(texts, vec!["Could you fix it?", "Please fix it."]);
+
+    // The two missing choices were asked for one at a time, each with its own seed
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 3);
+    assert_eq!(bodies[0]["n"], 3);
+    assert!(bodies[1..]
+        .iter()
+        .all(|body| body.get("n").is_none() && body["seed"].is_u64()));
+    assert_ne!(bodies[1]["seed"], bodies[2]["seed"]);
+}
+
+#[tokio::test]
+async fn ollama_candidates_are_sent_with_different_seeds() {
+    let server = MockServer::start().await;
+    let answer = |text: &str| {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "message": {"role": "assistant", "content": text},
+            "done": true,
+        }))
+    };
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    Mock::given(method("POST"))
+        .respond_with(answer_in_turn(
+            vec![
+                answer("Hello there."),
+                answer("Hello there."),
+                answer("Hi!"),
+                ResponseTemplate::new(400).set_body_json(json!({"error": "bad"})),
+            ],
+            bodies.clone(),
+        ))
+        .mount(&server)
+        .await;
+
+    // Duplicates are merged and a failed variant doesn't sink the others
+    let provider = OllamaProvider::new(&server.uri(), "llama3.2", fast_settings()).unwrap();
+    let request = RephraseRequest::new("hello");
+    let candidates = provider.rephrase_candidates(&request, 4).await.unwrap();
+    let mut texts: Vec<&str> = candidates
+        .iter()
+        .map(|c| c.rephrased_text.as_str())
+        .collect();
+    texts.sort();
+    assert_eq!(texts, vec!["Hello there.", "Hi!"]);
+
+    // Every variant has its own seed, and asking again doesn't reuse them
+    provider.rephrase_candidates(&request, 4).await.unwrap();
+    let seeds: Vec<u64> = bodies
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|body| body["options"]["seed"].as_u64().unwrap())
+        .collect();
+    assert_eq!(seeds.len(), 8);
+    let mut distinct = seeds.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), 8);
+
+    // With every variant failing, the error comes through
+    let failing = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({"error": "bad"})))
+        .mount(&failing)
+        .await;
+    let provider = OllamaProvider::new(&failing.uri(), "llama3.2", fast_settings()).unwrap();
+    assert!(matches!(
+        provider.rephrase_candidates(&request, 2).await,
+        Err(RephraseError::Rejected { status: 400, .. })
+    ));
+}
+
+#[tokio::test]
+async fn clipify_candidates_are_repeated_requests() {
+    let server = MockServer::start().await;
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    Mock::given(method("POST"))
+        .and(path(REPHRASE_ENDPOINT))
+        .and(header(
+            "authorization",
+            format!("Bearer {}", TOKEN).as_str(),
+        ))
+        .respond_with(answer_in_turn(
+            vec![rephrased("Hello."), rephrased("Hello."), rephrased("Hi.")],
+            bodies.clone(),
+        ))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let clipify = refine_lib::ClipifyProvider {
+        client: Arc::new(client(&server, 0)),
+        jwt_token: TOKEN.to_string(),
+    };
+    let candidates = clipify
+        .rephrase_candidates(&RephraseRequest::new("hello"), 3)
+        .await
+        .unwrap();
+    let mut texts: Vec<&str> = candidates
+        .iter()
+        .map(|c| c.rephrased_text.as_str())
+        .collect();
+    texts.sort();
+    assert_eq!(texts, vec!["Hello.", "Hi."]);
+    assert!(bodies
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|body| body["text"] == "hello"));
+}
+
+#[tokio::test]
+async fn profiles_build_the_configured_provider() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chat"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "message": {"role": "assistant", "content": "local"},
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(REPHRASE_ENDPOINT))
+        .respond_with(rephrased("hosted"))
+        .mount(&server)
+        .await;
+
+    let profile: RephraseProfile = serde_json::from_value(json!({
+        "name": "Offline",
+        "provider": "ollama",
+        "base_url": server.uri(),
+        "model": "llama3.2",
+    }))
+    .unwrap();
+    let mut store = RephraseProfileStore::default();
+    store.upsert(profile).unwrap();
+    store.set_default("Offline").unwrap();
+
+    let clipify = Arc::new(client(&server, 0));
+    let build = |name: Option<&str>| {
+        let profile = store.profile_for(name).unwrap();
+        profile
+            .provider
+            .build(&profile.settings, &clipify, Some(TOKEN.to_string()))
+            .unwrap()
+    };
+    let request = RephraseRequest::new("text");
+
+    let local = build(None);
+    assert_eq!(local.name(), "ollama");
+    assert_eq!(
+        local.rephrase(&request).await.unwrap().rephrased_text,
+        "local"
+    );
+
+    let hosted = build(Some("clipify"));
+    assert_eq!(hosted.name(), "clipify");
+    assert_eq!(
+        hosted.rephrase(&request).await.unwrap().rephrased_text,
+        "hosted"
+    );
+}
+
+#[test]
+fn profile_store_protects_builtins_and_validates_providers() {
+    let mut store = RephraseProfileStore::default();
+    let profile = |name: &str, provider: ProviderConfig| RephraseProfile {
+        name: name.to_string(),
+        description: String::new(),
+        provider,
+        settings: RephraseClientConfig::default(),
+        builtin: false,
+    };
+
+    assert!(store
+        .upsert(profile(
+            "clipify",
+            ProviderConfig::Clipify { base_url: None }
+        ))
+        .is_err());
+    assert!(store.remove("clipify").is_err());
+    assert!(store
+        .upsert(profile(
+            "Bad URL",
+            ProviderConfig::OpenAiCompatible {
+                base_url: "not a url".to_string(),
+                model: "gpt".to_string(),
+                api_key: None,
+                has_api_key: false,
+                temperature: None,
+            }
+        ))
+        .is_err());
+    assert!(store
+        .upsert(profile(
+            "No model",
+            ProviderConfig::Ollama {
+                base_url: "http://localhost:11434".to_string(),
+                model: " ".to_string(),
+            }
+        ))
+        .is_err());
+
+    // Ollama's URL defaults to the local server
+    let local: ProviderConfig =
+        serde_json::from_value(json!({"provider": "ollama", "model": "llama3.2"})).unwrap();
+    store.upsert(profile("Local", local.clone())).unwrap();
+    store.set_default("Local").unwrap();
+    assert_eq!(store.profile_for(None).unwrap().provider, local);
+    assert!(store.set_default("Missing").is_err());
+
+    assert!(store.remove("Local").unwrap());
+    assert_eq!(store.default_profile, "clipify");
+    assert_eq!(store.all().len(), 1);
+}
+
+#[test]
+fn api_keys_are_taken_out_of_saved_profiles() {
+    let mut store = RephraseProfileStore::default();
+    let profile = |api_key: Option<&str>| -> RephraseProfile {
+        let mut profile = json!({
+            "name": "Hosted",
+            "provider": "openai_compatible",
+            "base_url": "https://api.example.com/v1",
+            "model": "gpt-4o-mini",
+        });
+        if let Some(api_key) = api_key {
+            profile["api_key"] = json!(api_key);
+        }
+        serde_json::from_value(profile).unwrap()
+    };
+    let has_key =
+        |store: &RephraseProfileStore| store.get("Hosted").unwrap().provider.has_api_key();
+
+    assert_eq!(
+        store.upsert(profile(Some(" sk-test "))).unwrap(),
+        ApiKeyUpdate::Set("sk-test".to_string())
+    );
+    assert!(has_key(&store));
+    let saved = serde_json::to_string(&store).unwrap();
+    assert!(!saved.contains("sk-test"));
+    assert!(saved.contains("\"has_api_key\":true"));
+
+    // Editing without a key keeps the stored one; an empty key removes it
+    assert_eq!(store.upsert(profile(None)).unwrap(), ApiKeyUpdate::Keep);
+    assert!(has_key(&store));
+    assert_eq!(
+        store.upsert(profile(Some(""))).unwrap(),
+        ApiKeyUpdate::Remove
+    );
+    assert!(!has_key(&store));
+
+    // Keys in profile files written by older versions are handed over once
+    let mut old: RephraseProfileStore = serde_json::from_value(json!({
+        "profiles": [serde_json::to_value(profile(None)).unwrap()],
+    }))
+    .unwrap();
+    assert!(old.take_api_keys().is_empty());
+    old.profiles[0] = profile(Some("sk-old"));
+    assert_eq!(
+        old.take_api_keys(),
+        vec![("Hosted".to_string(), "sk-old".to_string())]
+    );
+    assert!(has_key(&old));
+    assert!(old.take_api_keys().is_empty());
+}
+
+#[test]
+fn profiles_with_a_temperature_round_trip() {
+    // Floats inside the flattened provider have to survive both the webview and the file
+    let text = r#"{"name":"Hosted","provider":"openai_compatible","base_url":"https://api.example.com/v1","model":"gpt-4o-mini","temperature":0.7}"#;
+    let profile: RephraseProfile = serde_json::from_str(text).unwrap();
+    assert!(matches!(
+        profile.provider,
+        ProviderConfig::OpenAiCompatible {
+            temperature: Some(temperature),
+            ..
+        } if temperature == 0.7
+    ));
+
+    let mut store = RephraseProfileStore::default();
+    store.upsert(profile.clone()).unwrap();
+    let saved = serde_json::to_string(&store).unwrap();
+    let loaded: RephraseProfileStore = serde_json::from_str(&saved).unwrap();
+    assert_eq!(loaded.get("Hosted"), Some(profile));
+}
@@ -0,0 +1,233 @@
+// Tests for the client-side rate limit and circuit breaker around the Clipify API.
+
+use refine_lib::{
+    ApiGuard, ApiGuardConfig, CircuitBreaker, CircuitState, RephraseClient, RephraseClientConfig,
+    RephraseError, RephraseRequest, ServiceStatus, ThrottleReason, TokenBucket, REPHRASE_ENDPOINT,
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn token_bucket_allows_bursts_then_refills() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(3, 60, start);
+    for _ in 0..3 {
+        assert!(bucket.try_acquire(start).is_ok());
+    }
+    let wait = bucket.try_acquire(start).unwrap_err();
+    assert_eq!(wait, Duration::from_secs(1));
+
+    assert!(bucket
+        .try_acquire(start + Duration::from_millis(500))
+        .is_err());
+    assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_ok());
+
+    // Refilling stops at the burst size
+    let later = start + Duration::from_secs(60);
+    for _ in 0..3 {
+        assert!(bucket.try_acquire(later).is_ok());
+    }
+    assert!(bucket.try_acquire(later).is_err());
+}
+
+#[test]
+fn circuit_opens_after_failures_and_probes_before_closing() {
+    let start = Instant::now();
+    let open_for = Duration::from_secs(30);
+    let mut breaker = CircuitBreaker::new(3, open_for, start);
+
+    assert_eq!(breaker.on_failure(start), None);
+    assert_eq!(breaker.on_failure(start), None);
+    // A success in between resets the count
+    assert_eq!(breaker.on_success(), None);
+    assert_eq!(breaker.on_failure(start), None);
+    assert_eq!(breaker.on_failure(start), None);
+    assert_eq!(breaker.on_failure(start), Some(CircuitState::Open));
+
+    let later = start + Duration::from_secs(10);
+    assert_eq!(breaker.allow(later), Err(Duration::from_secs(20)));
+
+    // One probe at a time once the open period is over
+    let reopen = start + open_for;
+    assert_eq!(breaker.allow(reopen), Ok(Some(CircuitState::HalfOpen)));
+    assert!(breaker.allow(reopen).is_err());
+    assert_eq!(breaker.on_failure(reopen), Some(CircuitState::Open));
+
+    let retry = reopen + open_for;
+    assert_eq!(breaker.allow(retry), Ok(Some(CircuitState::HalfOpen)));
+    assert_eq!(breaker.on_success(), Some(CircuitState::Closed));
+    assert_eq!(breaker.allow(retry), Ok(None));
+
+    // A probe that never reports back is replaced after the open period
+    for _ in 0..3 {
+        breaker.on_failure(retry);
+    }
+    let probe = retry + open_for;
+    assert_eq!(breaker.allow(probe), Ok(Some(CircuitState::HalfOpen)));
+    assert!(breaker.allow(probe + Duration::from_secs(1)).is_err());
+    assert_eq!(breaker.allow(probe + open_for), Ok(None));
+}
+
+fn client(server: &MockServer, guard: ApiGuard) -> RephraseClient {
+    let config = RephraseClientConfig {
+        timeout_ms: 500,
+        stream_timeout_ms: 2000,
+        connect_timeout_ms: 500,
+        max_retries: 2,
+        initial_backoff_ms: 10,
+        max_backoff_ms: 50,
+    };
+    RephraseClient::new(&server.uri(), config)
+        .unwrap()
+        .with_guard(Arc::new(guard))
+}
+
+#[tokio::test]
+async fn failing_service_is_not_called_while_the_circuit_is_open() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(REPHRASE_ENDPOINT))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let changes: Arc<Mutex<Vec<ServiceStatus>>> = Arc::default();
+    let seen = changes.clone();
+    let guard = ApiGuard::new(ApiGuardConfig {
+        burst: 10,
+        requests_per_minute: 60,
+        failure_threshold: 2,
+        open_secs: 60,
+    })
+    .with_alert(Arc::new(move |status: &ServiceStatus| {
+        seen.lock().unwrap().push(status.clone())
+    }));
+    let client = client(&server, guard);
+    let request = RephraseRequest::new("hello");
+
+    // The second attempt opens the circuit, so the retry after it is held back
+    let error = client.rephrase("token", &request).await.unwrap_err();
+    assert!(matches!(
+        error,
+        RephraseError::Throttled {
+            reason: ThrottleReason::ServiceDegraded,
+            ..
+        }
+    ));
+    assert!(error.is_retryable());
+    assert!(client.rephrase("token", &request).await.is_err());
+
+    let status = client.guard().unwrap().status();
+    assert!(status.is_degraded());
+    assert_eq!(status.consecutive_failures, 2);
+    assert!(status.retry_after_ms.unwrap() > 50_000);
+    let states: Vec<CircuitState> = changes.lock().unwrap().iter().map(|s| s.state).collect();
+    assert_eq!(states, vec![CircuitState::Open]);
+}
+
+#[tokio::test]
+async fn requests_over_the_rate_limit_are_held_back() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(REPHRASE_ENDPOINT))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "rephrased_text": "Hello.",
+        })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let client = client(
+        &server,
+        ApiGuard::new(ApiGuardConfig {
+            burst: 2,
+            requests_per_minute: 1,
+            ..ApiGuardConfig::default()
+        }),
+    );
+    let request = RephraseRequest::new("hello");
+    client.rephrase("token", &request).await.unwrap();
+    client.rephrase("token", &request).await.unwrap();
+    let error = client.rephrase("token", &request).await.unwrap_err();
+    assert!(matches!(
+        error,
+        RephraseError::Throttled {
+            reason: ThrottleReason::RateLimit,
+            retry_after_ms,
+        } if retry_after_ms > 55_000
+    ));
+    assert_eq!(error.to_string(), "Rephrasing too often, try again in 60s");
+    assert!(!client.guard().unwrap().status().is_degraded());
+}
+
+#[tokio::test]
+async fn streams_that_stall_count_against_the_service() {
+    // Sends the start of an event stream, then goes quiet
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(Mutex::new(0));
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            *accepted.lock().unwrap() += 1;
+            tokio::spawn(async move {
+                let mut request = [0u8; 4096];
+                let _ = socket.read(&mut request).await;
+                let _ = socket
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                          Connection: close\r\n\r\ndata: {\"delta\": \"Hel\"}\n\n",
+                    )
+                    .await;
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            });
+        }
+    });
+
+    let config = RephraseClientConfig {
+        timeout_ms: 200,
+        stream_timeout_ms: 2000,
+        connect_timeout_ms: 500,
+        max_retries: 0,
+        initial_backoff_ms: 10,
+        max_backoff_ms: 50,
+    };
+    let client = RephraseClient::new(&format!("http://{}", address), config)
+        .unwrap()
+        .with_guard(Arc::new(ApiGuard::new(ApiGuardConfig {
+            burst: 10,
+            requests_per_minute: 60,
+            failure_threshold: 2,
+            open_secs: 60,
+        })));
+    let request = RephraseRequest::new("hello");
+    let deltas = Mutex::new(Vec::new());
+    let on_delta = |delta: &str| deltas.lock().unwrap().push(delta.to_string());
+
+    for _ in 0..2 {
+        let error = client
+            .rephrase_streaming("token", &request, &on_delta)
+            .await
+            .unwrap_err();
+        assert_eq!(error, RephraseError::Timeout);
+    }
+    assert_eq!(*deltas.lock().unwrap(), vec!["Hel", "Hel"]);
+    assert!(client.guard().unwrap().status().is_degraded());
+
+    // With the circuit open the next stream isn't started
+    assert!(matches!(
+        client
+            .rephrase_streaming("token", &request, &on_delta)
+            .await,
+        Err(RephraseError::Throttled {
+            reason: ThrottleReason::ServiceDegraded,
+            ..
+        })
+    ));
+    assert_eq!(*connections.lock().unwrap(), 2);
+}
@@ -0,0 +1,122 @@
+// Tests for the offline rephrase queue's scheduling rules.
+
+use chrono::{Duration, Utc};
+use refine_lib::{JobStatus, RephraseError, RephraseQueue, RephraseRequest, ThrottleReason};
+
+fn network_error() -> RephraseError {
+    RephraseError::Network {
+        message: "connection refused".to_string(),
+    }
+}
+
+#[test]
+fn jobs_run_in_order_and_wait_while_offline() {
+    let now = Utc::now();
+    let mut queue = RephraseQueue::default();
+    let first = queue.enqueue(
+        Some("entry-1".to_string()),
+        RephraseRequest::new("one"),
+        None,
+        now,
+    );
+    let second = queue.enqueue(
+        None,
+        RephraseRequest::new("two"),
+        Some("local".to_string()),
+        now,
+    );
+
+    let job = queue.take_due(now).unwrap();
+    assert_eq!(job.id, first.id);
+    assert_eq!(queue.get(&first.id).unwrap().status, JobStatus::Running);
+
+    // Connectivity errors reschedule without using up attempts
+    let job = queue.fail(&first.id, &network_error(), now).unwrap();
+    assert_eq!(job.status, JobStatus::Queued);
+    assert_eq!(job.attempts, 0);
+    assert!(job.next_attempt_at > now);
+    assert_eq!(
+        job.last_error.as_deref(),
+        Some("Could not reach the rephrase service: connection refused")
+    );
+
+    assert_eq!(queue.take_due(now).unwrap().id, second.id);
+    assert!(queue.take_due(now).is_none());
+    assert_eq!(
+        queue.take_due(now + Duration::minutes(1)).unwrap().id,
+        first.id
+    );
+
+    let done = queue.complete(&second.id).unwrap();
+    assert_eq!(done.status, JobStatus::Completed);
+    assert_eq!(queue.jobs.len(), 1);
+
+    // Requests held back locally wait as long as asked, also without using up attempts
+    let throttled = RephraseError::Throttled {
+        reason: ThrottleReason::ServiceDegraded,
+        retry_after_ms: 20_000,
+    };
+    let job = queue.fail(&first.id, &throttled, now).unwrap();
+    assert_eq!(job.status, JobStatus::Queued);
+    assert_eq!(job.attempts, 0);
+    assert_eq!(job.next_attempt_at, now + Duration::seconds(20));
+}
+
+#[test]
+fn server_errors_back_off_and_permanent_errors_fail() {
+    let now = Utc::now();
+    let mut queue = RephraseQueue::default();
+    let job = queue.enqueue(None, RephraseRequest::new("text"), None, now);
+
+    let rate_limited = RephraseError::RateLimited {
+        retry_after_ms: Some(90_000),
+    };
+    let failed = queue.fail(&job.id, &rate_limited, now).unwrap();
+    assert_eq!(failed.attempts, 1);
+    assert_eq!(failed.next_attempt_at, now + Duration::seconds(90));
+
+    let server = RephraseError::Server {
+        status: 503,
+        message: "unavailable".to_string(),
+    };
+    let failed = queue.fail(&job.id, &server, now).unwrap();
+    assert_eq!(failed.next_attempt_at, now + Duration::seconds(60));
+
+    let unauthorized = RephraseError::Unauthorized {
+        message: "expired".to_string(),
+    };
+    let failed = queue.fail(&job.id, &unauthorized, now).unwrap();
+    assert_eq!(failed.status, JobStatus::Failed);
+    assert!(queue.take_due(now + Duration::days(1)).is_none());
+
+    // A manual retry queues it again with a fresh attempt count
+    let retried = queue.retry(&job.id, now).unwrap();
+    assert_eq!(retried.status, JobStatus::Queued);
+    assert_eq!(retried.attempts, 0);
+    assert!(queue.retry("missing", now).is_err());
+}
+
+#[test]
+fn cancelled_jobs_are_removed_and_running_jobs_can_be_resumed() {
+    let now = Utc::now();
+    let mut queue = RephraseQueue::default();
+    let cancelled = queue.enqueue(None, RephraseRequest::new("a"), None, now);
+    let running = queue.enqueue(None, RephraseRequest::new("b"), None, now);
+
+    assert_eq!(
+        queue.cancel(&cancelled.id).unwrap().status,
+        JobStatus::Cancelled
+    );
+    assert!(queue.cancel(&cancelled.id).is_none());
+
+    queue.take_due(now).unwrap();
+    assert!(queue.retry(&running.id, now).is_err());
+
+    // After a restart, jobs that were mid-flight run again
+    queue.requeue_interrupted();
+    assert_eq!(queue.take_due(now).unwrap().id, running.id);
+
+    // A job cancelled while running has nowhere to put its result
+    queue.cancel(&running.id);
+    assert!(queue.complete(&running.id).is_none());
+}
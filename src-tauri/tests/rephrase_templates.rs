@@ -0,0 +1,106 @@
+// Tests for rephrase templates: placeholder rendering and the template library.
+
+use refine_lib::{HotkeyAction, HotkeyBinding, RephraseTemplate, RephraseTemplateStore};
+use std::collections::{BTreeMap, HashMap};
+
+fn template(name: &str) -> RephraseTemplate {
+    RephraseTemplate {
+        name: name.to_string(),
+        description: String::new(),
+        style: "{{tone}}".to_string(),
+        context: "Update on {{ project }} for {{team}}".to_string(),
+        target_audience: "{{team}}".to_string(),
+        preserve_length: true,
+        variables: BTreeMap::from([
+            ("tone".to_string(), "friendly".to_string()),
+            ("project".to_string(), String::new()),
+            ("team".to_string(), "Engineering".to_string()),
+        ]),
+        profile: Some("local".to_string()),
+        builtin: false,
+    }
+}
+
+#[test]
+fn renders_placeholders_from_values_and_defaults() {
+    let template = template("status update");
+    assert_eq!(
+        template.placeholders().into_iter().collect::<Vec<_>>(),
+        vec!["project", "team", "tone"]
+    );
+
+    // A variable with an empty default has to be given
+    assert_eq!(
+        template.render("text", &HashMap::new()).unwrap_err(),
+        "Missing values for template variables: project"
+    );
+    assert_eq!(template.missing_variables(&HashMap::new()), vec!["project"]);
+
+    let values = HashMap::from([
+        ("project".to_string(), "Clipify".to_string()),
+        ("team".to_string(), "Design".to_string()),
+    ]);
+    let request = template.render("we shipped it", &values).unwrap();
+    assert_eq!(request.text, "we shipped it");
+    assert_eq!(request.style, "friendly");
+    assert_eq!(request.context, "Update on Clipify for Design");
+    assert_eq!(request.target_audience, "Design");
+    assert!(request.preserve_length);
+}
+
+#[test]
+fn store_protects_builtins_and_validates_templates() {
+    let mut store = RephraseTemplateStore::default();
+    let names: Vec<String> = store.all().into_iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["formal email", "slack casual", "release note"]);
+    let product = HashMap::from([("product".to_string(), "Clipify".to_string())]);
+    for builtin in store.all() {
+        assert!(builtin.render("text", &product).is_ok());
+    }
+
+    assert!(store.upsert(template("formal email")).is_err());
+    assert!(store.remove("slack casual").is_err());
+
+    let mut undeclared = template("broken");
+    undeclared.context = "For {{customer}}".to_string();
+    assert_eq!(
+        store.upsert(undeclared).unwrap_err(),
+        "Undeclared template variables: customer"
+    );
+
+    let mut custom = template("status update");
+    custom.builtin = true;
+    store.upsert(custom).unwrap();
+    let saved = store.get("status update").unwrap();
+    assert!(!saved.builtin);
+    assert_eq!(store.all().len(), 4);
+
+    assert_eq!(store.remove("status update"), Ok(true));
+    assert_eq!(store.remove("status update"), Ok(false));
+}
+
+#[test]
+fn shortcut_values_fill_builtin_templates() {
+    // Shortcuts bound to the release note template have to carry the product name
+    let release_note = RephraseTemplateStore::default()
+        .get("release note")
+        .unwrap();
+    assert_eq!(
+        release_note.missing_variables(&HashMap::new()),
+        vec!["product"]
+    );
+
+    let binding: HotkeyBinding = serde_json::from_value(serde_json::json!({
+        "shortcut": "CommandOrControl+Alt+R",
+        "action": "rephrase_with_template",
+        "template": "release note",
+        "variables": {"product": "Refine"},
+    }))
+    .unwrap();
+    let HotkeyAction::RephraseWithTemplate { variables, .. } = binding.action else {
+        panic!("unexpected action {:?}", binding.action);
+    };
+    assert!(release_note.missing_variables(&variables).is_empty());
+    let request = release_note.render("fixed the crash", &variables).unwrap();
+    assert_eq!(request.context, "Release notes for Refine");
+}
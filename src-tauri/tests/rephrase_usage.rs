@@ -0,0 +1,124 @@
+// Tests for rephrase usage accounting: daily, weekly and provider summaries and the soft cap.
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use refine_lib::{CapLevel, UsageCap, UsageLog, UsageOutcome, UsageRecord};
+
+// Wednesday
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 11, 15, 0, 0).unwrap()
+}
+
+fn record(provider: &str, at: DateTime<Utc>, outcome: UsageOutcome) -> UsageRecord {
+    UsageRecord {
+        provider: provider.to_string(),
+        input_chars: 100,
+        output_chars: if outcome == UsageOutcome::Success {
+            80
+        } else {
+            0
+        },
+        latency_ms: 200,
+        processing_time_ms: None,
+        outcome,
+        error: None,
+        at,
+    }
+}
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+}
+
+#[test]
+fn summarises_by_day_week_and_provider() {
+    let mut usage = UsageLog::default();
+    usage.record(record("clipify", now(), UsageOutcome::Success));
+    usage.record(record("ollama", now(), UsageOutcome::Failed));
+    usage.record(record(
+        "clipify",
+        now() - Duration::days(2),
+        UsageOutcome::Success,
+    ));
+    usage.record(record(
+        "clipify",
+        now() - Duration::days(9),
+        UsageOutcome::Success,
+    ));
+
+    let daily = usage.daily(3, now());
+    let days: Vec<NaiveDate> = daily.iter().map(|day| day.start).collect();
+    assert_eq!(days, vec![date(3, 9), date(3, 10), date(3, 11)]);
+    assert_eq!(daily[0].summary.calls, 1);
+    assert_eq!(daily[1].summary.calls, 0);
+    let today = &daily[2].summary;
+    assert_eq!((today.calls, today.succeeded, today.failed), (2, 1, 1));
+    assert_eq!((today.input_chars, today.output_chars), (200, 80));
+    assert_eq!(today.average_latency_ms, 200);
+
+    let weekly = usage.weekly(2, now());
+    assert_eq!(weekly[0].start, date(3, 2));
+    assert_eq!(weekly[1].start, date(3, 9));
+    assert_eq!(weekly[0].summary.calls, 1);
+    assert_eq!(weekly[1].summary.calls, 3);
+
+    let providers = usage.by_provider(None);
+    assert_eq!(providers["clipify"].calls, 3);
+    assert_eq!(providers["ollama"].failed, 1);
+    let recent = usage.by_provider(Some(now() - Duration::days(1)));
+    assert_eq!(recent["clipify"].calls, 1);
+
+    // Records past the retention period are dropped
+    usage.record(record(
+        "clipify",
+        now() + Duration::days(95),
+        UsageOutcome::Success,
+    ));
+    assert_eq!(usage.records.len(), 1);
+    assert_eq!(usage.clear(), 1);
+}
+
+#[test]
+fn soft_cap_warns_once_per_level() {
+    let mut usage = UsageLog {
+        cap: UsageCap {
+            max_calls_per_day: Some(5),
+            max_chars_per_day: None,
+            warn_at_percent: 80,
+        },
+        ..UsageLog::default()
+    };
+
+    let mut levels = Vec::new();
+    for _ in 0..7 {
+        let status = usage.record(record("clipify", now(), UsageOutcome::Success));
+        levels.push(status.map(|status| status.level));
+    }
+    assert_eq!(
+        levels,
+        vec![
+            None,
+            None,
+            None,
+            Some(CapLevel::Warning),
+            None,
+            Some(CapLevel::Exceeded),
+            None
+        ]
+    );
+    assert_eq!(usage.cap_status(now()).calls_today, 7);
+
+    // A new day starts below the cap again
+    assert_eq!(
+        usage.cap_status(now() + Duration::days(1)).level,
+        CapLevel::Ok
+    );
+
+    // Characters count towards the cap as well
+    usage.cap = UsageCap {
+        max_calls_per_day: None,
+        max_chars_per_day: Some(1000),
+        warn_at_percent: 50,
+    };
+    let status = usage.cap_status(now());
+    assert_eq!((status.level, status.chars_today), (CapLevel::Warning, 700));
+}
@@ -0,0 +1,72 @@
+// Tests for user-defined find/replace rules: ordering, capture groups, toggles and dry runs.
+
+use refine_lib::{detect_content_type, ReplaceRule, ReplaceRulesStore};
+
+fn replace_rule(name: &str, pattern: &str, replacement: &str) -> ReplaceRule {
+    ReplaceRule {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+        replacement: replacement.to_string(),
+        ..ReplaceRule::default()
+    }
+}
+
+#[test]
+fn replace_rules_run_in_order_with_capture_groups() {
+    let mut store = ReplaceRulesStore::default();
+    store
+        .set_rules(vec![
+            ReplaceRule {
+                case_insensitive: true,
+                ..replace_rule("Product casing", r"\bgithub\b", "GitHub")
+            },
+            replace_rule("Ticket prefix", r"^\[?[A-Z]+-\d+\]?:?\s*", ""),
+            replace_rule(
+                "Swap names",
+                r"(?P<first>\w+) & (?P<second>\w+)",
+                "${second} & $first",
+            ),
+        ])
+        .unwrap();
+    assert!(store.rules.iter().all(|rule| !rule.id.is_empty()));
+
+    let text = "[OPS-12] push to GITHUB\nOPS-7: Ada & Alan use github";
+    let dry_run = store.dry_run(text, "text");
+    let counts: Vec<usize> = dry_run.rules.iter().map(|r| r.matches).collect();
+    assert_eq!(counts, vec![2, 2, 1]);
+    assert_eq!(dry_run.result, "push to GitHub\nAlan & Ada use GitHub");
+    assert_eq!(store.apply(text, "text"), dry_run.result);
+}
+
+#[test]
+fn replace_rules_respect_toggles_and_content_types() {
+    let mut store = ReplaceRulesStore::default();
+    store
+        .set_rules(vec![
+            ReplaceRule {
+                enabled: false,
+                ..replace_rule("Disabled", "a", "b")
+            },
+            ReplaceRule {
+                content_types: vec!["code".to_string()],
+                ..replace_rule("Code only", "var ", "let ")
+            },
+        ])
+        .unwrap();
+
+    let code = "var a = 1;\nvar b = 2;";
+    assert_eq!(detect_content_type(code), "code");
+    assert_eq!(store.apply(code, "code"), "let a = 1;\nlet b = 2;");
+    assert_eq!(store.apply("var a", "text"), "var a");
+
+    // Skipped rules still report how often they would match
+    let dry_run = store.dry_run("var a", "text");
+    assert_eq!(dry_run.rules[0].matches, 2);
+    assert!(!dry_run.rules[0].applies && !dry_run.rules[1].applies);
+
+    let error = store
+        .set_rules(vec![replace_rule("Broken", "(unclosed", "")])
+        .unwrap_err();
+    assert!(error.starts_with("Invalid pattern in rule 'Broken'"));
+    assert_eq!(store.rules.len(), 2);
+}
@@ -0,0 +1,40 @@
+// Tests for Rhai cleanup scripts: entry metadata, return types and sandbox limits.
+
+use refine_lib::{eval_script, ScriptEntry, ScriptLimits};
+
+#[test]
+fn scripts_transform_text_using_entry_metadata() {
+    let script = r#"
+        let out = "";
+        for line in text.split("\n") {
+            if out != "" { out += "\n"; }
+            out += entry.content_type + ": " + line.to_upper();
+        }
+        out
+    "#;
+    let text = "hello\nworld";
+    let entry = ScriptEntry::for_text(text);
+    assert_eq!(
+        eval_script(script, text, &entry, &ScriptLimits::default()).unwrap(),
+        "text: HELLO\ntext: WORLD"
+    );
+
+    let error = eval_script("42", text, &entry, &ScriptLimits::default()).unwrap_err();
+    assert_eq!(error, "Script must return a string, not i64");
+}
+
+#[test]
+fn scripts_are_sandboxed_and_limited() {
+    let entry = ScriptEntry::for_text("x");
+    let limits = ScriptLimits {
+        timeout_ms: 50,
+        max_string_size: 1024,
+        ..ScriptLimits::default()
+    };
+    let run = |script: &str| eval_script(script, "x", &entry, &limits).unwrap_err();
+
+    assert_eq!(run("loop {}"), "Script timed out after 50 ms");
+    assert!(run("let s = text; loop { s += s; }").starts_with("Script exceeded its memory limit"));
+    assert!(run(r#"import "secrets" as s; text"#).starts_with("Script error:"));
+    assert!(run(r#"eval("text")"#).starts_with("Script error:"));
+}
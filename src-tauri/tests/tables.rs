@@ -0,0 +1,138 @@
+// Tests for table conversion between CSV, TSV, Markdown, ASCII and JSON.
+
+use proptest::prelude::*;
+use refine_lib::{ConvertTable, Table, TableFormat, Transform};
+
+// Tables whose cells hold delimiters, quotes, pipes and line breaks
+fn table() -> impl Strategy<Value = Table> {
+    (2usize..5, any::<bool>()).prop_flat_map(|(columns, has_header)| {
+        let row = prop::collection::vec("[a-c ,;\t\"|\n]{0,6}", columns);
+        let header = if has_header {
+            prop::collection::vec("[a-c][a-c ,\"|]{0,4}[a-c]", columns)
+                // Distinct names, since JSON keys must be unique
+                .prop_map(|names| {
+                    let names = names.iter().enumerate();
+                    Some(names.map(|(i, name)| format!("{}{}", name, i)).collect())
+                })
+                .boxed()
+        } else {
+            Just(None).boxed()
+        };
+        (header, prop::collection::vec(row, 1..5)).prop_map(|(header, rows)| Table { header, rows })
+    })
+}
+
+proptest! {
+    #[test]
+    fn tables_round_trip(table in table()) {
+        let has_header = Some(table.header.is_some());
+        for format in [TableFormat::Csv, TableFormat::Tsv, TableFormat::Json] {
+            let text = table.to_format(format).unwrap();
+            prop_assert_eq!(Table::parse(&text, format, has_header).unwrap(), table.clone());
+        }
+
+        // Markdown trims cells and always writes a header row
+        let trim = |row: &Vec<String>| row.iter().map(|cell| cell.trim().to_string()).collect();
+        let trimmed = Table {
+            header: table.header.as_ref().map(trim),
+            rows: table.rows.iter().map(trim).collect(),
+        };
+        let markdown = trimmed.to_format(TableFormat::Markdown).unwrap();
+        prop_assert_eq!(
+            Table::parse(&markdown, TableFormat::Markdown, None).unwrap().rows,
+            trimmed.rows
+        );
+    }
+}
+
+#[test]
+fn spreadsheet_tsv_becomes_markdown_and_json() {
+    let tsv = "Name\tRole\tAge\nAda Lovelace\tadmin\t36\nAlan Turing\t\"on call\tweekends\"\t41\n";
+    assert_eq!(
+        ConvertTable::default().apply(tsv).unwrap(),
+        "| Name         | Role             | Age |\n\
+         | ------------ | ---------------- | --- |\n\
+         | Ada Lovelace | admin            | 36  |\n\
+         | Alan Turing  | on call\tweekends | 41  |"
+    );
+
+    let to_json = ConvertTable {
+        to: TableFormat::Json,
+        ..ConvertTable::default()
+    };
+    let json = to_json.apply(tsv).unwrap();
+    assert!(json.starts_with(
+        "[\n  {\n    \"Name\": \"Ada Lovelace\",\n    \"Role\": \"admin\",\n    \"Age\": 36\n  },"
+    ));
+    assert!(json.contains("\"Role\": \"on call\\tweekends\""));
+}
+
+#[test]
+fn csv_quoting_handles_embedded_delimiters_and_newlines() {
+    let csv = "id,note\n1,\"a, b\"\n2,\"two\nlines\"\n3,\"say \"\"hi\"\"\"\n";
+    let table = Table::parse(csv, TableFormat::Auto, None).unwrap();
+    assert_eq!(
+        table.header,
+        Some(vec!["id".to_string(), "note".to_string()])
+    );
+    assert_eq!(table.rows[0][1], "a, b");
+    assert_eq!(table.rows[1][1], "two\nlines");
+    assert_eq!(table.rows[2][1], "say \"hi\"");
+    assert_eq!(table.to_format(TableFormat::Csv).unwrap(), csv.trim_end());
+
+    let markdown = table.to_format(TableFormat::Markdown).unwrap();
+    assert!(markdown.contains("| two<br>lines |"));
+    let tsv = table.to_format(TableFormat::Tsv).unwrap();
+    assert!(tsv.contains("1\ta, b\n2\t\"two\nlines\""));
+
+    let error = Table::parse("a,b\n1,\"open\n", TableFormat::Csv, None).unwrap_err();
+    assert_eq!(error, "Unterminated quoted field starting on line 2");
+}
+
+#[test]
+fn markdown_and_ascii_tables_are_read_back() {
+    let markdown = "| a | b \\| c |\n|:--|--:|\n| 1 | x<br>y |";
+    let table = Table::parse(markdown, TableFormat::Auto, None).unwrap();
+    assert_eq!(
+        table.header,
+        Some(vec!["a".to_string(), "b | c".to_string()])
+    );
+    assert_eq!(table.rows, vec![vec!["1".to_string(), "x\ny".to_string()]]);
+
+    let ascii = table.to_format(TableFormat::Ascii).unwrap();
+    assert_eq!(
+        ascii,
+        "+---+-------+\n| a | b | c |\n+---+-------+\n| 1 | x y   |\n+---+-------+"
+    );
+
+    let psql = " id | name\n----+------\n  1 | Ada\n  2 | Alan\n(2 rows)";
+    let table = Table::parse(psql, TableFormat::Ascii, None).unwrap();
+    assert_eq!(
+        table.header,
+        Some(vec!["id".to_string(), "name".to_string()])
+    );
+    assert_eq!(table.rows.len(), 3);
+}
+
+#[test]
+fn header_detection_and_json_input() {
+    let numbers = Table::parse("1,2\n3,4", TableFormat::Auto, None).unwrap();
+    assert_eq!(numbers.header, None);
+    assert_eq!(
+        numbers.to_format(TableFormat::Markdown).unwrap(),
+        "| Column 1 | Column 2 |\n| -------- | -------- |\n| 1        | 2        |\n| 3        | 4        |"
+    );
+
+    let json = r#"[{"id": 1, "tags": ["x"]}, {"id": 2, "name": null, "ok": true}]"#;
+    let csv = ConvertTable {
+        to: TableFormat::Csv,
+        ..ConvertTable::default()
+    }
+    .apply(json)
+    .unwrap();
+    assert_eq!(csv, "id,tags,name,ok\n1,\"[\"\"x\"\"]\",,\n2,,,true");
+
+    assert!(ConvertTable::default()
+        .apply("just a sentence, with a comma")
+        .is_err());
+}
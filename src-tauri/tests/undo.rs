@@ -0,0 +1,44 @@
+// Tests for the bounded undo/redo stack of the app's clipboard writes.
+
+use refine_lib::{ClipboardChange, ClipboardUndoStack, MAX_UNDO_STEPS};
+
+#[test]
+fn clipboard_undo_is_bounded_and_redo_is_cleared_by_new_writes() {
+    let mut stack = ClipboardUndoStack::default();
+    stack.record(ClipboardChange::new(
+        "cleanup",
+        "same".into(),
+        "same".into(),
+    ));
+    assert!(!stack.status().can_undo);
+
+    for i in 0..MAX_UNDO_STEPS + 5 {
+        stack.record(ClipboardChange::new(
+            "paste",
+            i.to_string(),
+            (i + 1).to_string(),
+        ));
+    }
+    let mut undone = 0;
+    while stack.next_undo().is_some() {
+        let change = stack.finish_undo().unwrap();
+        assert!(stack.is_restored(&change.before));
+        undone += 1;
+    }
+    assert_eq!(undone, MAX_UNDO_STEPS);
+    assert!(stack.is_restored("5"));
+
+    let redone = stack.finish_redo().unwrap();
+    assert_eq!((redone.before.as_str(), redone.after.as_str()), ("5", "6"));
+    assert_eq!(stack.status().redo_label.as_deref(), Some("paste"));
+
+    stack.record(ClipboardChange::new("transform", "6".into(), "six".into()));
+    let status = stack.status();
+    assert!(!status.can_redo);
+    assert_eq!(status.undo_label.as_deref(), Some("transform"));
+    assert!(!stack.is_restored("6"));
+
+    stack.clear();
+    assert!(stack.next_undo().is_none());
+    assert!(!stack.status().can_redo);
+}
@@ -0,0 +1,40 @@
+// Tests for the Unicode scrub: invisible and bidi characters and the report on them.
+
+use refine_lib::UnicodeScrub;
+
+#[test]
+fn unicode_scrub_reports_bidi_controls() {
+    let text = "let x = \"a\u{202E}b\"; // \u{200B}note\u{00A0}here";
+    let (scrubbed, report) = UnicodeScrub::default().scrub(text);
+
+    assert_eq!(scrubbed, "let x = \"ab\"; // note here");
+    assert!(report.has_bidi_controls());
+    let bidi = &report.findings[0];
+    assert_eq!(bidi.code_point, "U+202E");
+    assert_eq!((bidi.line, bidi.column), (1, 11));
+    assert_eq!(report.findings.len(), 3);
+}
+
+#[test]
+fn unicode_scrub_removes_invisible_operators_and_line_separators() {
+    let text = "f\u{2061}(x) = 2\u{2062}x\u{2064}1\u{2063}\u{2028}next\u{2029}para";
+    let (scrubbed, report) = UnicodeScrub::default().scrub(text);
+
+    assert_eq!(scrubbed, "f(x) = 2x1\nnext\npara");
+    let code_points: Vec<&str> = report
+        .findings
+        .iter()
+        .map(|f| f.code_point.as_str())
+        .collect();
+    assert_eq!(
+        code_points,
+        ["U+2061", "U+2062", "U+2064", "U+2063", "U+2028", "U+2029"]
+    );
+}
+
+#[test]
+fn unicode_scrub_keeps_emoji_joiners() {
+    let (scrubbed, report) = UnicodeScrub::default().scrub("👩\u{200D}💻");
+    assert_eq!(scrubbed, "👩\u{200D}💻");
+    assert!(report.findings.is_empty());
+}
@@ -0,0 +1,57 @@
+// Tests for URL cleaning with tracking-parameter rules.
+
+use refine_lib::{CleanUrls, CleanupPresetStore, UrlRulesStore};
+
+#[test]
+fn copied_urls_are_only_cleaned_when_enabled() {
+    let link = "https://example.com/a?utm_source=mail&id=1";
+    let mut store = UrlRulesStore::default();
+    assert_eq!(store.clean_copied_text(link), None);
+
+    store.auto_clean = true;
+    assert_eq!(
+        store.clean_copied_text(link).as_deref(),
+        Some("https://example.com/a?id=1")
+    );
+    assert_eq!(
+        store.clean_copied_text("see https://example.com/a?utm_source=x"),
+        None
+    );
+
+    // Links are kept as written unless something is removed from them
+    assert_eq!(
+        store.clean_copied_text("HTTPS://Example.COM:443/a%7Eb?id=1"),
+        None
+    );
+    assert_eq!(
+        store
+            .clean_copied_text("HTTPS://Example.COM:443/a%7Eb?utm_source=x&id=1#top")
+            .as_deref(),
+        Some("HTTPS://Example.COM:443/a%7Eb?id=1#top")
+    );
+}
+
+#[test]
+fn custom_url_rules_match_by_prefix() {
+    let rules = CleanUrls {
+        strip_params: vec!["ref".to_string(), "pk_*".to_string()],
+        unwrap_redirects: false,
+    };
+    assert_eq!(
+        rules
+            .clean_url("https://shop.example/item?REF=x&pk_campaign=y&utm_source=z")
+            .as_deref(),
+        Some("https://shop.example/item?utm_source=z")
+    );
+
+    // Pipelines clean URLs with the saved rules
+    assert_eq!(
+        CleanupPresetStore::default()
+            .pipeline_for(Some("url"))
+            .unwrap()
+            .with_url_rules(&rules)
+            .run("https://shop.example/item?ref=x&utm_source=z\n")
+            .unwrap(),
+        "https://shop.example/item?utm_source=z"
+    );
+}
@@ -12,7 +12,6 @@ import {
   useNotification,
   useClipboardHistory,
   useTextProcessing,
-  useAutoRephrase,
   useClipboardMonitoring,
   useShortcutStatus,
   useAuth,
@@ -47,12 +46,6 @@ function App() {
   // Sync JWT tokens from auth service to API client
   useAuthTokenSync();
 
-  // Auto-rephrase functionality (triggered by Cmd+Shift+C global shortcut)
-  const { setupAutoRephraseListener } = useAutoRephrase({
-    showNotification,
-    setShortcutStatus,
-  });
-
   // Clipboard monitoring functionality (event-driven via Cmd+Shift+C global shortcut)
   const { setupClipboardMonitoring } = useClipboardMonitoring({
     setCleanedText,
@@ -87,19 +80,11 @@ function App() {
   // Setup event listeners and global shortcut
   useEffect(() => {
     let mounted = true;
-    let unlistenAutoRephrase: (() => void) | undefined;
     let cleanupClipboardMonitoring: (() => void) | undefined;
 
     // Load clipboard history on mount
     loadClipboardHistory();
 
-    // Setup auto-rephrase listener
-    setupAutoRephraseListener().then((unlisten) => {
-      if (mounted && unlisten) {
-        unlistenAutoRephrase = unlisten;
-      }
-    });
-
     // Setup clipboard monitoring
     setupClipboardMonitoring().then((cleanup) => {
       if (mounted && cleanup) {
@@ -109,12 +94,10 @@ function App() {
 
     return () => {
       mounted = false;
-      if (unlistenAutoRephrase) unlistenAutoRephrase();
       if (cleanupClipboardMonitoring) cleanupClipboardMonitoring();
     };
   }, [
     loadClipboardHistory,
-    setupAutoRephraseListener,
     setupClipboardMonitoring,
   ]);
 
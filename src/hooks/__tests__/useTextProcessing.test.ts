@@ -160,7 +160,7 @@ describe('useTextProcessing', () => {
 
       expect(mockWriteText).toHaveBeenCalledWith(rephrasedText);
       expect(result.current.rephrasedText).toBe(rephrasedText);
-      // Note: Notification is now handled by useAutoRephrase hook to prevent duplicates
+      // Note: No notification here; the backend already shows one for the rephrase
       expect(mockShowNotification).not.toHaveBeenCalledWith(
         'Rephrased text copied to clipboard ✅',
         'success'
@@ -1,7 +1,6 @@
 export { useNotification } from './useNotification';
 export { useClipboardHistory } from './useClipboardHistory';
 export { useTextProcessing } from './useTextProcessing';
-export { useAutoRephrase } from './useAutoRephrase';
 export { useManualRephrase } from './useManualRephrase';
 export { useClipboardMonitoring } from './useClipboardMonitoring';
 export { useShortcutStatus } from './useShortcutStatus';
@@ -25,7 +25,7 @@ export const useClipboardMonitoring = ({
       const { listen } = await import("@tauri-apps/api/event");
 
       // Listen for clipboard updates from the global shortcut (Cmd+Shift+C)
-      // Note: Only handle UI updates here, API calls are handled by useAutoRephrase
+      // Note: Only handle UI updates here, rephrasing is done by the backend
       const unlistenGlobalShortcut = await listen("clipboard-updated", async (event) => {
         const cleanedText = event.payload as string;
         console.log(
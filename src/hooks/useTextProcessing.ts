@@ -45,7 +45,7 @@ export const useTextProcessing = (
       setRephrasedText(response.rephrased_text);
       lastValueRef.current = response.rephrased_text;
 
-      // Note: Notification is handled by useAutoRephrase hook to prevent duplicates
+      // Note: No notification here; the backend already shows one for the rephrase
 
       // Reload clipboard history to show new entry
       await loadClipboardHistory();